@@ -1,9 +1,28 @@
 use reqwest;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use once_cell::sync::Lazy;
+use std::time::Duration;
+use tokio::{sync::Mutex, time::Instant};
+
+/// TMDb API 地址，请求这个地址时受速率限制
+const TMDB_API_URL: &str = "https://api.themoviedb.org/";
+/// 两次 TMDb 请求之间的最小间隔（约每秒 20 次），并发扫描时避免触发 TMDb 的速率限制
+const TMDB_REQUEST_INTERVAL: Duration = Duration::from_millis(50);
+/// 下一个 TMDb 请求最早可以发出的时间
+static NEXT_TMDB_REQUEST: Lazy<Mutex<Instant>> = Lazy::new(|| Mutex::new(Instant::now()));
+
+/// 等待到可以发出下一个 TMDb 请求，并发的请求依次排队
+async fn wait_for_tmdb_rate_limit() {
+    let mut next = NEXT_TMDB_REQUEST.lock().await;
+    tokio::time::sleep_until(*next).await;
+    *next = Instant::now() + TMDB_REQUEST_INTERVAL;
+}
 
 /// 发送 GET 请求获取数据
 /// 
+/// 请求 TMDb API 时遵守速率限制，仍然被限流（HTTP 429）时按 Retry-After 等待后重试一次
+/// 
 /// # 参数
 /// * `url` - 请求的目标 URL
 /// 
@@ -16,7 +35,21 @@ use serde_json::Value;
 /// println!("Response: {}", response);
 /// ```
 pub async fn get_data(url: &str) -> Result<String, reqwest::Error> {
-    let response = reqwest::get(url).await?;
+    let is_tmdb = url.starts_with(TMDB_API_URL);
+    if is_tmdb {
+        wait_for_tmdb_rate_limit().await;
+    }
+    let mut response = reqwest::get(url).await?;
+    if is_tmdb && response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let retry_after = response.headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(1);
+        tokio::time::sleep(Duration::from_secs(retry_after)).await;
+        wait_for_tmdb_rate_limit().await;
+        response = reqwest::get(url).await?;
+    }
     let body = response.text().await?;
     Ok(body)
 }
@@ -1,6 +1,7 @@
 use crate::{log_info, log_debug};
 
 use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::collections::HashMap;
 use std::fs;
 use std::sync::{Mutex, Arc};
 use tauri::{AppHandle, Manager};
@@ -47,8 +48,263 @@ pub struct VideoInfo {
     pub episode_title: String,
     /// 剧集简介
     pub episode_overview: String,
+    /// 文件大小（字节），用于重新扫描时判断文件是否变化
+    #[serde(default)]
+    pub file_size: i64,
+    /// 文件修改时间（Unix时间戳）
+    #[serde(default)]
+    pub mtime: i64,
+    /// 内容指纹：文件大小加头、中、尾采样块的哈希，用于识别移动或改名的文件
+    #[serde(default)]
+    pub fingerprint: String,
+    /// 文件状态：ok 正常 / missing 文件已删除 / offline 所在磁盘或网络位置不可用
+    #[serde(default = "default_status")]
+    pub status: String,
+    /// 所属资料库ID，不属于任何资料库时为 0
+    #[serde(default)]
+    pub library_id: i64,
+    /// 多段视频中非第一段所属的主视频（第一段）ID，其他视频为空
+    #[serde(default)]
+    pub part_of: String,
+    /// 多段视频中的分段序号，从 1 开始，其他视频为 0
+    #[serde(default)]
+    pub part_number: i32,
+    /// 多集合一的文件（如 `S01E01-E03`）中最后一集的集数，单集文件为 0
+    #[serde(default)]
+    pub episode_end: i32,
+    /// 按播出日期命名的剧集（如 `2024.03.15`）的播出日期，格式为 `YYYY-MM-DD`，其他视频为空
+    #[serde(default)]
+    pub air_date: String,
+    /// 多段视频的各段（包括主视频本身），按序号排列，只在主视频中返回，不单独存储
+    #[serde(default)]
+    pub parts: Vec<VideoPart>,
 }
 
+/// 多段视频中的一段，如 `Movie.CD1.avi`、`Movie.CD2.avi`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoPart {
+    /// 该段的视频ID
+    pub id: String,
+    /// 文件路径
+    pub path: String,
+    /// 该段的时长
+    pub duration: String,
+    /// 分段序号
+    pub part_number: i32,
+    /// 文件状态
+    pub status: String,
+}
+
+fn default_status() -> String {
+    STATUS_OK.to_string()
+}
+
+/// 文件正常
+pub const STATUS_OK: &str = "ok";
+/// 文件所在的磁盘或网络位置可以访问，但文件已不存在
+pub const STATUS_MISSING: &str = "missing";
+/// 文件所在的磁盘未挂载或网络位置不可用
+pub const STATUS_OFFLINE: &str = "offline";
+
+/// 视频轨道信息
+/// 
+/// 扫描时从容器元数据中解析，按视频ID存储
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoTrack {
+    /// 所属视频ID
+    pub video_id: String,
+    /// 轨道在文件中的顺序
+    pub track_index: i32,
+    /// 轨道类型：video / audio / subtitle / other
+    pub track_type: String,
+    /// 容器内的原始编码标识
+    pub codec: String,
+    /// 编码名称，如 HEVC、AAC
+    pub codec_name: String,
+    /// 语言代码
+    pub language: String,
+    /// BCP 47 语言标签，如 zh-Hans、pt-BR，容器未提供时为空
+    pub language_ietf: String,
+    /// 轨道名称
+    pub name: String,
+    /// 是否为默认轨道
+    pub is_default: bool,
+    /// 是否为强制轨道
+    pub is_forced: bool,
+    /// 视频宽度
+    pub width: u32,
+    /// 视频高度
+    pub height: u32,
+    /// 音频声道数
+    pub channels: u32,
+    /// 音频采样率
+    pub sampling_frequency: f64,
+}
+
+/// 视频章节信息
+/// 
+/// 扫描时从 MKV 的 Chapters 元素中解析，按视频ID存储
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoChapter {
+    /// 所属视频ID
+    pub video_id: String,
+    /// 章节顺序
+    pub chapter_index: i32,
+    /// 开始时间（秒）
+    pub start_time: f64,
+    /// 结束时间（秒）
+    pub end_time: f64,
+    /// 默认章节标题
+    pub title: String,
+    /// 本地化章节标题，键为语言代码
+    pub titles: HashMap<String, String>,
+}
+
+/// 视频画质概况
+/// 
+/// 由视频轨道的分辨率、位深和 HDR 信息生成，用于按画质筛选和排序
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoQuality {
+    /// 所属视频ID
+    pub video_id: String,
+    /// 清晰度分类，如 2160p、1080p
+    pub resolution: String,
+    /// 视频宽度
+    pub width: u32,
+    /// 视频高度
+    pub height: u32,
+    /// 位深，未知时为 0
+    pub bit_depth: u32,
+    /// HDR 格式：SDR / HDR10 / HLG / Dolby Vision
+    pub hdr_format: String,
+    /// Dolby Vision profile，非 Dolby Vision 时为 0
+    pub dolby_vision_profile: u32,
+}
+
+/// 视频的发布信息
+/// 
+/// 由文件名（如 `Movie.2010.2160p.UHD.BluRay.x265.HDR.DTS-HD.MA.5.1-GROUP.mkv`）解析，
+/// 用于显示、筛选和在同一部影片的多个版本中选择最好的一个
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct VideoRelease {
+    /// 所属视频ID
+    pub video_id: String,
+    /// 文件名中的标题
+    pub title: String,
+    /// 文件名中的年份，没有时为空
+    pub year: String,
+    /// 文件名标注的清晰度，如 2160p、1080p
+    pub resolution: String,
+    /// 片源，如 BluRay、Remux、WEB-DL、HDTV
+    pub source: String,
+    /// 视频编码，如 H.264、H.265、AV1
+    pub video_codec: String,
+    /// 音频编码，如 DTS-HD MA、TrueHD Atmos、AAC
+    pub audio_codec: String,
+    /// 文件名标注了 HDR 或 Dolby Vision
+    pub hdr: bool,
+    /// 版本，如 Extended、Director's Cut
+    pub edition: String,
+    /// 发布组
+    pub release_group: String,
+    /// 是否为 REPACK / PROPER 修正版本
+    pub repack: bool,
+}
+
+/// 视频的附加内容，如预告片、花絮、删减片段
+/// 
+/// 按文件名后缀（如 `Movie-trailer.mkv`）或所在文件夹（如 `Featurettes`）识别，不作为独立的视频入库
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VideoExtra {
+    /// 附加内容ID，使用文件路径的MD5值
+    pub id: String,
+    /// 所属视频ID，尚未找到所属视频时为空
+    pub video_id: String,
+    /// 类型：trailer / featurette / behindthescenes / deleted / interview / scene / short / other
+    pub extra_type: String,
+    /// 标题，默认使用文件名
+    pub title: String,
+    /// 文件路径
+    pub path: String,
+    /// 时长
+    pub duration: String,
+    /// 文件大小（字节）
+    pub file_size: i64,
+    /// 文件修改时间（Unix时间戳）
+    pub mtime: i64,
+    /// 创建时间（Unix时间戳）
+    pub create_time: i64,
+}
+
+/// 资料库根目录
+/// 
+/// 添加后会被扫描并持续监听，目录中新增、移动和删除的视频会自动同步
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Library {
+    /// 资料库ID
+    pub id: i64,
+    /// 根目录路径
+    pub path: String,
+    /// 显示名称，默认使用目录名
+    pub name: String,
+    /// 内容类型：movie 电影 / series 剧集 / mixed 混合
+    pub content_type: String,
+    /// 获取 TMDb 信息时使用的语言，如 zh-CN、en-US，为空时使用默认语言
+    pub language: String,
+    /// 是否启用，停用的资料库不监听也不扫描
+    pub enabled: bool,
+    /// 最后扫描时间（Unix时间戳），未扫描过时为 0
+    pub last_scan_time: i64,
+    /// 添加时间（Unix时间戳）
+    pub create_time: i64,
+    /// 扫描时包含和排除文件的规则
+    #[serde(default)]
+    pub rules: ScanRules,
+}
+
+/// 资料库的扫描规则
+/// 
+/// 在遍历目录时判断，被排除的目录不会进入，被排除的文件不会被读取
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ScanRules {
+    /// 只入库匹配任一模式的文件，为空时不限制。模式为相对资料库根目录的 glob，`re:` 开头的为正则表达式
+    pub include_patterns: Vec<String>,
+    /// 排除匹配任一模式的文件和目录，如 `**/Extras/**`、`*.partial.mkv`
+    pub exclude_patterns: Vec<String>,
+    /// 最小文件大小（字节），更小的文件不入库，为 0 时不限制
+    pub min_file_size: u64,
+    /// 文件相对根目录的最大层级，1 表示只扫描根目录下的文件，为 0 时不限制
+    pub max_depth: u32,
+    /// 跳过以 `.` 开头的隐藏目录和文件
+    pub skip_hidden: bool,
+    /// 跟随符号链接进入其指向的目录和文件
+    pub follow_symlinks: bool,
+    /// 跳过文件名包含 sample 的示例视频
+    pub skip_samples: bool,
+}
+
+impl Default for ScanRules {
+    fn default() -> Self {
+        ScanRules {
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            min_file_size: 0,
+            max_depth: 0,
+            skip_hidden: true,
+            follow_symlinks: false,
+            skip_samples: true,
+        }
+    }
+}
+
+/// 电影资料库，文件名即使像剧集也按电影处理
+pub const CONTENT_MOVIE: &str = "movie";
+/// 剧集资料库
+pub const CONTENT_SERIES: &str = "series";
+/// 混合资料库，按文件名判断是电影还是剧集
+pub const CONTENT_MIXED: &str = "mixed";
+
 /// 数据库连接状态
 /// 
 /// 使用互斥锁包装SQLite连接，确保线程安全
@@ -77,6 +333,18 @@ pub fn init_db(app_handle: &AppHandle) -> Result<Connection> {
     let db_path = app_dir.join("videos.db");
     
     let conn = Connection::open(db_path)?;
+    create_tables(&conn)?;
+    Ok(conn)
+}
+
+/// 创建所有表，并为旧版本数据库补充新增的列
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// 
+/// # 返回
+/// * `Result<()>` - 成功返回Ok(()), 失败返回错误
+pub(crate) fn create_tables(conn: &Connection) -> Result<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS videos (
             id TEXT PRIMARY KEY,
@@ -96,12 +364,164 @@ pub fn init_db(app_handle: &AppHandle) -> Result<Connection> {
             season INTEGER NOT NULL DEFAULT 1,
             episode INTEGER NOT NULL DEFAULT 1,
             episode_title TEXT,
-            episode_overview TEXT
+            episode_overview TEXT,
+            file_size INTEGER NOT NULL DEFAULT 0,
+            mtime INTEGER NOT NULL DEFAULT 0,
+            fingerprint TEXT NOT NULL DEFAULT '',
+            status TEXT NOT NULL DEFAULT 'ok',
+            library_id INTEGER NOT NULL DEFAULT 0,
+            part_of TEXT NOT NULL DEFAULT '',
+            part_number INTEGER NOT NULL DEFAULT 0,
+            episode_end INTEGER NOT NULL DEFAULT 0,
+            air_date TEXT NOT NULL DEFAULT '',
+            tracks_probed BOOLEAN NOT NULL DEFAULT 0,
+            chapters_probed BOOLEAN NOT NULL DEFAULT 0
         )",
         [],
     )?;
-    
-    Ok(conn)
+    // 旧版本数据库没有这些列
+    add_column_if_missing(conn, "videos", "file_size", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "videos", "mtime", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "videos", "fingerprint", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "videos", "status", "TEXT NOT NULL DEFAULT 'ok'")?;
+    add_column_if_missing(conn, "videos", "library_id", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "videos", "part_of", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "videos", "part_number", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "videos", "episode_end", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "videos", "air_date", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "videos", "tracks_probed", "BOOLEAN NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "videos", "chapters_probed", "BOOLEAN NOT NULL DEFAULT 0")?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS video_tracks (
+            video_id TEXT NOT NULL,
+            track_index INTEGER NOT NULL,
+            track_type TEXT NOT NULL,
+            codec TEXT,
+            codec_name TEXT,
+            language TEXT,
+            language_ietf TEXT NOT NULL DEFAULT '',
+            name TEXT,
+            is_default BOOLEAN NOT NULL DEFAULT 0,
+            is_forced BOOLEAN NOT NULL DEFAULT 0,
+            width INTEGER NOT NULL DEFAULT 0,
+            height INTEGER NOT NULL DEFAULT 0,
+            channels INTEGER NOT NULL DEFAULT 0,
+            sampling_frequency REAL NOT NULL DEFAULT 0,
+            PRIMARY KEY (video_id, track_index)
+        )",
+        [],
+    )?;
+    add_column_if_missing(conn, "video_tracks", "language_ietf", "TEXT NOT NULL DEFAULT ''")?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS video_chapters (
+            video_id TEXT NOT NULL,
+            chapter_index INTEGER NOT NULL,
+            start_time REAL NOT NULL,
+            end_time REAL NOT NULL,
+            title TEXT,
+            titles TEXT,
+            PRIMARY KEY (video_id, chapter_index)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS video_quality (
+            video_id TEXT PRIMARY KEY,
+            resolution TEXT,
+            width INTEGER NOT NULL DEFAULT 0,
+            height INTEGER NOT NULL DEFAULT 0,
+            bit_depth INTEGER NOT NULL DEFAULT 0,
+            hdr_format TEXT NOT NULL DEFAULT 'SDR',
+            dolby_vision_profile INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS video_release (
+            video_id TEXT PRIMARY KEY,
+            title TEXT NOT NULL DEFAULT '',
+            year TEXT NOT NULL DEFAULT '',
+            resolution TEXT NOT NULL DEFAULT '',
+            source TEXT NOT NULL DEFAULT '',
+            video_codec TEXT NOT NULL DEFAULT '',
+            audio_codec TEXT NOT NULL DEFAULT '',
+            hdr INTEGER NOT NULL DEFAULT 0,
+            edition TEXT NOT NULL DEFAULT '',
+            release_group TEXT NOT NULL DEFAULT '',
+            repack INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS video_extras (
+            id TEXT PRIMARY KEY,
+            video_id TEXT NOT NULL DEFAULT '',
+            extra_type TEXT NOT NULL,
+            title TEXT NOT NULL DEFAULT '',
+            path TEXT NOT NULL,
+            duration TEXT NOT NULL DEFAULT '',
+            file_size INTEGER NOT NULL DEFAULT 0,
+            mtime INTEGER NOT NULL DEFAULT 0,
+            create_time INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS libraries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT NOT NULL UNIQUE,
+            name TEXT NOT NULL DEFAULT '',
+            content_type TEXT NOT NULL DEFAULT 'mixed',
+            language TEXT NOT NULL DEFAULT '',
+            enabled BOOLEAN NOT NULL DEFAULT 1,
+            last_scan_time INTEGER NOT NULL DEFAULT 0,
+            create_time INTEGER NOT NULL,
+            include_patterns TEXT NOT NULL DEFAULT '',
+            exclude_patterns TEXT NOT NULL DEFAULT '',
+            min_file_size INTEGER NOT NULL DEFAULT 0,
+            max_depth INTEGER NOT NULL DEFAULT 0,
+            skip_hidden BOOLEAN NOT NULL DEFAULT 1,
+            follow_symlinks BOOLEAN NOT NULL DEFAULT 0,
+            skip_samples BOOLEAN NOT NULL DEFAULT 1
+        )",
+        [],
+    )?;
+    add_column_if_missing(conn, "libraries", "name", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "libraries", "content_type", "TEXT NOT NULL DEFAULT 'mixed'")?;
+    add_column_if_missing(conn, "libraries", "language", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "libraries", "enabled", "BOOLEAN NOT NULL DEFAULT 1")?;
+    add_column_if_missing(conn, "libraries", "last_scan_time", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "libraries", "include_patterns", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "libraries", "exclude_patterns", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "libraries", "min_file_size", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "libraries", "max_depth", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "libraries", "skip_hidden", "BOOLEAN NOT NULL DEFAULT 1")?;
+    add_column_if_missing(conn, "libraries", "follow_symlinks", "BOOLEAN NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "libraries", "skip_samples", "BOOLEAN NOT NULL DEFAULT 1")?;
+    Ok(())
+}
+
+/// 为已有的表补充新增的列，列已存在时不做任何操作
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `table` - 表名
+/// * `column` - 列名
+/// * `definition` - 列定义，如 `INTEGER NOT NULL DEFAULT 0`
+/// 
+/// # 返回
+/// * `Result<()>` - 成功返回Ok(()), 失败返回错误
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt.query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .any(|name| name == column);
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+        log_info!("Added column {}.{}", table, column);
+    }
+    Ok(())
 }
 
 /// 通用执行查询方法
@@ -133,8 +553,9 @@ pub fn insert_video(conn: &Connection, video: &VideoInfo) -> Result<(), rusqlite
         "INSERT INTO videos (
             id, original_title, title, thumbnail, duration, path, category, description,
             create_time, last_play_time, play_count, favorite, tags,
-            is_series, season, episode, episode_title, episode_overview
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+            is_series, season, episode, episode_title, episode_overview, file_size, mtime, fingerprint, status, library_id,
+            part_of, part_number, episode_end, air_date
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27)",
         params![
             video.id,
             video.original_title,
@@ -153,75 +574,275 @@ pub fn insert_video(conn: &Connection, video: &VideoInfo) -> Result<(), rusqlite
             video.season,
             video.episode,
             video.episode_title,
-            video.episode_overview
+            video.episode_overview,
+            video.file_size,
+            video.mtime,
+            video.fingerprint,
+            video.status,
+            video.library_id,
+            video.part_of,
+            video.part_number,
+            video.episode_end,
+            video.air_date
         ],
     )?;
     log_debug!("Inserted video: {:?}", video);
     Ok(())
 }
 
-/// 检查视频是否存在
+/// 获取所有视频
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// 
+/// # 返回
+/// * `Result<Vec<VideoInfo>, rusqlite::Error>` - 成功返回视频列表，失败返回错误
+pub fn get_all_videos(conn: &Connection) -> Result<Vec<VideoInfo>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT * FROM videos ORDER BY title ASC"
+    )?;
+
+    let videos = stmt.query_map([], video_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(videos)
+}
+
+/// 根据ID获取单个视频
 /// 
 /// # 参数
 /// * `conn` - 数据库连接
 /// * `id` - 视频ID
 /// 
 /// # 返回
-/// * `bool` - 存在返回true，不存在返回false
-pub fn video_exists(conn: &Connection, id: &str) -> bool {
-    // conn.query_row("SELECT 1 FROM videos WHERE id = ?1", params![id], |_| Ok(true)).unwrap_or(false);
-    fetch_single_row(&conn, "SELECT 1 FROM videos WHERE id = ?", &[&id], |_| Ok(()))
-        .map(|opt| opt.is_some())
-        .unwrap_or(false) // 查询是否存在记录
+/// * `Result<Option<VideoInfo>, rusqlite::Error>` - 成功返回视频信息，不存在时返回None
+pub fn get_video(conn: &Connection, id: &str) -> Result<Option<VideoInfo>, rusqlite::Error> {
+    fetch_single_row(conn, "SELECT * FROM videos WHERE id = ?", &[&id], video_from_row)
 }
 
-/// 获取所有视频
+/// 将 `SELECT * FROM videos` 的一行转换为视频信息
+fn video_from_row(row: &rusqlite::Row) -> Result<VideoInfo, rusqlite::Error> {
+    Ok(VideoInfo {
+        id: row.get(0)?,
+        original_title: row.get(1)?,
+        title: row.get(2)?,
+        thumbnail: row.get(3)?,
+        duration: row.get(4)?,
+        path: row.get(5)?,
+        category: row.get(6)?,
+        description: row.get(7)?,
+        create_time: row.get(8)?,
+        last_play_time: row.get(9)?,
+        play_count: row.get(10)?,
+        favorite: row.get(11)?,
+        tags: row.get(12)?,
+        is_series: row.get(13)?,
+        season: row.get(14)?,
+        episode: row.get(15)?,
+        episode_title: row.get(16)?,
+        episode_overview: row.get(17)?,
+        file_size: row.get(18)?,
+        mtime: row.get(19)?,
+        fingerprint: row.get(20)?,
+        status: row.get(21)?,
+        library_id: row.get(22)?,
+        part_of: row.get(23)?,
+        part_number: row.get(24)?,
+        episode_end: row.get(25)?,
+        air_date: row.get(26)?,
+        parts: Vec::new(),
+    })
+}
+
+/// 获取视频记录中保存的文件大小和修改时间
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `id` - 视频ID
+/// 
+/// # 返回
+/// * `Result<Option<(i64, i64)>, rusqlite::Error>` - 成功返回 (文件大小, 修改时间)，视频不存在时返回None
+pub fn get_video_file_state(conn: &Connection, id: &str) -> Result<Option<(i64, i64)>, rusqlite::Error> {
+    fetch_single_row(conn, "SELECT file_size, mtime FROM videos WHERE id = ?", &[&id], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })
+}
+
+/// 文件变化后更新视频的时长、文件大小、修改时间和内容指纹，保留标题、播放记录等信息
 /// 
 /// # 参数
 /// * `conn` - 数据库连接
+/// * `id` - 视频ID
+/// * `duration` - 格式化后的时长
+/// * `file_size` - 文件大小（字节）
+/// * `mtime` - 文件修改时间（Unix时间戳）
+/// * `fingerprint` - 内容指纹
 /// 
 /// # 返回
-/// * `Result<Vec<VideoInfo>, rusqlite::Error>` - 成功返回视频列表，失败返回错误
-pub fn get_all_videos(conn: &Connection) -> Result<Vec<VideoInfo>, rusqlite::Error> {
-    let mut stmt = conn.prepare(
-        "SELECT * FROM videos ORDER BY title ASC"
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn update_video_file(conn: &Connection, id: &str, duration: &str, file_size: i64, mtime: i64, fingerprint: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE videos SET duration = ?1, file_size = ?2, mtime = ?3, fingerprint = ?4, status = 'ok' WHERE id = ?5",
+        params![duration, file_size, mtime, fingerprint, id],
     )?;
+    log_debug!("Updated file state for video: {}", id);
+    Ok(())
+}
 
-    let videos = stmt.query_map([], |row| {
-        Ok(VideoInfo {
-            id: row.get(0)?,
-            original_title: row.get(1)?,
-            title: row.get(2)?,
-            thumbnail: row.get(3)?,
-            duration: row.get(4)?,
-            path: row.get(5)?,
-            category: row.get(6)?,
-            description: row.get(7)?,
-            create_time: row.get(8)?,
-            last_play_time: row.get(9)?,
-            play_count: row.get(10)?,
-            favorite: row.get(11)?,
-            tags: row.get(12)?,
-            is_series: row.get(13)?,
-            season: row.get(14)?,
-            episode: row.get(15)?,
-            episode_title: row.get(16)?,
-            episode_overview: row.get(17)?,
-        })
-    })?
-    .collect::<Result<Vec<_>, _>>()?;
+/// 批量更新视频的文件状态
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `statuses` - (视频ID, 文件状态) 列表
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn update_video_statuses(conn: &mut Connection, statuses: &[(String, String)]) -> Result<(), rusqlite::Error> {
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare("UPDATE videos SET status = ?1 WHERE id = ?2")?;
+        for (id, status) in statuses {
+            stmt.execute(params![status, id])?;
+        }
+    }
+    tx.commit()
+}
 
+/// 删除所有已确认文件被删除（状态为 missing）的视频，离线的视频不受影响
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// 
+/// # 返回
+/// * `Result<Vec<String>, rusqlite::Error>` - 成功返回被删除的视频ID列表，失败返回错误
+pub fn delete_missing_videos(conn: &mut Connection) -> Result<Vec<String>, rusqlite::Error> {
+    let tx = conn.transaction()?;
+    let ids = {
+        let mut stmt = tx.prepare("SELECT id FROM videos WHERE status = ?1")?;
+        let ids = stmt.query_map(params![STATUS_MISSING], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        ids
+    };
+    for id in &ids {
+        delete_video(&tx, id)?;
+    }
+    tx.commit()?;
+    log_info!("Purged {} missing videos", ids.len());
+    Ok(ids)
+}
+
+/// 按内容指纹查找视频
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `fingerprint` - 内容指纹
+/// 
+/// # 返回
+/// * `Result<Vec<VideoInfo>, rusqlite::Error>` - 成功返回指纹相同的视频列表，失败返回错误
+pub fn find_videos_by_fingerprint(conn: &Connection, fingerprint: &str) -> Result<Vec<VideoInfo>, rusqlite::Error> {
+    if fingerprint.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare("SELECT * FROM videos WHERE fingerprint = ?1")?;
+    let videos = stmt.query_map(params![fingerprint], video_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
     Ok(videos)
 }
 
+/// 将视频记录重新关联到移动或改名后的文件
+/// 
+/// 视频ID由路径生成，路径变化后ID也随之变化，这里在一个事务中同时更新
+/// 视频记录和各个附属表中的视频ID，保留播放记录、收藏和标签
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `old_id` - 原视频ID
+/// * `video` - 新的视频信息，使用其中的ID、路径、缩略图、文件大小、修改时间和所属资料库
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn relink_video(conn: &mut Connection, old_id: &str, video: &VideoInfo) -> Result<(), rusqlite::Error> {
+    let tx = conn.transaction()?;
+    tx.execute(
+        "UPDATE videos SET id = ?1, path = ?2, thumbnail = ?3, file_size = ?4, mtime = ?5, library_id = ?6, status = 'ok' WHERE id = ?7",
+        params![video.id, video.path, video.thumbnail, video.file_size, video.mtime, video.library_id, old_id],
+    )?;
+    for table in ["video_tracks", "video_chapters", "video_quality", "video_release", "video_extras"] {
+        tx.execute(
+            &format!("UPDATE {} SET video_id = ?1 WHERE video_id = ?2", table),
+            params![video.id, old_id],
+        )?;
+    }
+    // 多段视频的主视频被移动时，其他段跟随新的ID
+    tx.execute("UPDATE videos SET part_of = ?1 WHERE part_of = ?2", params![video.id, old_id])?;
+    tx.commit()?;
+    log_info!("Relinked video {} -> {}: {}", old_id, video.id, video.path);
+    Ok(())
+}
+
 pub fn delete_video(conn: &Connection, id: &str) -> Result<(), rusqlite::Error> {
     conn.execute(
         "DELETE FROM videos WHERE id = ?1",
         params![id],
     )?;
+    conn.execute(
+        "DELETE FROM video_tracks WHERE video_id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "DELETE FROM video_chapters WHERE video_id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "DELETE FROM video_quality WHERE video_id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "DELETE FROM video_release WHERE video_id = ?1",
+        params![id],
+    )?;
+    // 附加内容文件仍然存在，只取消关联，下次扫描时重新关联到其他视频
+    conn.execute(
+        "UPDATE video_extras SET video_id = '' WHERE video_id = ?1",
+        params![id],
+    )?;
     Ok(())
 }
 
+/// 获取多段视频中除主视频外的其他段，按分段序号排列
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `id` - 主视频ID
+/// 
+/// # 返回
+/// * `Result<Vec<VideoInfo>, rusqlite::Error>` - 成功返回其他段的视频列表，不是多段视频时为空
+pub fn get_video_parts(conn: &Connection, id: &str) -> Result<Vec<VideoInfo>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT * FROM videos WHERE part_of = ?1 ORDER BY part_number")?;
+    let videos = stmt.query_map(params![id], video_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(videos)
+}
+
+/// 批量更新视频的多段分组
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `parts` - (视频ID, 主视频ID, 分段序号) 列表，主视频ID为空表示主视频或独立视频
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn update_video_parts(conn: &mut Connection, parts: &[(String, String, i32)]) -> Result<(), rusqlite::Error> {
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare("UPDATE videos SET part_of = ?1, part_number = ?2 WHERE id = ?3")?;
+        for (id, part_of, part_number) in parts {
+            stmt.execute(params![part_of, part_number, id])?;
+        }
+    }
+    tx.commit()
+}
+
 pub fn update_video(conn: &Connection, video: &VideoInfo) -> Result<(), rusqlite::Error> {
     let sql = "
         UPDATE videos
@@ -240,6 +861,7 @@ pub fn update_video(conn: &Connection, video: &VideoInfo) -> Result<(), rusqlite
             is_series = COALESCE(:is_series, is_series),
             season = COALESCE(:season, season),
             episode = COALESCE(:episode, episode),
+            episode_end = COALESCE(:episode_end, episode_end),
             episode_title = COALESCE(:episode_title, episode_title),
             episode_overview = COALESCE(:episode_overview, episode_overview)
         WHERE id = :id;
@@ -252,7 +874,8 @@ pub fn update_video(conn: &Connection, video: &VideoInfo) -> Result<(), rusqlite
             ":original_title": video.original_title,
             ":title": video.title,
             ":thumbnail": video.thumbnail,
-            ":duration": video.duration,
+            // 多段视频返回的是各段时长之和，不覆盖主视频本身的时长
+            ":duration": if video.parts.is_empty() { Some(&video.duration) } else { None },
             ":path": video.path,
             ":category": video.category,
             ":description": video.description,
@@ -263,9 +886,691 @@ pub fn update_video(conn: &Connection, video: &VideoInfo) -> Result<(), rusqlite
             ":is_series": video.is_series,
             ":season": video.season,
             ":episode": video.episode,
+            ":episode_end": video.episode_end,
             ":episode_title": video.episode_title,
             ":episode_overview": video.episode_overview
         },
     )?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// 保存视频的轨道信息，会先清除该视频已有的轨道记录，并标记该视频已解析过轨道
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `video_id` - 视频ID
+/// * `tracks` - 轨道列表
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn save_video_tracks(conn: &Connection, video_id: &str, tracks: &[VideoTrack]) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM video_tracks WHERE video_id = ?1",
+        params![video_id],
+    )?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO video_tracks (
+            video_id, track_index, track_type, codec, codec_name, language, language_ietf, name,
+            is_default, is_forced, width, height, channels, sampling_frequency
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+    )?;
+    for track in tracks {
+        stmt.execute(params![
+            video_id,
+            track.track_index,
+            track.track_type,
+            track.codec,
+            track.codec_name,
+            track.language,
+            track.language_ietf,
+            track.name,
+            track.is_default,
+            track.is_forced,
+            track.width,
+            track.height,
+            track.channels,
+            track.sampling_frequency
+        ])?;
+    }
+    conn.execute("UPDATE videos SET tracks_probed = 1 WHERE id = ?1", params![video_id])?;
+    log_debug!("Saved {} tracks for video: {}", tracks.len(), video_id);
+    Ok(())
+}
+
+/// 判断视频是否已解析过轨道，早期扫描的视频没有轨道记录也没有该标记
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `video_id` - 视频ID
+/// 
+/// # 返回
+/// * `Result<bool, rusqlite::Error>` - 已解析过时返回true，即使没有任何轨道
+pub fn video_tracks_probed(conn: &Connection, video_id: &str) -> Result<bool, rusqlite::Error> {
+    Ok(fetch_single_row(conn, "SELECT tracks_probed FROM videos WHERE id = ?", &[&video_id], |row| row.get(0))?.unwrap_or(false))
+}
+
+/// 获取视频的轨道信息
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `video_id` - 视频ID
+/// 
+/// # 返回
+/// * `Result<Vec<VideoTrack>, rusqlite::Error>` - 成功返回按顺序排列的轨道列表，失败返回错误
+pub fn get_video_tracks(conn: &Connection, video_id: &str) -> Result<Vec<VideoTrack>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT video_id, track_index, track_type, codec, codec_name, language, language_ietf, name,
+            is_default, is_forced, width, height, channels, sampling_frequency
+        FROM video_tracks WHERE video_id = ?1 ORDER BY track_index ASC"
+    )?;
+
+    let tracks = stmt.query_map(params![video_id], |row| {
+        Ok(VideoTrack {
+            video_id: row.get(0)?,
+            track_index: row.get(1)?,
+            track_type: row.get(2)?,
+            codec: row.get(3)?,
+            codec_name: row.get(4)?,
+            language: row.get(5)?,
+            language_ietf: row.get(6)?,
+            name: row.get(7)?,
+            is_default: row.get(8)?,
+            is_forced: row.get(9)?,
+            width: row.get(10)?,
+            height: row.get(11)?,
+            channels: row.get(12)?,
+            sampling_frequency: row.get(13)?,
+        })
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(tracks)
+}
+
+/// 保存视频的章节信息，会先清除该视频已有的章节记录，并标记该视频已解析过章节
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `video_id` - 视频ID
+/// * `chapters` - 章节列表
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn save_video_chapters(conn: &Connection, video_id: &str, chapters: &[VideoChapter]) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM video_chapters WHERE video_id = ?1",
+        params![video_id],
+    )?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO video_chapters (
+            video_id, chapter_index, start_time, end_time, title, titles
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for chapter in chapters {
+        stmt.execute(params![
+            video_id,
+            chapter.chapter_index,
+            chapter.start_time,
+            chapter.end_time,
+            chapter.title,
+            serde_json::to_string(&chapter.titles).unwrap_or_default()
+        ])?;
+    }
+    conn.execute("UPDATE videos SET chapters_probed = 1 WHERE id = ?1", params![video_id])?;
+    log_debug!("Saved {} chapters for video: {}", chapters.len(), video_id);
+    Ok(())
+}
+
+/// 判断视频是否已解析过章节，大多数视频没有章节，解析过后不再重复读取文件
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `video_id` - 视频ID
+/// 
+/// # 返回
+/// * `Result<bool, rusqlite::Error>` - 已解析过时返回true，即使没有任何章节
+pub fn video_chapters_probed(conn: &Connection, video_id: &str) -> Result<bool, rusqlite::Error> {
+    Ok(fetch_single_row(conn, "SELECT chapters_probed FROM videos WHERE id = ?", &[&video_id], |row| row.get(0))?.unwrap_or(false))
+}
+
+/// 获取视频的章节信息
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `video_id` - 视频ID
+/// 
+/// # 返回
+/// * `Result<Vec<VideoChapter>, rusqlite::Error>` - 成功返回按开始时间排列的章节列表，失败返回错误
+pub fn get_video_chapters(conn: &Connection, video_id: &str) -> Result<Vec<VideoChapter>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT video_id, chapter_index, start_time, end_time, title, titles
+        FROM video_chapters WHERE video_id = ?1 ORDER BY chapter_index ASC"
+    )?;
+
+    let chapters = stmt.query_map(params![video_id], |row| {
+        let titles: Option<String> = row.get(5)?;
+        Ok(VideoChapter {
+            video_id: row.get(0)?,
+            chapter_index: row.get(1)?,
+            start_time: row.get(2)?,
+            end_time: row.get(3)?,
+            title: row.get(4)?,
+            titles: titles.and_then(|t| serde_json::from_str(&t).ok()).unwrap_or_default(),
+        })
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(chapters)
+}
+
+/// 保存视频的画质概况，已存在时覆盖
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `quality` - 画质概况
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn save_video_quality(conn: &Connection, quality: &VideoQuality) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR REPLACE INTO video_quality (
+            video_id, resolution, width, height, bit_depth, hdr_format, dolby_vision_profile
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            quality.video_id,
+            quality.resolution,
+            quality.width,
+            quality.height,
+            quality.bit_depth,
+            quality.hdr_format,
+            quality.dolby_vision_profile
+        ],
+    )?;
+    log_debug!("Saved quality for video: {:?}", quality);
+    Ok(())
+}
+
+/// 获取所有视频的画质概况
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// 
+/// # 返回
+/// * `Result<Vec<VideoQuality>, rusqlite::Error>` - 成功返回画质列表，失败返回错误
+pub fn get_all_video_qualities(conn: &Connection) -> Result<Vec<VideoQuality>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT video_id, resolution, width, height, bit_depth, hdr_format, dolby_vision_profile
+        FROM video_quality"
+    )?;
+
+    let qualities = stmt.query_map([], |row| {
+        Ok(VideoQuality {
+            video_id: row.get(0)?,
+            resolution: row.get(1)?,
+            width: row.get(2)?,
+            height: row.get(3)?,
+            bit_depth: row.get(4)?,
+            hdr_format: row.get(5)?,
+            dolby_vision_profile: row.get(6)?,
+        })
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(qualities)
+}
+
+/// 保存视频的发布信息，已存在时覆盖
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `release` - 发布信息
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn save_video_release(conn: &Connection, release: &VideoRelease) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR REPLACE INTO video_release (
+            video_id, title, year, resolution, source, video_codec, audio_codec, hdr, edition, release_group, repack
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            release.video_id,
+            release.title,
+            release.year,
+            release.resolution,
+            release.source,
+            release.video_codec,
+            release.audio_codec,
+            release.hdr,
+            release.edition,
+            release.release_group,
+            release.repack
+        ],
+    )?;
+    log_debug!("Saved release for video: {:?}", release);
+    Ok(())
+}
+
+/// 获取所有视频的发布信息
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// 
+/// # 返回
+/// * `Result<Vec<VideoRelease>, rusqlite::Error>` - 成功返回发布信息列表，失败返回错误
+pub fn get_all_video_releases(conn: &Connection) -> Result<Vec<VideoRelease>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT video_id, title, year, resolution, source, video_codec, audio_codec, hdr, edition, release_group, repack
+        FROM video_release"
+    )?;
+
+    let releases = stmt.query_map([], |row| {
+        Ok(VideoRelease {
+            video_id: row.get(0)?,
+            title: row.get(1)?,
+            year: row.get(2)?,
+            resolution: row.get(3)?,
+            source: row.get(4)?,
+            video_codec: row.get(5)?,
+            audio_codec: row.get(6)?,
+            hdr: row.get(7)?,
+            edition: row.get(8)?,
+            release_group: row.get(9)?,
+            repack: row.get(10)?,
+        })
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(releases)
+}
+
+/// 按路径前缀查找视频，用于目录被删除或移走时找出其中的视频
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `dir` - 目录路径
+/// 
+/// # 返回
+/// * `Result<Vec<VideoInfo>, rusqlite::Error>` - 成功返回目录下（含子目录）的视频列表，失败返回错误
+pub fn get_videos_under_path(conn: &Connection, dir: &str) -> Result<Vec<VideoInfo>, rusqlite::Error> {
+    let prefix = format!("{}{}", dir.trim_end_matches(['/', '\\']), std::path::MAIN_SEPARATOR);
+    // 不使用 LIKE，避免路径中的 % 和 _ 被当作通配符
+    let mut stmt = conn.prepare("SELECT * FROM videos WHERE substr(path, 1, length(?1)) = ?1")?;
+    let videos = stmt.query_map(params![prefix], video_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(videos)
+}
+
+/// 保存附加内容，已存在时覆盖，同时删除以前被当作视频入库的同一文件
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `extra` - 附加内容
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn save_video_extra(conn: &mut Connection, extra: &VideoExtra) -> Result<(), rusqlite::Error> {
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT OR REPLACE INTO video_extras (id, video_id, extra_type, title, path, duration, file_size, mtime, create_time)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            extra.id, extra.video_id, extra.extra_type, extra.title, extra.path,
+            extra.duration, extra.file_size, extra.mtime, extra.create_time
+        ],
+    )?;
+    delete_video(&tx, &extra.id)?;
+    tx.commit()?;
+    log_debug!("Saved video extra: {:?}", extra);
+    Ok(())
+}
+
+/// 根据ID获取附加内容
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `id` - 附加内容ID
+/// 
+/// # 返回
+/// * `Result<Option<VideoExtra>, rusqlite::Error>` - 成功返回附加内容，不存在时返回None
+pub fn get_video_extra(conn: &Connection, id: &str) -> Result<Option<VideoExtra>, rusqlite::Error> {
+    fetch_single_row(conn, &format!("SELECT {} FROM video_extras WHERE id = ?", EXTRA_COLUMNS), &[&id], extra_from_row)
+}
+
+/// 获取视频的附加内容，按类型和标题排列
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `video_id` - 视频ID
+/// 
+/// # 返回
+/// * `Result<Vec<VideoExtra>, rusqlite::Error>` - 成功返回附加内容列表，失败返回错误
+pub fn get_video_extras(conn: &Connection, video_id: &str) -> Result<Vec<VideoExtra>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM video_extras WHERE video_id = ?1 ORDER BY extra_type, title", EXTRA_COLUMNS
+    ))?;
+    let extras = stmt.query_map(params![video_id], extra_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(extras)
+}
+
+/// 按路径前缀查找附加内容
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `dir` - 目录路径
+/// 
+/// # 返回
+/// * `Result<Vec<VideoExtra>, rusqlite::Error>` - 成功返回目录下（含子目录）的附加内容列表，失败返回错误
+pub fn get_extras_under_path(conn: &Connection, dir: &str) -> Result<Vec<VideoExtra>, rusqlite::Error> {
+    let prefix = format!("{}{}", dir.trim_end_matches(['/', '\\']), std::path::MAIN_SEPARATOR);
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM video_extras WHERE substr(path, 1, length(?1)) = ?1", EXTRA_COLUMNS
+    ))?;
+    let extras = stmt.query_map(params![prefix], extra_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(extras)
+}
+
+/// 批量更新附加内容所属的视频
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `links` - (附加内容ID, 视频ID) 列表
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn update_extra_parents(conn: &mut Connection, links: &[(String, String)]) -> Result<(), rusqlite::Error> {
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare("UPDATE video_extras SET video_id = ?1 WHERE id = ?2")?;
+        for (id, video_id) in links {
+            stmt.execute(params![video_id, id])?;
+        }
+    }
+    tx.commit()
+}
+
+/// 删除附加内容
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `id` - 附加内容ID
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn delete_video_extra(conn: &Connection, id: &str) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM video_extras WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+const EXTRA_COLUMNS: &str = "id, video_id, extra_type, title, path, duration, file_size, mtime, create_time";
+
+fn extra_from_row(row: &rusqlite::Row) -> Result<VideoExtra, rusqlite::Error> {
+    Ok(VideoExtra {
+        id: row.get(0)?,
+        video_id: row.get(1)?,
+        extra_type: row.get(2)?,
+        title: row.get(3)?,
+        path: row.get(4)?,
+        duration: row.get(5)?,
+        file_size: row.get(6)?,
+        mtime: row.get(7)?,
+        create_time: row.get(8)?,
+    })
+}
+
+/// 添加资料库，路径已存在时返回已有的记录
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `path` - 根目录路径
+/// * `name` - 显示名称
+/// 
+/// # 返回
+/// * `Result<Library, rusqlite::Error>` - 成功返回资料库，失败返回错误
+pub fn insert_library(conn: &Connection, path: &str, name: &str) -> Result<Library, rusqlite::Error> {
+    conn.execute(
+        "INSERT OR IGNORE INTO libraries (path, name, content_type, create_time) VALUES (?1, ?2, ?3, ?4)",
+        params![path, name, CONTENT_MIXED, chrono::Utc::now().timestamp()],
+    )?;
+    let library = conn.query_row(
+        &format!("SELECT {} FROM libraries WHERE path = ?1", LIBRARY_COLUMNS),
+        params![path],
+        library_from_row,
+    )?;
+    log_info!("Added library: {:?}", library);
+    Ok(library)
+}
+
+/// 根据ID获取资料库
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `id` - 资料库ID
+/// 
+/// # 返回
+/// * `Result<Option<Library>, rusqlite::Error>` - 成功返回资料库，不存在时返回None
+pub fn get_library(conn: &Connection, id: i64) -> Result<Option<Library>, rusqlite::Error> {
+    fetch_single_row(conn, &format!("SELECT {} FROM libraries WHERE id = ?", LIBRARY_COLUMNS), &[&id], library_from_row)
+}
+
+/// 获取所有资料库
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// 
+/// # 返回
+/// * `Result<Vec<Library>, rusqlite::Error>` - 成功返回资料库列表，失败返回错误
+pub fn get_all_libraries(conn: &Connection) -> Result<Vec<Library>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM libraries ORDER BY id", LIBRARY_COLUMNS))?;
+    let libraries = stmt.query_map([], library_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(libraries)
+}
+
+/// 更新资料库的名称、内容类型、语言、启用状态和扫描规则，路径不可修改
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `library` - 资料库信息
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn update_library(conn: &Connection, library: &Library) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE libraries SET name = ?1, content_type = ?2, language = ?3, enabled = ?4,
+            include_patterns = ?5, exclude_patterns = ?6, min_file_size = ?7, max_depth = ?8,
+            skip_hidden = ?9, follow_symlinks = ?10, skip_samples = ?11
+         WHERE id = ?12",
+        params![
+            library.name, library.content_type, library.language, library.enabled,
+            library.rules.include_patterns.join("\n"), library.rules.exclude_patterns.join("\n"),
+            library.rules.min_file_size as i64, library.rules.max_depth,
+            library.rules.skip_hidden, library.rules.follow_symlinks, library.rules.skip_samples,
+            library.id
+        ],
+    )?;
+    log_debug!("Updated library: {:?}", library);
+    Ok(())
+}
+
+/// 记录资料库的扫描时间，并将目录下尚未归属的视频归入该资料库
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `library` - 资料库
+/// * `scan_time` - 扫描时间（Unix时间戳）
+/// 
+/// # 返回
+/// * `Result<(), rusqlite::Error>` - 成功返回Ok(()), 失败返回错误
+pub fn finish_library_scan(conn: &Connection, library: &Library, scan_time: i64) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE libraries SET last_scan_time = ?1 WHERE id = ?2",
+        params![scan_time, library.id],
+    )?;
+    // 添加资料库之前已经扫描过的视频
+    let prefix = format!("{}{}", library.path.trim_end_matches(['/', '\\']), std::path::MAIN_SEPARATOR);
+    conn.execute(
+        "UPDATE videos SET library_id = ?1 WHERE library_id = 0 AND substr(path, 1, length(?2)) = ?2",
+        params![library.id, prefix],
+    )?;
+    Ok(())
+}
+
+/// 删除资料库，已入库的视频保留，但不再属于任何资料库
+/// 
+/// # 参数
+/// * `conn` - 数据库连接
+/// * `id` - 资料库ID
+/// 
+/// # 返回
+/// * `Result<Option<Library>, rusqlite::Error>` - 成功返回被删除的资料库，不存在时返回None
+pub fn delete_library(conn: &Connection, id: i64) -> Result<Option<Library>, rusqlite::Error> {
+    let library = get_library(conn, id)?;
+    conn.execute("DELETE FROM libraries WHERE id = ?1", params![id])?;
+    conn.execute("UPDATE videos SET library_id = 0 WHERE library_id = ?1", params![id])?;
+    Ok(library)
+}
+
+const LIBRARY_COLUMNS: &str = "id, path, name, content_type, language, enabled, last_scan_time, create_time,
+    include_patterns, exclude_patterns, min_file_size, max_depth, skip_hidden, follow_symlinks, skip_samples";
+
+fn library_from_row(row: &rusqlite::Row) -> Result<Library, rusqlite::Error> {
+    Ok(Library {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        name: row.get(2)?,
+        content_type: row.get(3)?,
+        language: row.get(4)?,
+        enabled: row.get(5)?,
+        last_scan_time: row.get(6)?,
+        create_time: row.get(7)?,
+        rules: ScanRules {
+            include_patterns: split_patterns(&row.get::<_, String>(8)?),
+            exclude_patterns: split_patterns(&row.get::<_, String>(9)?),
+            min_file_size: row.get::<_, i64>(10)?.max(0) as u64,
+            max_depth: row.get(11)?,
+            skip_hidden: row.get(12)?,
+            follow_symlinks: row.get(13)?,
+            skip_samples: row.get(14)?,
+        },
+    })
+}
+
+/// 扫描规则中的模式按行保存
+fn split_patterns(text: &str) -> Vec<String> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty()).map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{memory_db, test_video};
+
+    #[test]
+    fn test_library_rules_round_trip() {
+        let conn = memory_db();
+        let mut library = insert_library(&conn, "/media/movies", "Movies").unwrap();
+        assert_eq!(library.rules, ScanRules::default());
+        assert_eq!(library.content_type, CONTENT_MIXED);
+
+        // 同一路径再次添加时返回已有的记录
+        assert_eq!(insert_library(&conn, "/media/movies", "Other").unwrap().id, library.id);
+
+        library.name = "电影".to_string();
+        library.content_type = CONTENT_MOVIE.to_string();
+        library.language = "en-US".to_string();
+        library.enabled = false;
+        library.rules = ScanRules {
+            include_patterns: vec!["**/*.mkv".to_string(), "re:^Movies/.*\\.mp4$".to_string()],
+            exclude_patterns: vec!["**/Extras/**".to_string()],
+            min_file_size: 50 * 1024 * 1024,
+            max_depth: 3,
+            skip_hidden: false,
+            follow_symlinks: true,
+            skip_samples: false,
+        };
+        update_library(&conn, &library).unwrap();
+
+        let libraries = get_all_libraries(&conn).unwrap();
+        assert_eq!(libraries.len(), 1);
+        let stored = &libraries[0];
+        assert_eq!(stored.path, "/media/movies");
+        assert_eq!(stored.name, "电影");
+        assert_eq!(stored.content_type, CONTENT_MOVIE);
+        assert_eq!(stored.language, "en-US");
+        assert!(!stored.enabled);
+        assert_eq!(stored.rules, library.rules);
+
+        // 清空模式后读回空列表，而不是包含一个空字符串
+        library.rules.include_patterns.clear();
+        library.rules.exclude_patterns.clear();
+        update_library(&conn, &library).unwrap();
+        assert_eq!(get_library(&conn, library.id).unwrap().unwrap().rules, library.rules);
+
+        assert_eq!(delete_library(&conn, library.id).unwrap().map(|library| library.id), Some(library.id));
+        assert!(get_all_libraries(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tracks_and_chapters_probed() {
+        let conn = memory_db();
+        insert_video(&conn, &test_video("a", "/videos/a.mkv")).unwrap();
+        assert!(!video_tracks_probed(&conn, "a").unwrap());
+        assert!(!video_chapters_probed(&conn, "a").unwrap());
+        assert!(!video_tracks_probed(&conn, "missing").unwrap());
+
+        // 解析结果为空也记录为已解析
+        save_video_chapters(&conn, "a", &[]).unwrap();
+        assert!(video_chapters_probed(&conn, "a").unwrap());
+        assert!(!video_tracks_probed(&conn, "a").unwrap());
+
+        let track = VideoTrack {
+            video_id: "a".to_string(),
+            track_index: 0,
+            track_type: "subtitle".to_string(),
+            codec: "S_TEXT/UTF8".to_string(),
+            codec_name: "SRT".to_string(),
+            language: "chi".to_string(),
+            language_ietf: "zh-Hant".to_string(),
+            name: String::new(),
+            is_default: false,
+            is_forced: false,
+            width: 0,
+            height: 0,
+            channels: 0,
+            sampling_frequency: 0.0,
+        };
+        save_video_tracks(&conn, "a", &[track]).unwrap();
+        assert!(video_tracks_probed(&conn, "a").unwrap());
+        assert_eq!(get_video_tracks(&conn, "a").unwrap()[0].language_ietf, "zh-Hant");
+    }
+
+    #[test]
+    fn test_relink_and_delete_video_extras() {
+        let mut conn = memory_db();
+        insert_video(&conn, &test_video("old", "/m/Movie/Movie.mkv")).unwrap();
+        let extra = VideoExtra {
+            id: "trailer".to_string(),
+            video_id: "old".to_string(),
+            extra_type: "trailer".to_string(),
+            title: "Movie-trailer".to_string(),
+            path: "/m/Movie/Movie-trailer.mkv".to_string(),
+            duration: "00:02:00".to_string(),
+            file_size: 100,
+            mtime: 0,
+            create_time: 0,
+        };
+        save_video_extra(&mut conn, &extra).unwrap();
+
+        // 移动后附加内容跟随新的视频ID
+        relink_video(&mut conn, "old", &test_video("new", "/m/Movie/Movie (2020).mkv")).unwrap();
+        assert!(get_video_extras(&conn, "old").unwrap().is_empty());
+        assert_eq!(get_video_extras(&conn, "new").unwrap().len(), 1);
+
+        // 删除视频后附加内容保留，但不再关联到已删除的ID
+        delete_video(&conn, "new").unwrap();
+        assert!(get_video_extras(&conn, "new").unwrap().is_empty());
+        assert_eq!(get_video_extra(&conn, "trailer").unwrap().unwrap().video_id, "");
+    }
+}
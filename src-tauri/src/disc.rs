@@ -0,0 +1,459 @@
+//! 光盘结构
+//!
+//! Blu-ray（`BDMV`）和 DVD（`VIDEO_TS`）文件夹以及 ISO 镜像作为一个视频入库，
+//! 其中的 `.m2ts`、`.VOB` 片段不单独入库。正片按播放列表（Blu-ray 的 MPLS、DVD 的 PGC）中时长最长的一个选择。
+
+use std::{
+    fs::{self, File}, io::{Read, Seek, SeekFrom}, path::{Path, PathBuf}
+};
+
+/// 光盘镜像的扩展名，扫描时与视频扩展名一起识别
+pub(crate) const DISC_IMAGE_EXTENSIONS: [&str; 1] = ["iso"];
+
+/// 光盘类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum DiscKind {
+    Bluray,
+    Dvd,
+}
+
+impl DiscKind {
+    /// 光盘类型名称，同时用作 VLC 的协议名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiscKind::Bluray => "bluray",
+            DiscKind::Dvd => "dvd",
+        }
+    }
+}
+
+/// 光盘的正片信息
+#[derive(Debug)]
+pub(crate) struct DiscInfo {
+    pub kind: DiscKind,
+    /// 正片所在的文件：Blu-ray 为正片播放列表中最大的 `.m2ts`，DVD 为正片标题集的第一个 `.VOB`，
+    /// ISO 镜像为镜像文件本身。用于判断文件变化、计算内容指纹和解析轨道信息
+    pub main_file: PathBuf,
+    /// 正片时长（秒），无法读取时为 0
+    pub duration: f64,
+}
+
+/// 判断目录是否为光盘文件夹：包含 `BDMV/index.bdmv` 或 `VIDEO_TS/VIDEO_TS.IFO`
+pub(crate) fn folder_kind(dir: &Path) -> Option<DiscKind> {
+    if find_child(dir, "BDMV").is_some_and(|bdmv| find_child(&bdmv, "index.bdmv").is_some()) {
+        Some(DiscKind::Bluray)
+    } else if find_child(dir, "VIDEO_TS").is_some_and(|video_ts| find_child(&video_ts, "VIDEO_TS.IFO").is_some()) {
+        Some(DiscKind::Dvd)
+    } else {
+        None
+    }
+}
+
+/// 查找路径所在的光盘文件夹（路径本身或其上级目录），只查找到 `root` 为止
+///
+/// 光盘文件夹内部的文件变化按光盘整体处理，如 `Movie/BDMV/STREAM/00001.m2ts` 对应 `Movie`。
+pub(crate) fn disc_root<'a>(path: &'a Path, root: &Path) -> Option<&'a Path> {
+    path.ancestors()
+        .take_while(|dir| dir.starts_with(root))
+        .find(|dir| dir.is_dir() && folder_kind(dir).is_some())
+}
+
+/// 判断路径（光盘文件夹或 ISO 镜像）的光盘类型，只读取少量数据
+pub(crate) fn disc_kind(path: &Path) -> Option<DiscKind> {
+    if path.is_dir() {
+        folder_kind(path)
+    } else if is_disc_image(path) {
+        iso_kind(path)
+    } else {
+        None
+    }
+}
+
+/// 读取光盘的正片信息，路径不是光盘文件夹或 ISO 镜像时返回 None
+pub(crate) fn probe_disc(path: &Path) -> Option<DiscInfo> {
+    if is_disc_image(path) && path.is_file() {
+        // 镜像内部的文件系统不解析，正片由播放器选择
+        let kind = iso_kind(path).unwrap_or(DiscKind::Bluray);
+        return Some(DiscInfo { kind, main_file: path.to_path_buf(), duration: 0.0 });
+    }
+    match folder_kind(path)? {
+        DiscKind::Bluray => probe_bluray(&find_child(path, "BDMV")?),
+        DiscKind::Dvd => probe_dvd(&find_child(path, "VIDEO_TS")?),
+    }
+}
+
+/// 播放器打开光盘使用的地址，如 `bluray:///media/Movie`，ISO 镜像无法判断类型时返回 None
+pub(crate) fn player_url(path: &Path) -> Option<String> {
+    let kind = disc_kind(path)?;
+    let path = path.to_string_lossy().replace('\\', "/");
+    let separator = if path.starts_with('/') { "" } else { "/" };
+    Some(format!("{}://{}{}", kind.as_str(), separator, path))
+}
+
+/// 扩展名为光盘镜像
+pub(crate) fn is_disc_image(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| DISC_IMAGE_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+}
+
+/// 不区分大小写查找子文件或子目录，光盘复制到不同文件系统后大小写可能不同
+fn find_child(dir: &Path, name: &str) -> Option<PathBuf> {
+    let exact = dir.join(name);
+    if exact.exists() {
+        return Some(exact);
+    }
+    fs::read_dir(dir).ok()?
+        .filter_map(|entry| entry.ok())
+        .find(|entry| entry.file_name().to_string_lossy().eq_ignore_ascii_case(name))
+        .map(|entry| entry.path())
+}
+
+/// 列出目录中指定扩展名的文件
+fn list_files(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(extension)))
+        .collect();
+    files.sort();
+    files
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+/// Blu-ray：解析 `PLAYLIST` 中的所有播放列表，时长最长的为正片，
+/// 没有可用的播放列表时取 `STREAM` 中最大的片段
+fn probe_bluray(bdmv: &Path) -> Option<DiscInfo> {
+    let stream = find_child(bdmv, "STREAM")?;
+    let main = find_child(bdmv, "PLAYLIST")
+        .map(|dir| list_files(&dir, "mpls"))
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| parse_mpls(&fs::read(path).ok()?))
+        .filter(|playlist| playlist.duration > 0.0)
+        .max_by(|a, b| a.duration.total_cmp(&b.duration));
+
+    let (clips, duration) = match main {
+        Some(playlist) => {
+            let clips = playlist.clips.iter()
+                .filter_map(|clip| find_child(&stream, &format!("{}.m2ts", clip)))
+                .collect();
+            (clips, playlist.duration)
+        }
+        None => (list_files(&stream, "m2ts"), 0.0),
+    };
+    let main_file = clips.into_iter().max_by_key(|path| file_size(path))?;
+    Some(DiscInfo { kind: DiscKind::Bluray, main_file, duration })
+}
+
+/// Blu-ray 播放列表
+#[derive(Debug, PartialEq)]
+struct Playlist {
+    /// 按顺序引用的片段名，如 `00001`
+    clips: Vec<String>,
+    /// 各播放项时长之和（秒）
+    duration: f64,
+}
+
+/// 解析 MPLS 播放列表：PlayList 中每个 PlayItem 的片段名和入点、出点（45kHz 时钟）
+fn parse_mpls(data: &[u8]) -> Option<Playlist> {
+    if data.get(..4)? != b"MPLS" {
+        return None;
+    }
+    let start = read_u32_be(data, 8)? as usize;
+    let item_count = read_u16_be(data, start + 6)? as usize;
+
+    let mut clips = Vec::new();
+    let mut ticks = 0u64;
+    let mut offset = start + 10;
+    for _ in 0..item_count {
+        let length = read_u16_be(data, offset)? as usize;
+        let item = data.get(offset + 2..offset + 2 + length)?;
+        let clip = String::from_utf8_lossy(item.get(..5)?).to_string();
+        let in_time = read_u32_be(item, 12)?;
+        let out_time = read_u32_be(item, 16)?;
+        if !clips.contains(&clip) {
+            clips.push(clip);
+        }
+        ticks += out_time.saturating_sub(in_time) as u64;
+        offset += 2 + length;
+    }
+    Some(Playlist { clips, duration: ticks as f64 / 45_000.0 })
+}
+
+/// DVD：解析每个标题集的 IFO，节目链时长最长的标题集为正片，正片从该标题集的第一个 VOB 开始；
+/// IFO 无法解析时取 VOB 总大小最大的标题集
+fn probe_dvd(video_ts: &Path) -> Option<DiscInfo> {
+    let title_sets: Vec<(String, f64, u64)> = list_files(video_ts, "ifo").iter()
+        .filter_map(|ifo| {
+            let name = ifo.file_stem()?.to_string_lossy().to_uppercase();
+            // 标题集的 IFO 为 VTS_nn_0.IFO，VIDEO_TS.IFO 是菜单
+            let prefix = name.strip_suffix("_0").filter(|prefix| prefix.starts_with("VTS_"))?.to_string();
+            let duration = fs::read(ifo).ok().and_then(|data| parse_ifo_duration(&data)).unwrap_or(0.0);
+            let size = list_files(video_ts, "vob").iter()
+                .filter(|vob| vob.file_stem().is_some_and(|stem| {
+                    let stem = stem.to_string_lossy().to_uppercase();
+                    stem.starts_with(&format!("{}_", prefix)) && !stem.ends_with("_0")
+                }))
+                .map(|vob| file_size(vob))
+                .sum();
+            Some((prefix, duration, size))
+        })
+        .collect();
+
+    let (prefix, duration, _) = title_sets.into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(a.2.cmp(&b.2)))?;
+    let main_file = find_child(video_ts, &format!("{}_1.VOB", prefix))?;
+    Some(DiscInfo { kind: DiscKind::Dvd, main_file, duration })
+}
+
+/// DVD 扇区大小
+const DVD_SECTOR_SIZE: usize = 2048;
+
+/// 解析标题集 IFO 中最长的节目链（PGC）时长
+fn parse_ifo_duration(data: &[u8]) -> Option<f64> {
+    if data.get(..12)? != b"DVDVIDEO-VTS" {
+        return None;
+    }
+    // VTS_PGCIT 所在的扇区
+    let pgcit = read_u32_be(data, 0xCC)? as usize * DVD_SECTOR_SIZE;
+    let pgc_count = read_u16_be(data, pgcit)? as usize;
+    (0..pgc_count)
+        .filter_map(|index| {
+            let pgc = pgcit + read_u32_be(data, pgcit + 8 + index * 8 + 4)? as usize;
+            parse_dvd_time(data.get(pgc + 4..pgc + 8)?)
+        })
+        .max_by(f64::total_cmp)
+}
+
+/// 解析 DVD 的 BCD 时间：时、分、秒、帧（高两位为帧率：01 为 25fps，11 为 30fps）
+fn parse_dvd_time(time: &[u8]) -> Option<f64> {
+    let bcd = |byte: u8| -> Option<f64> {
+        let (high, low) = (byte >> 4, byte & 0x0F);
+        (high < 10 && low < 10).then_some((high * 10 + low) as f64)
+    };
+    let fps = match time[3] >> 6 {
+        1 => 25.0,
+        3 => 30.0,
+        _ => return None,
+    };
+    let frames = bcd(time[3] & 0x3F)?;
+    Some(bcd(time[0])? * 3600.0 + bcd(time[1])? * 60.0 + bcd(time[2])? + frames / fps)
+}
+
+/// 光盘扇区大小，卷描述符从第 16 个扇区开始
+const ISO_SECTOR_SIZE: u64 = 2048;
+
+/// 判断 ISO 镜像的光盘类型：ISO 9660 根目录中有 `VIDEO_TS` 为 DVD，有 `BDMV` 为 Blu-ray；
+/// 没有 ISO 9660 目录时按 UDF 版本判断，UDF 2.x（NSR03）为 Blu-ray，UDF 1.x（NSR02）为 DVD
+fn iso_kind(path: &Path) -> Option<DiscKind> {
+    let mut file = File::open(path).ok()?;
+    let mut read_sector = |sector: u64, length: usize| -> Option<Vec<u8>> {
+        let mut buffer = vec![0u8; length];
+        file.seek(SeekFrom::Start(sector * ISO_SECTOR_SIZE)).ok()?;
+        file.read_exact(&mut buffer).ok()?;
+        Some(buffer)
+    };
+
+    let mut root = None;
+    let mut udf = None;
+    for sector in 16..32 {
+        let Some(descriptor) = read_sector(sector, ISO_SECTOR_SIZE as usize) else { break };
+        match &descriptor[1..6] {
+            // 主卷描述符中的根目录记录
+            b"CD001" if descriptor[0] == 1 => root = Some((read_u32_le(&descriptor, 158)?, read_u32_le(&descriptor, 166)?)),
+            b"CD001" if descriptor[0] == 255 => {}
+            b"NSR02" => udf = Some(DiscKind::Dvd),
+            b"NSR03" => udf = Some(DiscKind::Bluray),
+            b"BEA01" | b"TEA01" | b"CD001" => {}
+            _ => break,
+        }
+    }
+
+    if let Some((extent, length)) = root {
+        let names = read_sector(extent as u64, (length as usize).min(64 * 1024))
+            .map(|data| iso_directory_names(&data))
+            .unwrap_or_default();
+        if names.iter().any(|name| name == "VIDEO_TS") {
+            return Some(DiscKind::Dvd);
+        }
+        if names.iter().any(|name| name == "BDMV") {
+            return Some(DiscKind::Bluray);
+        }
+    }
+    udf
+}
+
+/// 列出 ISO 9660 目录中的文件名（大写，去掉版本号）
+fn iso_directory_names(data: &[u8]) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let length = data[offset] as usize;
+        if length == 0 {
+            // 目录记录不跨扇区，剩余部分填充 0
+            offset = (offset / ISO_SECTOR_SIZE as usize + 1) * ISO_SECTOR_SIZE as usize;
+            continue;
+        }
+        let Some(record) = data.get(offset..offset + length) else { break };
+        if let Some(name) = record.get(32).and_then(|&len| record.get(33..33 + len as usize)) {
+            let name = String::from_utf8_lossy(name).to_uppercase();
+            names.push(name.split(';').next().unwrap_or_default().to_string());
+        }
+        offset += length;
+    }
+    names
+}
+
+fn read_u16_be(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32_be(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn read_u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    /// 构造 MPLS 播放列表，每项为 (片段名, 入点, 出点)
+    fn mpls(items: &[(&str, u32, u32)]) -> Vec<u8> {
+        let mut data = b"MPLS0200".to_vec();
+        data.extend(20u32.to_be_bytes());
+        data.extend([0u8; 8]);
+        let mut playlist = vec![0u8; 6];
+        playlist.extend((items.len() as u16).to_be_bytes());
+        playlist.extend([0u8; 2]);
+        for (clip, in_time, out_time) in items {
+            let mut item = clip.as_bytes().to_vec();
+            item.extend(b"M2TS");
+            item.extend([0u8; 3]);
+            item.extend(in_time.to_be_bytes());
+            item.extend(out_time.to_be_bytes());
+            item.extend([0u8; 12]);
+            playlist.extend((item.len() as u16).to_be_bytes());
+            playlist.extend(item);
+        }
+        data.extend(playlist);
+        data
+    }
+
+    /// 构造标题集 IFO，每个节目链一个 BCD 时长
+    fn ifo(times: &[[u8; 4]]) -> Vec<u8> {
+        let mut data = vec![0u8; DVD_SECTOR_SIZE * 2];
+        data[..12].copy_from_slice(b"DVDVIDEO-VTS");
+        data[0xCC..0xD0].copy_from_slice(&1u32.to_be_bytes());
+        let pgcit = DVD_SECTOR_SIZE;
+        data[pgcit..pgcit + 2].copy_from_slice(&(times.len() as u16).to_be_bytes());
+        for (index, time) in times.iter().enumerate() {
+            let pgc = 8 + times.len() * 8 + index * 16;
+            data[pgcit + 8 + index * 8 + 4..pgcit + 8 + index * 8 + 8].copy_from_slice(&(pgc as u32).to_be_bytes());
+            data[pgcit + pgc + 4..pgcit + pgc + 8].copy_from_slice(time);
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_mpls() {
+        let data = mpls(&[("00001", 0, 45_000 * 3600), ("00002", 45_000 * 10, 45_000 * 70), ("00001", 0, 45_000)]);
+        assert_eq!(parse_mpls(&data), Some(Playlist {
+            clips: vec!["00001".to_string(), "00002".to_string()],
+            duration: 3600.0 + 60.0 + 1.0,
+        }));
+        assert_eq!(parse_mpls(b"HDMV0200"), None);
+        assert_eq!(parse_mpls(&data[..data.len() - 10]), None);
+    }
+
+    #[test]
+    fn test_parse_ifo_duration() {
+        // 01:45:30 + 12 帧（25fps），以及一个 00:02:00 的短节目链
+        let data = ifo(&[[0x00, 0x02, 0x00, 0x40], [0x01, 0x45, 0x30, 0x52]]);
+        assert_eq!(parse_ifo_duration(&data), Some(3600.0 + 45.0 * 60.0 + 30.0 + 12.0 / 25.0));
+        assert_eq!(parse_ifo_duration(&ifo(&[[0x00, 0x0A, 0x00, 0x40]])), None);
+        assert_eq!(parse_ifo_duration(b"DVDVIDEO-VMG"), None);
+    }
+
+    #[test]
+    fn test_probe_bluray_folder() {
+        let dir = TempDir::new("disc_bluray", &[
+            ("Movie (2010)/BDMV/index.bdmv", b"INDX0200".to_vec()),
+            ("Movie (2010)/BDMV/PLAYLIST/00000.mpls", mpls(&[("00010", 0, 45_000 * 60)])),
+            ("Movie (2010)/BDMV/PLAYLIST/00800.mpls", mpls(&[("00001", 0, 45_000 * 3000), ("00002", 0, 45_000 * 3000)])),
+            ("Movie (2010)/BDMV/STREAM/00001.m2ts", vec![0u8; 300]),
+            ("Movie (2010)/BDMV/STREAM/00002.m2ts", vec![0u8; 200]),
+            ("Movie (2010)/BDMV/STREAM/00010.m2ts", vec![0u8; 1000]),
+        ]);
+        let root = dir.0.join("Movie (2010)");
+
+        let disc = probe_disc(&root).unwrap();
+        assert_eq!(disc.kind, DiscKind::Bluray);
+        assert_eq!(disc.main_file, root.join("BDMV/STREAM/00001.m2ts"));
+        assert_eq!(disc.duration, 6000.0);
+
+        assert_eq!(disc_root(&root.join("BDMV/STREAM/00001.m2ts"), &dir.0), Some(root.as_path()));
+        assert_eq!(disc_root(&root.join("BDMV/STREAM/00001.m2ts"), &root.join("BDMV")), None);
+        assert_eq!(disc_root(&dir.0, &dir.0), None);
+        assert!(player_url(&root).is_some_and(|url| url.starts_with("bluray:///") && url.ends_with("/Movie (2010)")));
+        assert_eq!(player_url(&root.join("BDMV")), None);
+    }
+
+    #[test]
+    fn test_probe_dvd_folder() {
+        let dir = TempDir::new("disc_dvd", &[
+            ("Movie/VIDEO_TS/VIDEO_TS.IFO", b"DVDVIDEO-VMG".to_vec()),
+            ("Movie/VIDEO_TS/VTS_01_0.IFO", ifo(&[[0x00, 0x03, 0x00, 0x40]])),
+            ("Movie/VIDEO_TS/VTS_01_1.VOB", vec![0u8; 100]),
+            ("Movie/VIDEO_TS/VTS_02_0.IFO", ifo(&[[0x01, 0x30, 0x00, 0x40]])),
+            ("Movie/VIDEO_TS/VTS_02_1.VOB", vec![0u8; 50]),
+            ("Movie/VIDEO_TS/VTS_02_2.VOB", vec![0u8; 50]),
+        ]);
+        let root = dir.0.join("Movie");
+
+        let disc = probe_disc(&root).unwrap();
+        assert_eq!(disc.kind, DiscKind::Dvd);
+        assert_eq!(disc.main_file, root.join("VIDEO_TS/VTS_02_1.VOB"));
+        assert_eq!(disc.duration, 5400.0);
+        assert_eq!(probe_disc(&root.join("VIDEO_TS")).map(|disc| disc.kind), None);
+    }
+
+    #[test]
+    fn test_iso_kind() {
+        // 主卷描述符在第 16 扇区，根目录在第 18 扇区，包含 VIDEO_TS 目录
+        let mut data = vec![0u8; ISO_SECTOR_SIZE as usize * 19];
+        let pvd = 16 * ISO_SECTOR_SIZE as usize;
+        data[pvd] = 1;
+        data[pvd + 1..pvd + 6].copy_from_slice(b"CD001");
+        data[pvd + 158..pvd + 162].copy_from_slice(&18u32.to_le_bytes());
+        data[pvd + 166..pvd + 170].copy_from_slice(&(ISO_SECTOR_SIZE as u32).to_le_bytes());
+        let terminator = 17 * ISO_SECTOR_SIZE as usize;
+        data[terminator] = 255;
+        data[terminator + 1..terminator + 6].copy_from_slice(b"CD001");
+        let root = 18 * ISO_SECTOR_SIZE as usize;
+        data[root] = 33 + 8;
+        data[root + 32] = 8;
+        data[root + 33..root + 41].copy_from_slice(b"VIDEO_TS");
+
+        let dir = TempDir::new("disc_iso", &[("dvd.iso", data.clone())]);
+        assert_eq!(iso_kind(&dir.0.join("dvd.iso")), Some(DiscKind::Dvd));
+
+        // 只有 UDF 2.x 卷描述符
+        let mut udf = vec![0u8; ISO_SECTOR_SIZE as usize * 20];
+        for (sector, id) in [(16, b"BEA01"), (17, b"NSR03"), (18, b"TEA01")] {
+            let offset = sector * ISO_SECTOR_SIZE as usize;
+            udf[offset + 1..offset + 6].copy_from_slice(id);
+        }
+        let dir = TempDir::new("disc_udf", &[("bluray.iso", udf)]);
+        assert_eq!(iso_kind(&dir.0.join("bluray.iso")), Some(DiscKind::Bluray));
+    }
+}
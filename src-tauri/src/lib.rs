@@ -4,20 +4,28 @@ mod api;
 mod video;
 mod logger;
 mod metadata;
+mod watcher;
+mod scan_filter;
+mod disc;
+mod release;
+#[cfg(test)]
+mod test_util;
 
-use walkdir::WalkDir;
-use tauri::{Manager, State};
-use db::{DbState, VideoInfo};
+use tauri::{Emitter, Manager, State};
+use db::{DbState, Library, VideoChapter, VideoExtra, VideoInfo, VideoQuality, VideoRelease, VideoTrack};
+use watcher::LibraryWatcher;
+use scan_filter::ScanFilter;
 use std::{
-    env, fs::{self, File}, io::{self, BufRead}, path::Path, process::Command, sync::{Arc, Mutex}
+    env, fs::{self, File}, io::{self, BufRead}, path::{Path, PathBuf}, process::Command,
+    sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}
 };
 use serde::{Deserialize, Serialize};
+use once_cell::sync::Lazy;
+use tokio::sync::Semaphore;
 
 // 导出日志宏
 pub use crate::logger::{log_error, log_info, log_debug};
 
-const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mkv", "avi", "mov"];
-
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct Settings {
     player_path: Option<String>,
@@ -39,75 +47,465 @@ struct Settings {
     tmdb_api_key: Option<String>,
     auto_tmdb: Option<bool>,
     auto_tmdb_poster: Option<bool>,
+    /// 用户额外添加的视频扩展名，逗号分隔，如 "rmvb, vob"
+    extra_video_extensions: Option<String>,
 }
 
-struct AppState {
-    settings: Arc<Mutex<Settings>>,
+/// 扫描时识别为视频的扩展名：已注册容器格式的扩展名、光盘镜像的扩展名，加上设置中用户添加的扩展名。
+fn video_extensions(settings: &Settings) -> Vec<String> {
+    let mut extensions: Vec<String> = metadata::supported_extensions()
+        .chain(disc::DISC_IMAGE_EXTENSIONS)
+        .map(str::to_string)
+        .collect();
+    if let Some(ref extra) = settings.extra_video_extensions {
+        for ext in extra.split([',', ';', ' ']) {
+            let ext = ext.trim().trim_start_matches('.').to_ascii_lowercase();
+            if !ext.is_empty() && !extensions.contains(&ext) {
+                extensions.push(ext);
+            }
+        }
+    }
+    extensions
 }
 
-#[tauri::command]
-async fn scan_folder(path: String, db: State<'_, DbState>, settings: Settings) -> Result<Vec<VideoInfo>, String> {
-    let db = db.0.clone();
-    let new_videos = Arc::new(Mutex::new(Vec::new()));
-
-    for entry in WalkDir::new(path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| {
-            if let Some(ext) = e.path().extension() {
-                // 忽略非视频文件和示例视频
-                VIDEO_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()) &&
-                !e.file_name().to_string_lossy().to_ascii_lowercase().contains("sample")
+/// 扫描结果：新增、更新和移动的视频，识别出的附加内容，以及文件未变化而跳过的数量
+#[derive(Serialize, Debug, Default)]
+struct ScanReport {
+    added: Vec<VideoInfo>,
+    updated: Vec<VideoInfo>,
+    moved: Vec<MovedVideo>,
+    /// 新增或变化的附加内容，以前被当作视频入库的同一文件已删除
+    extras: Vec<VideoExtra>,
+    unchanged: usize,
+    /// 扫描是否被取消，取消前已处理的视频仍然保留
+    cancelled: bool,
+}
+
+/// 扫描进度，扫描过程中通过 `scan-progress` 事件发送
+#[derive(Serialize, Debug, Clone, Default)]
+struct ScanProgress {
+    /// 扫描的文件夹
+    root: String,
+    /// 已发现的视频文件数，查找文件期间会持续增加
+    discovered: usize,
+    /// 是否已查找完所有视频文件
+    discovery_done: bool,
+    /// 已处理的视频文件数
+    processed: usize,
+    /// 处理失败的视频文件数
+    errors: usize,
+    /// 最近处理完成的文件，扫描结束后为空
+    current: String,
+}
+
+/// 单个文件处理失败，通过 `scan-error` 事件发送
+#[derive(Serialize, Debug, Clone)]
+struct ScanError {
+    path: String,
+    error: String,
+}
+
+/// 扫描时同时处理的文件数，TMDb 请求另有速率限制
+const SCAN_CONCURRENCY: usize = 8;
+/// 新视频累积到该数量后在一个事务中写入数据库
+const INSERT_BATCH_SIZE: usize = 50;
+/// 同时读取视频文件（解析元数据、计算指纹、提取封面）的数量，避免机械硬盘和网络存储随机读取过多
+static FILE_READ_PERMITS: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(4));
+
+/// 扫描进度事件，内容为 `ScanProgress`
+const EVENT_SCAN_PROGRESS: &str = "scan-progress";
+/// 文件处理失败事件，内容为 `ScanError`
+const EVENT_SCAN_ERROR: &str = "scan-error";
+
+/// 扫描任务状态：同一时间只允许一个扫描，可通过 `cancel_scan` 取消
+#[derive(Default)]
+struct ScanJob {
+    running: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+impl ScanJob {
+    /// 开始扫描，已有扫描在进行时返回错误；返回的守卫在扫描结束时释放任务
+    fn start(&self) -> Result<ScanJobGuard<'_>, String> {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Err("已有扫描正在进行，请等待完成或取消后重试".to_string());
+        }
+        self.cancelled.store(false, Ordering::SeqCst);
+        Ok(ScanJobGuard(self))
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// 扫描结束（包括出错提前返回）时释放扫描任务
+struct ScanJobGuard<'a>(&'a ScanJob);
+
+impl Drop for ScanJobGuard<'_> {
+    fn drop(&mut self) {
+        self.0.running.store(false, Ordering::SeqCst);
+    }
+}
+
+/// 发送事件给前端，失败时只记录日志
+fn emit_event<S: Serialize + Clone>(app_handle: &tauri::AppHandle, event: &str, payload: S) {
+    if let Err(e) = app_handle.emit(event, payload) {
+        log_error!("Failed to emit {}: {}", event, e);
+    }
+}
+
+/// 移动或改名后重新关联的视频，`old_id` 为原路径对应的ID
+#[derive(Serialize, Debug, Clone)]
+struct MovedVideo {
+    old_id: String,
+    video: VideoInfo,
+}
+
+/// 待入库的新视频
+struct NewVideo {
+    video: VideoInfo,
+    tracks: Vec<VideoTrack>,
+    chapters: Vec<VideoChapter>,
+    quality: Option<VideoQuality>,
+    release: VideoRelease,
+}
+
+/// 单个视频文件的分析结果：新视频等待入库，其他情况已处理完毕
+///
+/// 新视频很快会被移入待入库列表，不需要装箱
+#[allow(clippy::large_enum_variant)]
+enum FileAnalysis {
+    New(NewVideo),
+    Done(ScanOutcome),
+}
+
+/// 单个视频文件的处理结果
+enum ScanOutcome {
+    Added(VideoInfo),
+    Updated(VideoInfo),
+    Moved(MovedVideo),
+    Extra(VideoExtra),
+    Unchanged,
+}
+
+impl ScanReport {
+    fn record(&mut self, outcome: ScanOutcome) {
+        match outcome {
+            ScanOutcome::Added(video) => self.added.push(video),
+            ScanOutcome::Updated(video) => self.updated.push(video),
+            ScanOutcome::Moved(moved) => self.moved.push(moved),
+            ScanOutcome::Extra(extra) => self.extras.push(extra),
+            ScanOutcome::Unchanged => self.unchanged += 1,
+        }
+    }
+
+    /// 用重新分组后的视频替换结果中的记录，不在结果中的记为更新
+    fn record_regrouped(&mut self, videos: Vec<VideoInfo>) {
+        for video in videos {
+            if let Some(added) = self.added.iter_mut().find(|added| added.id == video.id) {
+                *added = video;
+            } else if let Some(updated) = self.updated.iter_mut().find(|updated| updated.id == video.id) {
+                *updated = video;
             } else {
-                false
+                self.updated.push(video);
             }
-        })
-    {
-        let path = entry.path().to_owned();
-        let id = format!("{:x}", md5::compute(path.to_string_lossy().as_bytes()));
-        
-        // 检查视频是否已存在
-        let db_clone = db.clone();
-        let id_clone = id.clone();
-        let exists = match tokio::task::spawn_blocking(move || {
-            let conn = db_clone.lock().unwrap();
-            db::video_exists(&conn, &id_clone)
-        }).await {
-            Ok(result) => result,
-            Err(e) => {
-                log_error!("Failed to check video existence: {}", e);
-                continue;
+        }
+    }
+}
+
+/// 新添加的资料库及其首次扫描的结果
+#[derive(Serialize, Debug)]
+struct AddedLibrary {
+    library: Library,
+    report: ScanReport,
+}
+
+/// 资料库健康报告：文件已删除和磁盘离线的视频
+#[derive(Serialize, Debug, Default)]
+struct HealthReport {
+    total: usize,
+    ok: usize,
+    missing: Vec<VideoInfo>,
+    offline: Vec<VideoInfo>,
+}
+
+struct AppState {
+    settings: Arc<Mutex<Settings>>,
+}
+
+/// 文件大小或修改时间变化后重新解析元数据，更新时长、轨道、章节和画质，保留标题和播放记录。
+/// 光盘文件夹和镜像解析正片文件。
+fn refresh_video_file(conn: &rusqlite::Connection, id: &str, path: &Path, file_size: i64, mtime: i64) -> Result<Option<VideoInfo>, String> {
+    let disc = disc::probe_disc(path);
+    let media_path = disc.as_ref().map_or(path, |disc| disc.main_file.as_path());
+    let metadata = video::probe_video(&media_path.to_string_lossy());
+    let duration = video::format_duration(media_duration(disc.as_ref(), metadata.as_ref()));
+    let fingerprint = video::content_fingerprint(media_path).unwrap_or_default();
+    db::update_video_file(conn, id, &duration, file_size, mtime, &fingerprint).map_err(|e| e.to_string())?;
+
+    let tracks = metadata.as_ref().map(|m| video::build_video_tracks(id, m)).unwrap_or_default();
+    let chapters = metadata.as_ref().map(|m| video::build_video_chapters(id, m)).unwrap_or_default();
+    db::save_video_tracks(conn, id, &tracks).map_err(|e| e.to_string())?;
+    db::save_video_chapters(conn, id, &chapters).map_err(|e| e.to_string())?;
+    if let Some(quality) = metadata.as_ref().and_then(|m| video::build_video_quality(id, m)) {
+        db::save_video_quality(conn, &quality).map_err(|e| e.to_string())?;
+    }
+    db::get_video(conn, id).map_err(|e| e.to_string())
+}
+
+/// 视频时长（秒）：光盘优先使用正片播放列表的时长，其次是解析到的文件时长
+fn media_duration(disc: Option<&disc::DiscInfo>, metadata: Option<&metadata::VideoMetadata>) -> f64 {
+    disc.map(|disc| disc.duration)
+        .filter(|duration| *duration > 0.0)
+        .or_else(|| metadata.map(|m| m.video_duration_seconds))
+        .unwrap_or(0.0)
+}
+
+/// 查找内容指纹相同、但原文件已被删除的视频，将其重新关联到新路径和所在的资料库。
+/// 所在磁盘未挂载的视频只是离线，不会被其他磁盘上的副本取代。
+/// 没有匹配的视频时返回 None，按新视频处理。
+fn relink_moved_video(conn: &mut rusqlite::Connection, id: &str, path: &Path, file_size: i64, mtime: i64, fingerprint: &str, library_id: i64) -> Result<Option<MovedVideo>, String> {
+    let candidates = db::find_videos_by_fingerprint(conn, fingerprint).map_err(|e| e.to_string())?;
+    let mut video = match candidates.into_iter().find(|video| video::file_status(Path::new(&video.path)) == db::STATUS_MISSING) {
+        Some(video) => video,
+        None => return Ok(None),
+    };
+
+    let old_id = std::mem::replace(&mut video.id, id.to_string());
+    video.path = path.to_string_lossy().to_string();
+    video.thumbnail = video::relocate_poster(&video.thumbnail, path);
+    video.file_size = file_size;
+    video.mtime = mtime;
+    video.library_id = library_id;
+    db::relink_video(conn, &old_id, &video).map_err(|e| e.to_string())?;
+    // 改名后文件名中的发布信息可能不同
+    db::save_video_release(conn, &build_release(id, path, disc::disc_kind(path))).map_err(|e| e.to_string())?;
+    Ok(Some(MovedVideo { old_id, video }))
+}
+
+/// 由文件名（光盘文件夹为文件夹名）生成发布信息，文件名没有标注片源时按光盘类型补充
+fn build_release(id: &str, path: &Path, disc: Option<disc::DiscKind>) -> VideoRelease {
+    let name = if path.is_dir() { path.file_name() } else { path.file_stem() };
+    let mut release = release::build_video_release(id, &name.unwrap_or_default().to_string_lossy());
+    if release.source.is_empty() {
+        release.source = match disc {
+            Some(disc::DiscKind::Bluray) => "BluRay",
+            Some(disc::DiscKind::Dvd) => "DVD",
+            None => "",
+        }.to_string();
+    }
+    release
+}
+
+/// 查找路径所属的资料库，资料库嵌套时取最内层的一个
+fn library_for_path<'a>(libraries: &'a [Library], path: &Path) -> Option<&'a Library> {
+    libraries.iter()
+        .filter(|library| path.starts_with(&library.path))
+        .max_by_key(|library| library.path.len())
+}
+
+/// 在阻塞线程中读取视频文件，同时进行的读取数受 [`FILE_READ_PERMITS`] 限制
+async fn read_file_blocking<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let _permit = FILE_READ_PERMITS.acquire().await.map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(f).await.map_err(|e| e.to_string())
+}
+
+/// 将新视频及其轨道、章节、画质和发布信息在一个事务中写入数据库
+fn save_new_videos(conn: &mut rusqlite::Connection, videos: &[NewVideo]) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for new_video in videos {
+        let id = &new_video.video.id;
+        db::insert_video(&tx, &new_video.video).map_err(|e| e.to_string())?;
+        db::save_video_tracks(&tx, id, &new_video.tracks).map_err(|e| e.to_string())?;
+        db::save_video_chapters(&tx, id, &new_video.chapters).map_err(|e| e.to_string())?;
+        if let Some(ref quality) = new_video.quality {
+            db::save_video_quality(&tx, quality).map_err(|e| e.to_string())?;
+        }
+        db::save_video_release(&tx, &new_video.release).map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// 重新识别目录（包括子目录）下的多段视频，返回分组发生变化的视频：
+/// 主视频附带各段信息，其他段的 `part_of` 为主视频ID，恢复为独立的视频不附带其他信息。
+fn regroup_video_parts(conn: &mut rusqlite::Connection, dir: &str) -> Result<Vec<VideoInfo>, String> {
+    let videos = db::get_videos_under_path(conn, dir).map_err(|e| e.to_string())?;
+    let changes = video::group_video_parts(&videos);
+    if changes.is_empty() {
+        return Ok(Vec::new());
+    }
+    db::update_video_parts(conn, &changes).map_err(|e| e.to_string())?;
+
+    let changed: std::collections::HashSet<&str> = changes.iter()
+        .flat_map(|(id, part_of, _)| [id.as_str(), part_of.as_str()])
+        .collect();
+    let videos = db::get_videos_under_path(conn, dir).map_err(|e| e.to_string())?;
+    let parts: Vec<VideoInfo> = videos.iter()
+        .filter(|video| !video.part_of.is_empty() && changed.contains(video.id.as_str()))
+        .cloned()
+        .collect();
+    let mut regrouped: Vec<VideoInfo> = video::merge_video_parts(videos).into_iter()
+        .filter(|video| changed.contains(video.id.as_str()))
+        .collect();
+    log_info!("Regrouped {} multi-part videos under {}", regrouped.len(), dir);
+    regrouped.extend(parts);
+    Ok(regrouped)
+}
+
+/// 处理单个视频文件，文件夹监听使用该流程，新视频立即入库。
+async fn process_video_file(path: PathBuf, rescan: bool, db: Arc<Mutex<rusqlite::Connection>>, settings: &Settings, library: Option<&Library>, cover_dir: Option<&Path>) -> Result<ScanOutcome, String> {
+    match analyze_video_file(path, rescan, db.clone(), settings, library, cover_dir).await? {
+        FileAnalysis::Done(outcome) => Ok(outcome),
+        FileAnalysis::New(new_video) => {
+            let new_video = tokio::task::spawn_blocking(move || {
+                let mut conn = db.lock().unwrap();
+                save_new_videos(&mut conn, std::slice::from_ref(&new_video))?;
+                Ok::<_, String>(new_video)
+            }).await.map_err(|e| e.to_string())??;
+            Ok(ScanOutcome::Added(new_video.video))
+        }
+    }
+}
+
+/// 已入库视频的文件是否按未变化处理：不重新扫描时总是跳过，重新扫描时比较文件大小和修改时间
+fn is_file_unchanged(stored: (i64, i64), current: (i64, i64), rescan: bool) -> bool {
+    !rescan || stored == current
+}
+
+/// 分析单个视频文件，文件夹扫描和文件夹监听共用该流程。
+///
+/// 新文件解析元数据、获取 TMDb 信息后返回待入库的视频，由调用方写入数据库；
+/// 内容指纹匹配到原文件已不存在的记录时沿用原记录；
+/// `rescan` 为 true 时，已入库的视频会比较文件大小和修改时间，变化的文件重新解析并更新记录，
+/// 否则已入库的视频直接跳过。
+///
+/// 文件属于某个资料库时，新视频归入该资料库，并按资料库的内容类型和语言获取 TMDb 信息。
+async fn analyze_video_file(path: PathBuf, rescan: bool, db: Arc<Mutex<rusqlite::Connection>>, settings: &Settings, library: Option<&Library>, cover_dir: Option<&Path>) -> Result<FileAnalysis, String> {
+    let library_id = library.map_or(0, |library| library.id);
+    let id = format!("{:x}", md5::compute(path.to_string_lossy().as_bytes()));
+    // 光盘文件夹和镜像作为一个视频，文件状态、内容指纹和轨道信息取自正片文件
+    let disc = if path.is_dir() || disc::is_disc_image(&path) {
+        let path_clone = path.clone();
+        read_file_blocking(move || disc::probe_disc(&path_clone)).await?
+    } else {
+        None
+    };
+    if path.is_dir() && disc.is_none() {
+        return Err(format!("无法识别的光盘文件夹：{}", path.display()));
+    }
+    let media_path = disc.as_ref().map_or_else(|| path.clone(), |disc| disc.main_file.clone());
+    let (file_size, mtime) = video::file_state(&media_path).unwrap_or_default();
+
+    // 预告片、花絮等附加内容不作为视频入库，也不获取 TMDb 信息
+    if let Some(extra) = video::parse_extra(&path) {
+        return analyze_extra_file(path, id, &extra, file_size, mtime, rescan, db).await;
+    }
+    
+    // 检查视频是否已存在
+    let db_clone = db.clone();
+    let id_clone = id.clone();
+    let stored_state = tokio::task::spawn_blocking(move || {
+        let conn = db_clone.lock().unwrap();
+        db::get_video_file_state(&conn, &id_clone).unwrap_or_default()
+    }).await.map_err(|e| e.to_string())?;
+
+    match stored_state {
+        Some(state) if is_file_unchanged(state, (file_size, mtime), rescan) => Ok(FileAnalysis::Done(ScanOutcome::Unchanged)),
+        Some(_) => {
+            // 文件被替换或重新写入，只刷新文件相关的信息
+            log_info!("Video file changed, refreshing: {}", path.display());
+            let refreshed = tokio::task::spawn_blocking(move || {
+                let conn = db.lock().unwrap();
+                refresh_video_file(&conn, &id, &path, file_size, mtime)
+            }).await.map_err(|e| e.to_string())??;
+            // 记录在刷新前已被删除时按未变化处理
+            Ok(FileAnalysis::Done(refreshed.map_or(ScanOutcome::Unchanged, ScanOutcome::Updated)))
+        }
+        None => {
+            let path_clone = media_path.clone();
+            let fingerprint = read_file_blocking(move || video::content_fingerprint(&path_clone)).await?
+                .unwrap_or_else(|e| {
+                    log_error!("Failed to compute fingerprint: {}", e);
+                    String::new()
+                });
+
+            // 指纹相同且原文件已不存在，说明文件被移动或改名，沿用原来的记录
+            let binding = db.clone();
+            let (id_clone, path_clone, fingerprint_clone) = (id.clone(), path.clone(), fingerprint.clone());
+            match tokio::task::spawn_blocking(move || {
+                let mut conn = binding.lock().unwrap();
+                relink_moved_video(&mut conn, &id_clone, &path_clone, file_size, mtime, &fingerprint_clone, library_id)
+            }).await {
+                Ok(Ok(Some(moved))) => return Ok(FileAnalysis::Done(ScanOutcome::Moved(moved))),
+                Ok(Ok(None)) => {}
+                Ok(Err(e)) => log_error!("Failed to relink moved video: {}", e),
+                Err(e) => log_error!("Failed to relink moved video: {}", e),
             }
-        };
 
-        if !exists {
-            let file_name = path.file_name()
+            let mut file_name = path.file_name()
                 .unwrap_or_default()
                 .to_string_lossy()
                 .to_string();
-            
-            // 解析剧集信息
-            let series_info = video::parse_series_info(&file_name);
+            // 光盘文件夹按文件夹名称识别，加上类型作为扩展名，名称中的 `.` 不会被当作扩展名去掉
+            if let Some(disc) = disc.as_ref().filter(|_| path.is_dir()) {
+                file_name = format!("{}.{}", file_name, disc.kind.as_str());
+            }
+        
+            // 解析剧集信息，同时参考季文件夹和剧集文件夹，电影资料库中的文件名即使像剧集也按电影处理
+            let mut series_info = video::parse_series_path(&path, &file_name, library.map(|library| Path::new(&library.path)));
+            if library.is_some_and(|library| library.content_type == db::CONTENT_MOVIE) {
+                series_info.is_series = false;
+            }
+            // 多段视频按去掉分段标记的名称查找，第 1 段存在时其他段不再单独查找
+            let stack_files = match video::parse_stack_part(&file_name) {
+                Some(part) if !series_info.is_series => Some((video::find_stack_files(&path, &part), part)),
+                _ => None,
+            };
+            let stack_part = stack_files.as_ref()
+                .filter(|(files, _)| files.len() > 1)
+                .map(|(files, part)| (part, files.iter().any(|(number, _)| *number == 1)));
             let search_name = if series_info.is_series {
                 &series_info.series_title
+            } else if let Some((part, _)) = stack_part {
+                &part.name
             } else {
                 &file_name
             };
+            let skip_lookup = stack_part.is_some_and(|(part, has_first)| part.number > 1 && has_first);
 
-            // 获取视频时长
-            let formatted_duration = video::get_duration(&path.to_string_lossy());
-            
+            // 获取视频时长和轨道信息
+            let path_str = media_path.to_string_lossy().to_string();
+            let video_metadata = read_file_blocking(move || video::probe_video(&path_str)).await?;
+            let formatted_duration = video::format_duration(media_duration(disc.as_ref(), video_metadata.as_ref()));
+            let tracks = video_metadata.as_ref()
+                .map(|m| video::build_video_tracks(&id, m))
+                .unwrap_or_default();
+            let chapters = video_metadata.as_ref()
+                .map(|m| video::build_video_chapters(&id, m))
+                .unwrap_or_default();
+            let quality = video_metadata.as_ref()
+                .and_then(|m| video::build_video_quality(&id, m));
+            let release = build_release(&id, &path, disc.as_ref().map(|disc| disc.kind));
+        
+            let language = library.map(|library| library.language.as_str())
+                .filter(|language| !language.is_empty())
+                .unwrap_or(video::DEFAULT_TMDB_LANGUAGE);
             let mut video_info_str = String::new();
-            if settings.auto_tmdb.unwrap_or(false) {
+            if settings.auto_tmdb.unwrap_or(false) && !skip_lookup {
                 // 获取 TMDb 信息
                 if let Some(ref api_key) = settings.tmdb_api_key {
                     if series_info.is_series { // 获取电视剧
-                        video_info_str = match video::fetch_tv_info_from_tmdb(&series_info, api_key).await {
+                        video_info_str = match video::fetch_tv_info_from_tmdb(&series_info, api_key, language).await {
                             Ok(info) => info,
                             Err(_) => String::new(), // 返回空字符串
                         }
                     } else {
-                        video_info_str = match video::fetch_video_info_from_tmdb(&search_name, api_key).await {
+                        video_info_str = match video::fetch_video_info_from_tmdb(&search_name, api_key, language).await {
                             Ok(info) => info,
                             Err(_) => String::new(), // 返回空字符串
                         }
@@ -117,8 +515,14 @@ async fn scan_folder(path: String, db: State<'_, DbState>, settings: Settings) -
 
             log_debug!("video_info_str: {}", video_info_str);
             if video_info_str.is_empty() {
+                // 没有 TMDb 信息时电影使用文件名中解析出的标题
+                let title = match stack_part {
+                    _ if series_info.is_series => search_name.clone(),
+                    Some((part, _)) => release::parse_release_name(&part.name).title,
+                    None => release.title.clone(),
+                };
                 video_info_str = serde_json::json!({
-                    "title": search_name,
+                    "title": if title.is_empty() { search_name } else { &title },
                     "original_title": search_name,
                     "overview": "未找到匹配的电影信息",
                     "release_date": "",
@@ -129,10 +533,7 @@ async fn scan_folder(path: String, db: State<'_, DbState>, settings: Settings) -
             }
             let mut video_info = match serde_json::from_str::<serde_json::Value>(&video_info_str) {
                 Ok(info) => info,
-                Err(e) => {
-                    log_error!("Failed to parse video info: {}", e);
-                    continue;
-                }
+                Err(e) => return Err(format!("Failed to parse video info: {}", e)),
             };
 
             // 下载并保存海报图片
@@ -152,12 +553,27 @@ async fn scan_folder(path: String, db: State<'_, DbState>, settings: Settings) -
                 }
             }
 
+            // 没有 TMDb 海报时使用视频内嵌的封面，仍然没有才使用占位图
+            let has_poster = video_info.get("poster_path")
+                .and_then(|v| v.as_str())
+                .is_some_and(|p| !p.is_empty() && !p.contains("/assets/no-poster.png"));
+            if !has_poster {
+                if let Some(cover_dir) = cover_dir {
+                    let (path_clone, id_clone, cover_dir) = (media_path.clone(), id.clone(), cover_dir.to_path_buf());
+                    match read_file_blocking(move || video::save_embedded_cover(&path_clone, &id_clone, &cover_dir)).await? {
+                        Ok(Some(cover_path)) => video_info["poster_path"] = serde_json::Value::String(cover_path),
+                        Ok(None) => video_info["poster_path"] = serde_json::Value::String("/assets/no-poster.png".to_string()),
+                        Err(e) => log_error!("Failed to extract embedded cover: {}", e),
+                    }
+                }
+            }
+
             let video: VideoInfo = VideoInfo {
-                id: id,
+                id,
                 original_title: video_info.get("original_title").and_then(|v| v.as_str()).unwrap_or(&file_name).to_string(),
                 title: video_info.get("title").and_then(|v| v.as_str()).unwrap_or(&series_info.series_title).to_string(),
                 thumbnail: video_info.get("poster_path").and_then(|v| v.as_str()).unwrap_or("").to_string(),
-                duration: formatted_duration.unwrap_or_else(|_| "Unknown".to_string()),
+                duration: formatted_duration,
                 path: path.to_string_lossy().to_string(),
                 category: if series_info.is_series { "剧集" } else { "电影" }.to_string(),
                 description: video_info.get("overview").and_then(|v| v.as_str()).unwrap_or("").to_string(),
@@ -167,48 +583,354 @@ async fn scan_folder(path: String, db: State<'_, DbState>, settings: Settings) -
                 favorite: false,
                 tags: video_info.get("genres").and_then(|v| v.as_str()).unwrap_or("未分类").to_string(),
                 is_series: series_info.is_series,
-                season: series_info.season,
-                episode: series_info.episode,
+                // 按播出日期命名的剧集以 TMDb 中对应的季数和集数为准
+                season: video_info.get("season").and_then(|v| v.as_i64()).map_or(series_info.season, |v| v as i32),
+                episode: video_info.get("episode").and_then(|v| v.as_i64()).map_or(series_info.episode, |v| v as i32),
                 episode_title: video_info.get("episode_title").and_then(|v| v.as_str()).unwrap_or(&series_info.series_title).to_string(),
                 episode_overview: video_info.get("episode_overview").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                file_size,
+                mtime,
+                fingerprint,
+                status: db::STATUS_OK.to_string(),
+                library_id,
+                part_of: String::new(),
+                part_number: 0,
+                episode_end: video_info.get("episode_end").and_then(|v| v.as_i64()).map_or(series_info.episode_end, |v| v as i32),
+                air_date: series_info.air_date.clone(),
+                parts: Vec::new(),
             };
 
-            let binding = db.clone();
-            let new_videos_clone = new_videos.clone();
-            match tokio::task::spawn_blocking(move || {
-                let conn = binding.lock().unwrap();
-                let _ = db::insert_video(&conn, &video);
-                new_videos_clone.lock().unwrap().push(video);
-            }).await {
-                Ok(result) => result,
-                Err(e) => {
-                    log_error!("Failed to check video existence: {}", e);
-                    continue;
-                }
+            Ok(FileAnalysis::New(NewVideo { video, tracks, chapters, quality, release }))
+        }
+    }
+}
+
+/// 分析附加内容文件：新文件或变化的文件读取时长后保存，所属视频在扫描结束后关联
+async fn analyze_extra_file(path: PathBuf, id: String, extra: &video::ExtraMatch, file_size: i64, mtime: i64, rescan: bool, db: Arc<Mutex<rusqlite::Connection>>) -> Result<FileAnalysis, String> {
+    let db_clone = db.clone();
+    let id_clone = id.clone();
+    let stored = tokio::task::spawn_blocking(move || {
+        let conn = db_clone.lock().unwrap();
+        db::get_video_extra(&conn, &id_clone).unwrap_or_default()
+    }).await.map_err(|e| e.to_string())?;
+    if stored.as_ref().is_some_and(|stored| !rescan || (stored.file_size, stored.mtime) == (file_size, mtime)) {
+        return Ok(FileAnalysis::Done(ScanOutcome::Unchanged));
+    }
+
+    let path_str = path.to_string_lossy().to_string();
+    let video_metadata = read_file_blocking(move || video::probe_video(&path_str)).await?;
+    let extra = VideoExtra {
+        id,
+        video_id: stored.as_ref().map(|stored| stored.video_id.clone()).unwrap_or_default(),
+        extra_type: extra.extra_type.to_string(),
+        title: path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+        path: path.to_string_lossy().to_string(),
+        duration: video::format_duration(video_metadata.as_ref().map(|m| m.video_duration_seconds).unwrap_or(0.0)),
+        file_size,
+        mtime,
+        create_time: stored.map_or_else(|| chrono::Utc::now().timestamp(), |stored| stored.create_time),
+    };
+    let extra = tokio::task::spawn_blocking(move || {
+        let mut conn = db.lock().unwrap();
+        db::save_video_extra(&mut conn, &extra).map_err(|e| e.to_string())?;
+        Ok::<_, String>(extra)
+    }).await.map_err(|e| e.to_string())??;
+    log_info!("Found {} extra: {}", extra.extra_type, extra.path);
+    Ok(FileAnalysis::Done(ScanOutcome::Extra(extra)))
+}
+
+/// 为目录（包括子目录）下的附加内容关联所属视频，所属视频不存在时取消关联
+fn link_video_extras(conn: &mut rusqlite::Connection, dir: &str) -> Result<(), String> {
+    let extras = db::get_extras_under_path(conn, dir).map_err(|e| e.to_string())?;
+    // 按所属视频所在的目录缓存候选视频
+    let mut candidates: std::collections::HashMap<PathBuf, Vec<VideoInfo>> = std::collections::HashMap::new();
+    let mut links = Vec::new();
+    for extra in extras {
+        let path = Path::new(&extra.path);
+        let Some(matched) = video::parse_extra(path) else { continue };
+        let Some(parent_dir) = video::extra_parent_dir(path, &matched) else { continue };
+        if !candidates.contains_key(parent_dir) {
+            let videos = db::get_videos_under_path(conn, &parent_dir.to_string_lossy()).map_err(|e| e.to_string())?;
+            candidates.insert(parent_dir.to_path_buf(), videos);
+        }
+        let video_id = video::choose_extra_parent(path, &matched, &candidates[parent_dir])
+            .map(|video| video.id.clone())
+            .unwrap_or_default();
+        if video_id != extra.video_id {
+            links.push((extra.id, video_id));
+        }
+    }
+    if !links.is_empty() {
+        db::update_extra_parents(conn, &links).map_err(|e| e.to_string())?;
+        log_info!("Linked {} extras under {}", links.len(), dir);
+    }
+    Ok(())
+}
+
+/// 扫描文件夹并添加新视频，`rescan` 的含义见 [`analyze_video_file`]。
+///
+/// 先查找所有视频文件，再同时处理至多 [`SCAN_CONCURRENCY`] 个文件，新视频按批在事务中入库，
+/// 期间通过事件发送进度和处理失败的文件。
+/// 扫描被取消时不再处理新的文件，正在处理的文件完成后停止，已处理的视频保留。
+#[tauri::command]
+async fn scan_folder(path: String, rescan: Option<bool>, db: State<'_, DbState>, settings: Settings, app_handle: tauri::AppHandle) -> Result<ScanReport, String> {
+    let job = app_handle.state::<ScanJob>();
+    let _guard = job.start()?;
+    let db = db.0.clone();
+    let rescan = rescan.unwrap_or(false);
+    let extensions = video_extensions(&settings);
+    let settings = Arc::new(settings);
+    // 内嵌封面保存在应用缓存目录中
+    let cover_dir = app_handle.path().app_cache_dir().ok().map(|dir| dir.join("covers"));
+    let libraries = {
+        let conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::get_all_libraries(&conn).map_err(|e| e.to_string())?
+    };
+    let mut report = ScanReport::default();
+    let mut progress = ScanProgress { root: path.clone(), ..Default::default() };
+
+    // 先查找所有视频文件，前端可以显示总数
+    // 扫描资料库内的目录时使用资料库的扫描规则，其他目录使用默认规则
+    let filter = match library_for_path(&libraries, Path::new(&path)) {
+        Some(library) => ScanFilter::new(Path::new(&library.path), &library.rules, &extensions)?,
+        None => ScanFilter::new(Path::new(&path), &db::ScanRules::default(), &extensions)?,
+    };
+    let mut files = Vec::new();
+    for file in filter.walk(Path::new(&path)) {
+        if job.is_cancelled() {
+            break;
+        }
+        files.push(file);
+        progress.discovered = files.len();
+        if files.len() % 50 == 0 {
+            emit_event(&app_handle, EVENT_SCAN_PROGRESS, progress.clone());
+        }
+    }
+    progress.discovery_done = true;
+
+    let mut files = files.into_iter();
+    let mut tasks = tokio::task::JoinSet::new();
+    let mut pending = Vec::new();
+    loop {
+        // 补充任务直到达到并发上限，取消后不再开始新的文件
+        while tasks.len() < SCAN_CONCURRENCY && !job.is_cancelled() {
+            let path = match files.next() {
+                Some(path) => path,
+                None => break,
             };
+            let library = library_for_path(&libraries, &path).cloned();
+            let (db, settings, cover_dir) = (db.clone(), settings.clone(), cover_dir.clone());
+            tasks.spawn(async move {
+                let result = analyze_video_file(path.clone(), rescan, db, &settings, library.as_ref(), cover_dir.as_deref()).await;
+                (path, result)
+            });
         }
+
+        let (path, result) = match tasks.join_next().await {
+            Some(Ok(joined)) => joined,
+            Some(Err(e)) => {
+                log_error!("Scan task failed: {}", e);
+                progress.processed += 1;
+                progress.errors += 1;
+                continue;
+            }
+            None => break,
+        };
+        progress.processed += 1;
+        progress.current = path.to_string_lossy().to_string();
+        match result {
+            Ok(FileAnalysis::Done(outcome)) => report.record(outcome),
+            Ok(FileAnalysis::New(new_video)) => pending.push(new_video),
+            Err(e) => {
+                log_error!("Failed to process video {}: {}", path.display(), e);
+                progress.errors += 1;
+                emit_event(&app_handle, EVENT_SCAN_ERROR, ScanError { path: progress.current.clone(), error: e });
+            }
+        }
+        if pending.len() >= INSERT_BATCH_SIZE {
+            flush_new_videos(&db, &mut pending, &mut report, &mut progress, &app_handle).await;
+        }
+        emit_event(&app_handle, EVENT_SCAN_PROGRESS, progress.clone());
+    }
+    flush_new_videos(&db, &mut pending, &mut report, &mut progress, &app_handle).await;
+    progress.current.clear();
+    emit_event(&app_handle, EVENT_SCAN_PROGRESS, progress);
+
+    // 各段和附加内容可能由不同的任务处理，全部入库后再识别多段视频、关联附加内容
+    let db_clone = db.clone();
+    let root = path.clone();
+    match tokio::task::spawn_blocking(move || {
+        let mut conn = db_clone.lock().unwrap();
+        let regrouped = regroup_video_parts(&mut conn, &root)?;
+        link_video_extras(&mut conn, &root)?;
+        Ok::<_, String>(regrouped)
+    }).await {
+        Ok(Ok(regrouped)) => report.record_regrouped(regrouped),
+        Ok(Err(e)) => log_error!("Failed to group multi-part videos and extras: {}", e),
+        Err(e) => log_error!("Failed to group multi-part videos and extras: {}", e),
     }
 
-    // 最后获取所有视频
-    let new_videos = new_videos.lock().unwrap().clone();
-    Ok(new_videos)
-    // tokio::task::spawn_blocking(move || {
-    //     let conn = db.lock().unwrap();
-    //     db::get_all_videos(&conn).map_err(|e| e.to_string())
-    // }).await.unwrap()
+    report.cancelled = job.is_cancelled();
+    log_info!("Scan {}: {} added, {} updated, {} moved, {} extras, {} unchanged",
+        if report.cancelled { "cancelled" } else { "finished" },
+        report.added.len(), report.updated.len(), report.moved.len(), report.extras.len(), report.unchanged);
+    Ok(report)
 }
 
+/// 将累积的新视频写入数据库，写入失败时这一批视频都记为处理失败
+async fn flush_new_videos(db: &Arc<Mutex<rusqlite::Connection>>, pending: &mut Vec<NewVideo>, report: &mut ScanReport, progress: &mut ScanProgress, app_handle: &tauri::AppHandle) {
+    if pending.is_empty() {
+        return;
+    }
+    let batch = std::mem::take(pending);
+    let db = db.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let mut conn = db.lock().unwrap();
+        match save_new_videos(&mut conn, &batch) {
+            Ok(()) => Ok(batch),
+            Err(e) => Err((e, batch)),
+        }
+    }).await;
+
+    match result {
+        Ok(Ok(batch)) => report.added.extend(batch.into_iter().map(|new_video| new_video.video)),
+        Ok(Err((e, batch))) => {
+            log_error!("Failed to save {} new videos: {}", batch.len(), e);
+            progress.errors += batch.len();
+            for new_video in batch {
+                emit_event(app_handle, EVENT_SCAN_ERROR, ScanError { path: new_video.video.path, error: e.clone() });
+            }
+        }
+        Err(e) => log_error!("Failed to save new videos: {}", e),
+    }
+}
+
+/// 取消正在进行的扫描，正在处理的文件完成后停止。没有扫描在进行时返回 false
 #[tauri::command]
-async fn select_and_scan_folder(app_state: State<'_, AppState>, db: State<'_, DbState>) -> Result<Vec<VideoInfo>, String> {
+async fn cancel_scan(job: State<'_, ScanJob>) -> Result<bool, String> {
+    if !job.running.load(Ordering::SeqCst) {
+        return Ok(false);
+    }
+    job.cancelled.store(true, Ordering::SeqCst);
+    log_info!("Scan cancel requested");
+    Ok(true)
+}
+
+#[tauri::command]
+async fn select_and_scan_folder(rescan: Option<bool>, app_state: State<'_, AppState>, db: State<'_, DbState>, app_handle: tauri::AppHandle) -> Result<ScanReport, String> {
     if let Some(path) = rfd::FileDialog::new().pick_folder() {
         let settings = {
             let settings_guard = app_state.settings.lock().unwrap();
             settings_guard.clone()
         };
-        scan_folder(path.to_string_lossy().to_string(), db, settings).await
+        scan_folder(path.to_string_lossy().to_string(), rescan, db, settings, app_handle).await
     } else {
-        Ok(vec![]) // 用户取消选择
+        Ok(ScanReport::default()) // 用户取消选择
+    }
+}
+
+/// 扫描资料库，完成后记录扫描时间，并将目录下此前扫描过的视频归入该资料库
+async fn scan_library(library: &Library, rescan: bool, app_state: State<'_, AppState>, db: State<'_, DbState>, app_handle: tauri::AppHandle) -> Result<ScanReport, String> {
+    let settings = app_state.settings.lock().unwrap().clone();
+    let db_clone = db.0.clone();
+    let report = scan_folder(library.path.clone(), Some(rescan), db, settings, app_handle).await?;
+    if report.cancelled {
+        return Ok(report);
+    }
+
+    let conn = db_clone.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+    db::finish_library_scan(&conn, library, chrono::Utc::now().timestamp()).map_err(|e| e.to_string())?;
+    Ok(report)
+}
+
+/// 添加资料库：扫描目录后持续监听其中的变化。
+/// 未指定路径时弹出文件夹选择框，用户取消选择时返回None
+#[tauri::command]
+async fn add_library(path: Option<String>, app_state: State<'_, AppState>, db: State<'_, DbState>, watcher: State<'_, LibraryWatcher>, app_handle: tauri::AppHandle) -> Result<Option<AddedLibrary>, String> {
+    let path = match path.map(PathBuf::from).or_else(|| rfd::FileDialog::new().pick_folder()) {
+        Some(path) => path,
+        None => return Ok(None), // 用户取消选择
+    };
+    if !path.is_dir() {
+        return Err(format!("资料库目录不存在：{}", path.display()));
+    }
+
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let library = {
+        let conn = db.0.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::insert_library(&conn, &path.to_string_lossy(), &name).map_err(|e| e.to_string())?
+    };
+    watcher.watch(&path)?;
+
+    let report = scan_library(&library, false, app_state, db.clone(), app_handle).await?;
+    let library = {
+        let conn = db.0.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::get_library(&conn, library.id).map_err(|e| e.to_string())?.unwrap_or(library)
+    };
+    Ok(Some(AddedLibrary { library, report }))
+}
+
+/// 更新资料库的名称、内容类型、语言、启用状态和扫描规则，启用状态变化时开始或停止监听
+#[tauri::command]
+async fn update_library(library: Library, db: State<'_, DbState>, watcher: State<'_, LibraryWatcher>) -> Result<Library, String> {
+    let conn = db.0.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+    let stored = db::get_library(&conn, library.id).map_err(|e| e.to_string())?
+        .ok_or_else(|| "资料库不存在".to_string())?;
+    if ![db::CONTENT_MOVIE, db::CONTENT_SERIES, db::CONTENT_MIXED].contains(&library.content_type.as_str()) {
+        return Err(format!("未知的内容类型：{}", library.content_type));
+    }
+    // 提前检查模式是否有效，避免扫描时才出错
+    ScanFilter::new(Path::new(&stored.path), &library.rules, &[])?;
+    db::update_library(&conn, &library).map_err(|e| e.to_string())?;
+
+    if library.enabled != stored.enabled {
+        let path = Path::new(&stored.path);
+        let result = if library.enabled { watcher.watch(path) } else { watcher.unwatch(path) };
+        if let Err(e) = result {
+            log_error!("Failed to update watch for library {}: {}", stored.path, e);
+        }
+    }
+    db::get_library(&conn, library.id).map_err(|e| e.to_string())?
+        .ok_or_else(|| "资料库不存在".to_string())
+}
+
+/// 重新扫描资料库，文件有变化的视频重新解析
+#[tauri::command]
+async fn rescan_library(id: i64, app_state: State<'_, AppState>, db: State<'_, DbState>, app_handle: tauri::AppHandle) -> Result<ScanReport, String> {
+    let library = {
+        let conn = db.0.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::get_library(&conn, id).map_err(|e| e.to_string())?
+            .ok_or_else(|| "资料库不存在".to_string())?
+    };
+    if !library.enabled {
+        return Err(format!("资料库已停用：{}", library.name));
+    }
+    if !Path::new(&library.path).is_dir() {
+        return Err(format!("资料库目录不可用：{}", library.path));
     }
+    scan_library(&library, true, app_state, db, app_handle).await
+}
+
+/// 移除资料库并停止监听，已入库的视频保留
+#[tauri::command]
+async fn remove_library(id: i64, db: State<'_, DbState>, watcher: State<'_, LibraryWatcher>) -> Result<(), String> {
+    let library = {
+        let conn = db.0.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::delete_library(&conn, id).map_err(|e| e.to_string())?
+    };
+    if let Some(library) = library.filter(|library| library.enabled) {
+        // 目录已被删除或磁盘离线时监听早已失效
+        if let Err(e) = watcher.unwatch(Path::new(&library.path)) {
+            log_error!("Failed to unwatch library {}: {}", library.path, e);
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn list_libraries(db: State<'_, DbState>) -> Result<Vec<Library>, String> {
+    let conn = db.0.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+    db::get_all_libraries(&conn).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -217,15 +939,105 @@ async fn get_cached_videos(db: State<'_, DbState>) -> Result<Vec<VideoInfo>, Str
         Ok(lock) => lock,
         Err(_) => return Err("Failed to acquire database lock".to_string()),
     };
-    db::get_all_videos(&conn).map_err(|e| e.to_string())
+    // 多段视频只返回主视频
+    db::get_all_videos(&conn).map(video::merge_video_parts).map_err(|e| e.to_string())
 }
 
+/// 检查所有视频的文件是否存在，更新状态发生变化的记录。
+fn verify_videos(db: &Arc<Mutex<rusqlite::Connection>>) -> Result<HealthReport, String> {
+    let videos = {
+        let conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::get_all_videos(&conn).map_err(|e| e.to_string())?
+    };
+
+    // 检查文件时不持有数据库锁，离线的网络位置可能需要等待较长时间
+    let mut report = HealthReport { total: videos.len(), ..Default::default() };
+    let mut changed = Vec::new();
+    for mut video in videos {
+        let status = video::file_status(Path::new(&video.path));
+        if video.status != status {
+            changed.push((video.id.clone(), status.to_string()));
+            video.status = status.to_string();
+        }
+        match status {
+            db::STATUS_MISSING => report.missing.push(video),
+            db::STATUS_OFFLINE => report.offline.push(video),
+            _ => report.ok += 1,
+        }
+    }
+
+    if !changed.is_empty() {
+        let mut conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::update_video_statuses(&mut conn, &changed).map_err(|e| e.to_string())?;
+    }
+    log_info!("Verified {} videos: {} missing, {} offline", report.total, report.missing.len(), report.offline.len());
+    Ok(report)
+}
+
+/// 检查所有视频文件，返回资料库健康报告
+#[tauri::command]
+async fn get_health_report(db: State<'_, DbState>) -> Result<HealthReport, String> {
+    let db = db.0.clone();
+    tokio::task::spawn_blocking(move || verify_videos(&db)).await.map_err(|e| e.to_string())?
+}
+
+/// 重新检查后删除所有文件已被删除的视频，磁盘离线的视频保留，返回被删除的视频ID
 #[tauri::command]
-async fn play_video(mut video: VideoInfo, app_handle: tauri::AppHandle) -> Result<(), String> {
+async fn purge_missing_videos(db: State<'_, DbState>) -> Result<Vec<String>, String> {
+    let db = db.0.clone();
+    tokio::task::spawn_blocking(move || {
+        verify_videos(&db)?;
+        let mut conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::delete_missing_videos(&mut conn).map_err(|e| e.to_string())
+    }).await.map_err(|e| e.to_string())?
+}
+
+/// 播放视频，多段视频的各段按顺序作为播放列表交给播放器
+#[tauri::command]
+async fn play_video(mut video: VideoInfo, start_time: Option<f64>, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let mut playlist = vec![(video.id.clone(), video.path.clone())];
+    {
+        let db = app_handle.state::<DbState>();
+        let conn = db.0.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        let parts = db::get_video_parts(&conn, &video.id).map_err(|e| e.to_string())?;
+        playlist.extend(parts.into_iter().map(|part| (part.id, part.path)));
+    }
+
+    // 文件不可用时记录状态并给出明确的提示，而不是让播放器打开失败
+    for (id, path) in &playlist {
+        let status = video::file_status(Path::new(path));
+        if status == db::STATUS_OK {
+            continue;
+        }
+        let db = app_handle.state::<DbState>();
+        if let Ok(mut conn) = db.0.lock() {
+            if let Err(e) = db::update_video_statuses(&mut conn, &[(id.clone(), status.to_string())]) {
+                log_error!("Failed to update video status: {}", e);
+            }
+        }
+        return Err(if status == db::STATUS_OFFLINE {
+            format!("视频所在的磁盘或网络位置不可用，请连接后重试：{}", path)
+        } else {
+            format!("视频文件不存在，可能已被删除或移动：{}", path)
+        });
+    }
+
     let app_state = app_handle.state::<AppState>();
     let settings = app_state.settings.lock().unwrap();
-    let path = video.path.clone();
+    // 多段视频交给系统默认播放器时使用 m3u 播放列表
+    let path = if playlist.len() > 1 && settings.player_path.as_deref().unwrap_or("").is_empty() {
+        video::write_playlist(&video.id, playlist.iter().map(|(_, path)| path.as_str()))?
+            .to_string_lossy()
+            .to_string()
+    } else {
+        video.path.clone()
+    };
     let subtitle_path = video::find_subtitles(&video).unwrap_or_default();
+    let tracks = {
+        let db = app_handle.state::<DbState>();
+        let conn = db.0.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::get_video_tracks(&conn, &video.id).unwrap_or_default()
+    };
 
     video.play_count += 1;
     video.last_play_time = chrono::Utc::now().timestamp();
@@ -233,6 +1045,29 @@ async fn play_video(mut video: VideoInfo, app_handle: tauri::AppHandle) -> Resul
 
     // 检查是否自动加载字幕
     let auto_subtitle = settings.auto_subtitle.clone().unwrap_or(false);
+    let subtitle_path = (auto_subtitle && !subtitle_path.is_empty()).then_some(subtitle_path.as_str());
+    // 没有外部字幕时选择设置语言的内嵌字幕
+    let subtitle_language = settings.subtitle_language.as_deref().unwrap_or("eng");
+    let embedded_subtitle = (auto_subtitle && subtitle_path.is_none())
+        .then(|| video::choose_embedded_subtitle(&tracks, subtitle_language))
+        .flatten();
+    if let Some(index) = embedded_subtitle {
+        log_debug!("No external subtitle, using embedded subtitle track {}", index);
+    }
+    let playlist: Vec<&str> = playlist.iter().map(|(_, path)| path.as_str()).collect();
+    launch_player(&settings, &playlist, &path, subtitle_path, embedded_subtitle, start_time)
+}
+
+/// 使用设置中的播放器打开视频
+///
+/// # 参数
+/// * `settings` - 应用设置，决定播放器和字幕语言
+/// * `playlist` - 按顺序播放的视频文件，VLC 依次播放，光盘以 `bluray://`、`dvd://` 地址打开
+/// * `system_path` - 交给系统默认播放器打开的文件，多段视频为播放列表文件，光盘文件夹打开正片文件
+/// * `subtitle_path` - 外部字幕文件
+/// * `embedded_subtitle` - 没有外部字幕时使用的内嵌字幕，为字幕轨道中的序号
+/// * `start_time` - 开始播放的时间（秒），系统默认播放器不支持
+fn launch_player(settings: &Settings, playlist: &[&str], system_path: &str, subtitle_path: Option<&str>, embedded_subtitle: Option<usize>, start_time: Option<f64>) -> Result<(), String> {
     let subtitle_language = settings.subtitle_language.clone().unwrap_or_else(|| "eng".to_string());
 
     match &settings.player_path {
@@ -240,12 +1075,18 @@ async fn play_video(mut video: VideoInfo, app_handle: tauri::AppHandle) -> Resul
             match settings.player_type.as_deref() {
                 Some("vlc") => {
                     let mut command = Command::new(player_path);
-                    command.arg(&path); // 指定视频文件
+                    // 指定视频文件，多段视频依次播放
+                    command.args(playlist.iter().map(|path| disc::player_url(Path::new(path)).unwrap_or_else(|| path.to_string())));
                     
-                    if auto_subtitle {
-                        command.arg("--sub-file").arg(&subtitle_path); // 指定字幕文件
+                    if let Some(subtitle_path) = subtitle_path {
+                        command.arg("--sub-file").arg(subtitle_path); // 指定字幕文件
+                    } else if let Some(index) = embedded_subtitle {
+                        command.arg(format!("--sub-track={}", index)); // 指定内嵌字幕轨道
                     }
                     command.arg("--sub-language").arg(&subtitle_language); // 指定字幕语言
+                    if let Some(start_time) = start_time.filter(|t| *t > 0.0) {
+                        command.arg(format!("--start-time={:.3}", start_time)); // 从指定章节开始播放
+                    }
                     command.arg("--fullscreen"); // 全屏播放（可选）
                     command.spawn().map_err(|e| e.to_string())?;
                 }
@@ -257,23 +1098,32 @@ async fn play_video(mut video: VideoInfo, app_handle: tauri::AppHandle) -> Resul
         }
         _ => {
             // 如果没有设置播放器路径，使用系统默认播放器
+            if start_time.is_some() {
+                log_info!("System default player does not support start time, playing from beginning");
+            }
+            // 系统默认播放器会把光盘文件夹当作普通文件夹打开
+            let main_file = Path::new(system_path).is_dir()
+                .then(|| disc::probe_disc(Path::new(system_path)))
+                .flatten()
+                .map(|disc| disc.main_file.to_string_lossy().to_string());
+            let system_path = main_file.as_deref().unwrap_or(system_path);
             #[cfg(target_os = "windows")]
             let status = Command::new("cmd")
                 .arg("/C")
                 .arg("start")
-                .arg(&path)
+                .arg(system_path)
                 .status()
                 .expect("Failed to open video");
         
             #[cfg(target_os = "macos")]
             let status = Command::new("open")
-                .arg(&path)
+                .arg(system_path)
                 .status()
                 .expect("Failed to open video");
         
             #[cfg(target_os = "linux")]
             let status = Command::new("xdg-open")
-                .arg(&path)
+                .arg(system_path)
                 .status()
                 .expect("Failed to open video");
 
@@ -285,6 +1135,23 @@ async fn play_video(mut video: VideoInfo, app_handle: tauri::AppHandle) -> Resul
     Ok(())
 }
 
+/// 获取视频的附加内容
+#[tauri::command]
+async fn get_video_extras(id: String, db: State<'_, DbState>) -> Result<Vec<VideoExtra>, String> {
+    let conn = db.0.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+    db::get_video_extras(&conn, &id).map_err(|e| e.to_string())
+}
+
+/// 播放附加内容，不记录播放次数
+#[tauri::command]
+async fn play_extra(extra: VideoExtra, app_handle: tauri::AppHandle) -> Result<(), String> {
+    if video::file_status(Path::new(&extra.path)) != db::STATUS_OK {
+        return Err(format!("视频文件不可用：{}", extra.path));
+    }
+    let settings = app_handle.state::<AppState>().settings.lock().unwrap().clone();
+    launch_player(&settings, &[&extra.path], &extra.path, None, None, None)
+}
+
 #[tauri::command]
 async fn save_settings(settings: Settings, app_handle: tauri::AppHandle) -> Result<(), String> {
     let config_dir = app_handle.path().app_config_dir().unwrap();
@@ -326,7 +1193,8 @@ async fn load_settings(app_handle: tauri::AppHandle) -> Result<Settings, String>
             subtitle_language: Some("eng".to_string()),
             tmdb_api_key: None,
             auto_tmdb: Some(false),
-            auto_tmdb_poster: Some(false)
+            auto_tmdb_poster: Some(false),
+            extra_video_extensions: None,
         })
     }
 }
@@ -337,6 +1205,10 @@ fn remove_video(db: State<'_, DbState>, id: String) -> Result<(), String> {
         Ok(lock) => lock,
         Err(_) => return Err("Failed to acquire database lock".to_string()),
     };
+    // 多段视频的其他段一并删除
+    for part in db::get_video_parts(&conn, &id).map_err(|e| e.to_string())? {
+        db::delete_video(&conn, &part.id).map_err(|e| e.to_string())?;
+    }
     db::delete_video(&conn, &id).map_err(|e| e.to_string())
 }
 
@@ -366,6 +1238,97 @@ fn update_video(db: State<'_, DbState>, video: VideoInfo) -> Result<(), String>
     db::update_video(&conn, &video).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn get_video_tracks(id: String, path: String, db: State<'_, DbState>) -> Result<Vec<VideoTrack>, String> {
+    let db = db.0.clone();
+    tokio::task::spawn_blocking(move || {
+        {
+            let conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+            if db::video_tracks_probed(&conn, &id).map_err(|e| e.to_string())? {
+                return db::get_video_tracks(&conn, &id).map_err(|e| e.to_string());
+            }
+        }
+
+        // 早期扫描的视频没有轨道记录，首次查看时补充解析，同时补充画质概况。
+        // 文件暂时无法访问时不记录解析结果，以便下次重试
+        let metadata = video::probe_video(&path);
+        if metadata.is_none() && !Path::new(&path).exists() {
+            return Ok(Vec::new());
+        }
+        let tracks = metadata.as_ref()
+            .map(|metadata| video::build_video_tracks(&id, metadata))
+            .unwrap_or_default();
+        let conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::save_video_tracks(&conn, &id, &tracks).map_err(|e| e.to_string())?;
+        if let Some(quality) = metadata.as_ref().and_then(|metadata| video::build_video_quality(&id, metadata)) {
+            db::save_video_quality(&conn, &quality).map_err(|e| e.to_string())?;
+        }
+        Ok(tracks)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn get_video_qualities(db: State<'_, DbState>) -> Result<Vec<VideoQuality>, String> {
+    let db = db.0.clone();
+    tokio::task::spawn_blocking(move || {
+        let conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::get_all_video_qualities(&conn).map_err(|e| e.to_string())
+    }).await.map_err(|e| e.to_string())?
+}
+
+/// 获取所有视频的发布信息，早期扫描的视频没有记录时按文件名补充
+#[tauri::command]
+async fn get_video_releases(db: State<'_, DbState>) -> Result<Vec<VideoRelease>, String> {
+    let db = db.0.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        let mut releases = db::get_all_video_releases(&conn).map_err(|e| e.to_string())?;
+        let known: std::collections::HashSet<String> = releases.iter().map(|release| release.video_id.clone()).collect();
+        let missing: Vec<VideoRelease> = db::get_all_videos(&conn).map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|video| !known.contains(&video.id))
+            .map(|video| {
+                let path = Path::new(&video.path);
+                build_release(&video.id, path, disc::disc_kind(path))
+            })
+            .collect();
+        if !missing.is_empty() {
+            let tx = conn.transaction().map_err(|e| e.to_string())?;
+            for release in &missing {
+                db::save_video_release(&tx, release).map_err(|e| e.to_string())?;
+            }
+            tx.commit().map_err(|e| e.to_string())?;
+        }
+        releases.extend(missing);
+        Ok(releases)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn get_video_chapters(id: String, path: String, db: State<'_, DbState>) -> Result<Vec<VideoChapter>, String> {
+    let db = db.0.clone();
+    tokio::task::spawn_blocking(move || {
+        {
+            let conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+            if db::video_chapters_probed(&conn, &id).map_err(|e| e.to_string())? {
+                return db::get_video_chapters(&conn, &id).map_err(|e| e.to_string());
+            }
+        }
+
+        // 早期扫描的视频没有章节记录，首次查看时补充解析，文件暂时无法访问时下次重试
+        let metadata = video::probe_video(&path);
+        if metadata.is_none() && !Path::new(&path).exists() {
+            return Ok(Vec::new());
+        }
+        let chapters = metadata
+            .map(|metadata| video::build_video_chapters(&id, &metadata))
+            .unwrap_or_default();
+        let conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+        db::save_video_chapters(&conn, &id, &chapters).map_err(|e| e.to_string())?;
+        Ok(chapters)
+    }).await.map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 async fn get_video_duration(path: String) -> Result<String, String> {
     tokio::task::spawn_blocking(move || {
@@ -423,16 +1386,45 @@ pub fn run() {
                 settings: Arc::new(Mutex::new(settings)),
             };
             app.manage(app_state);
+            app.manage(ScanJob::default());
+
+            // 监听已添加的资料库
+            let library_watcher = LibraryWatcher::new(handle.clone())?;
+            let libraries = {
+                let db = app.state::<DbState>();
+                let conn = db.0.lock().unwrap();
+                db::get_all_libraries(&conn)?
+            };
+            for library in libraries.into_iter().filter(|library| library.enabled) {
+                if let Err(e) = library_watcher.watch(Path::new(&library.path)) {
+                    log_error!("Failed to watch library {}: {}", library.path, e);
+                }
+            }
+            app.manage(library_watcher);
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             select_and_scan_folder,
             scan_folder,
+            cancel_scan,
             get_cached_videos,
             get_video_duration,
+            get_video_tracks,
+            get_video_chapters,
+            get_video_extras,
+            get_video_qualities,
+            get_video_releases,
+            get_health_report,
+            purge_missing_videos,
+            add_library,
+            update_library,
+            rescan_library,
+            remove_library,
+            list_libraries,
             update_video,
             play_video,
+            play_extra,
             remove_video,
             delete_folder_if_exists,
             save_settings,
@@ -441,3 +1433,63 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{memory_db, test_video};
+
+    #[test]
+    fn test_is_file_unchanged() {
+        let conn = memory_db();
+        let video = VideoInfo { file_size: 1000, mtime: 1_700_000_000, ..test_video("a", "/videos/a.mkv") };
+        db::insert_video(&conn, &video).unwrap();
+        let stored = db::get_video_file_state(&conn, "a").unwrap().unwrap();
+        assert_eq!(stored, (1000, 1_700_000_000));
+        assert_eq!(db::get_video_file_state(&conn, "b").unwrap(), None);
+
+        // (当前大小, 当前修改时间, 重新扫描, 未变化)
+        let cases = [
+            (1000, 1_700_000_000, true, true),
+            (1001, 1_700_000_000, true, false),
+            (1000, 1_700_000_001, true, false),
+            (1001, 1_700_000_001, false, true),
+        ];
+        for (size, mtime, rescan, unchanged) in cases {
+            assert_eq!(is_file_unchanged(stored, (size, mtime), rescan), unchanged, "{} {} {}", size, mtime, rescan);
+        }
+
+        // 刷新后记录新的大小和修改时间
+        db::update_video_file(&conn, "a", "01:30:00", 1001, 1_700_000_001, "").unwrap();
+        let stored = db::get_video_file_state(&conn, "a").unwrap().unwrap();
+        assert!(is_file_unchanged(stored, (1001, 1_700_000_001), true));
+    }
+
+    #[test]
+    fn test_scan_report_record() {
+        let mut report = ScanReport::default();
+        report.record(ScanOutcome::Added(test_video("a", "/videos/a.mkv")));
+        report.record(ScanOutcome::Updated(test_video("b", "/videos/b.mkv")));
+        report.record(ScanOutcome::Moved(MovedVideo { old_id: "old".to_string(), video: test_video("c", "/videos/c.mkv") }));
+        report.record(ScanOutcome::Unchanged);
+        report.record(ScanOutcome::Unchanged);
+        assert_eq!(report.added.len(), 1);
+        assert_eq!(report.updated.len(), 1);
+        assert_eq!(report.moved.len(), 1);
+        assert!(report.extras.is_empty());
+        assert_eq!(report.unchanged, 2);
+
+        // 新增和更新的视频被替换，不在结果中的记为更新
+        let regrouped = vec![
+            VideoInfo { part_number: 1, ..test_video("a", "/videos/a.mkv") },
+            VideoInfo { part_of: "a".to_string(), part_number: 2, ..test_video("b", "/videos/b.mkv") },
+            VideoInfo { part_of: "a".to_string(), part_number: 3, ..test_video("d", "/videos/d.mkv") },
+        ];
+        report.record_regrouped(regrouped);
+        assert_eq!(report.added.len(), 1);
+        assert_eq!(report.added[0].part_number, 1);
+        assert_eq!(report.updated.iter().map(|video| video.id.as_str()).collect::<Vec<_>>(), ["b", "d"]);
+        assert_eq!(report.updated[0].part_of, "a");
+        assert_eq!(report.unchanged, 2);
+    }
+}
@@ -1,15 +1,92 @@
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+
+mod asf;
+mod avi;
+#[cfg(test)]
+mod fixtures;
+mod flv;
+mod mp4;
+mod ts;
 
 /// 定义 EBML 头部和一些常用的元素 ID 常量。
 const EBML_HEADER_ID: u32 = 0x1A45DFA3;
+const DOC_TYPE_ID: u32 = 0x4282;
 const SEGMENT_ID: u32 = 0x18538067;
 const INFO_ID: u32 = 0x1549A966;
 const DURATION_ID: u32 = 0x4489;
 const TIMECODE_SCALE_ID: u32 = 0x2AD7B1;
+const CLUSTER_ID: u32 = 0x1F43B675;
+const SEEK_HEAD_ID: u32 = 0x114D9B74;
+const SEEK_ID: u32 = 0x4DBB;
+const SEEK_ID_ID: u32 = 0x53AB;
+const SEEK_POSITION_ID: u32 = 0x53AC;
+const VOID_ID: u32 = 0xEC;
+const CRC32_ID: u32 = 0xBF;
+const TRACKS_ID: u32 = 0x1654AE6B;
+const TRACK_ENTRY_ID: u32 = 0xAE;
+const TRACK_TYPE_ID: u32 = 0x83;
+const CODEC_ID_ID: u32 = 0x86;
+const LANGUAGE_ID: u32 = 0x22B59C;
+const LANGUAGE_IETF_ID: u32 = 0x22B59D;
+const NAME_ID: u32 = 0x536E;
+const FLAG_DEFAULT_ID: u32 = 0x88;
+const FLAG_FORCED_ID: u32 = 0x55AA;
+const VIDEO_ID: u32 = 0xE0;
+const PIXEL_WIDTH_ID: u32 = 0xB0;
+const PIXEL_HEIGHT_ID: u32 = 0xBA;
+const AUDIO_ID: u32 = 0xE1;
+const SAMPLING_FREQUENCY_ID: u32 = 0xB5;
+const CHANNELS_ID: u32 = 0x9F;
+const CODEC_PRIVATE_ID: u32 = 0x63A2;
+const COLOUR_ID: u32 = 0x55B0;
+const MATRIX_COEFFICIENTS_ID: u32 = 0x55B1;
+const BITS_PER_CHANNEL_ID: u32 = 0x55B2;
+const TRANSFER_CHARACTERISTICS_ID: u32 = 0x55BA;
+const PRIMARIES_ID: u32 = 0x55BB;
+const MASTERING_METADATA_ID: u32 = 0x55D0;
+const BLOCK_ADDITION_MAPPING_ID: u32 = 0x41E4;
+const BLOCK_ADD_ID_TYPE_ID: u32 = 0x41E7;
+const BLOCK_ADD_ID_EXTRA_DATA_ID: u32 = 0x41ED;
+const CHAPTERS_ID: u32 = 0x1043A770;
+const EDITION_ENTRY_ID: u32 = 0x45B9;
+const EDITION_FLAG_HIDDEN_ID: u32 = 0x45BD;
+const EDITION_FLAG_DEFAULT_ID: u32 = 0x45DB;
+const CHAPTER_ATOM_ID: u32 = 0xB6;
+const CHAPTER_TIME_START_ID: u32 = 0x91;
+const CHAPTER_TIME_END_ID: u32 = 0x92;
+const CHAPTER_FLAG_HIDDEN_ID: u32 = 0x98;
+const CHAPTER_FLAG_ENABLED_ID: u32 = 0x4598;
+const CHAPTER_DISPLAY_ID: u32 = 0x80;
+const CHAP_STRING_ID: u32 = 0x85;
+const CHAP_LANGUAGE_ID: u32 = 0x437C;
+const CHAP_LANGUAGE_IETF_ID: u32 = 0x437D;
+const ATTACHMENTS_ID: u32 = 0x1941A469;
+const ATTACHED_FILE_ID: u32 = 0x61A7;
+const FILE_NAME_ID: u32 = 0x466E;
+const FILE_MIME_TYPE_ID: u32 = 0x4660;
+const FILE_DATA_ID: u32 = 0x465C;
+
+/// Info、Tracks 等需要整体读入内存的主元素的最大字节数。
+const MAX_MASTER_ELEMENT_SIZE: u64 = 16 * 1024 * 1024;
+/// EBML 头部的最大字节数，正常文件只有几十字节。
+const MAX_EBML_HEADER_SIZE: u64 = 4096;
+/// 最多跟随的 SeekHead 条目数，防止损坏的文件造成大量跳转。
+const MAX_SEEK_ENTRIES: usize = 256;
+/// ITU-T H.273 中的色彩参数取值：PQ（SMPTE ST 2084）、HLG（ARIB STD-B67）、BT.2020。
+const TRANSFER_PQ: u64 = 16;
+const TRANSFER_HLG: u64 = 18;
+const PRIMARIES_BT2020: u64 = 9;
+const MATRIX_BT2020_NCL: u64 = 9;
+const MATRIX_BT2020_CL: u64 = 10;
+/// Matroska 规范中 TimecodeScale 的默认值（1 毫秒）。
+const DEFAULT_TIMECODE_SCALE: u64 = 1_000_000;
+/// 内嵌封面图片的最大字节数。
+const MAX_COVER_ART_SIZE: u64 = 20 * 1024 * 1024;
 
-/// 读取 EBML 中的 VINT（可变长度整数）。
-fn read_vint<R: Read>(reader: &mut R) -> Result<u64, String> {
+/// 读取 EBML 中的 VINT（可变长度整数），同时返回其占用的字节数。
+fn read_vint_with_length<R: Read>(reader: &mut R) -> Result<(u64, usize), String> {
     let mut first_byte = [0u8; 1];
     reader.read_exact(&mut first_byte).map_err(|e| e.to_string())?;
 
@@ -34,7 +111,17 @@ fn read_vint<R: Read>(reader: &mut R) -> Result<u64, String> {
         value = (value << 8) | b as u64;
     }
 
-    Ok(value)
+    Ok((value, length))
+}
+
+/// 读取元素大小。数据位全为 1 表示大小未知（直播流或未完成写入的 Segment/Cluster），返回 None。
+fn read_element_size<R: Read>(reader: &mut R) -> Result<Option<u64>, String> {
+    let (value, length) = read_vint_with_length(reader)?;
+    if value == (1u64 << (7 * length)) - 1 {
+        Ok(None)
+    } else {
+        Ok(Some(value))
+    }
 }
 
 /// 读取 EBML 元素的 ID。
@@ -74,129 +161,1003 @@ fn bytes_to_f64(buffer: &[u8]) -> f64 {
     }
 }
 
+/// 轨道类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackType {
+    Video,
+    Audio,
+    Subtitle,
+    Other,
+}
+
+impl TrackType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrackType::Video => "video",
+            TrackType::Audio => "audio",
+            TrackType::Subtitle => "subtitle",
+            TrackType::Other => "other",
+        }
+    }
+}
+
+/// 单条轨道信息，各容器解析器统一输出该结构
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct TrackInfo {
+    pub track_type: TrackType,
+    /// 容器内的原始编码标识，如 `avc1`、`V_MPEGH/ISO/HEVC`
+    pub codec: String,
+    /// ISO 639-2 语言代码，未知时为 `und`
+    pub language: String,
+    /// BCP 47 语言标签（仅 MKV 提供），如 `zh-Hans`
+    pub language_ietf: String,
+    /// 轨道名称
+    pub name: String,
+    pub default: bool,
+    pub forced: bool,
+    pub width: u32,
+    pub height: u32,
+    pub channels: u32,
+    pub sampling_frequency: f64,
+    /// 视频位深，未知时为 0
+    pub bit_depth: u32,
+    pub hdr_format: HdrFormat,
+    /// Dolby Vision profile，非 Dolby Vision 或未知时为 0
+    pub dolby_vision_profile: u8,
+}
+
+impl TrackInfo {
+    /// 便于界面显示的编码名称，如 `HEVC`、`AAC`。
+    pub fn codec_name(&self) -> String {
+        codec_display_name(&self.codec)
+    }
+
+    pub(crate) fn new(track_type: TrackType, codec: &str) -> Self {
+        TrackInfo {
+            track_type,
+            codec: codec.to_string(),
+            language: "und".to_string(),
+            language_ietf: String::new(),
+            name: String::new(),
+            default: true,
+            forced: false,
+            width: 0,
+            height: 0,
+            channels: 0,
+            sampling_frequency: 0.0,
+            bit_depth: 0,
+            hdr_format: HdrFormat::Sdr,
+            dolby_vision_profile: 0,
+        }
+    }
+
+    /// 根据 Dolby Vision 配置记录（dvcC / dvvC / dvwC）标记为 Dolby Vision。
+    pub(crate) fn set_dolby_vision(&mut self, record: &[u8]) {
+        self.hdr_format = HdrFormat::DolbyVision;
+        // dv_version_major(8) + dv_version_minor(8) + dv_profile(7) + dv_level(6) ...
+        if let Some(byte) = record.get(2) {
+            self.dolby_vision_profile = byte >> 1;
+        }
+    }
+}
+
+/// HDR 格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HdrFormat {
+    #[default]
+    Sdr,
+    Hdr10,
+    Hlg,
+    DolbyVision,
+}
+
+impl HdrFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HdrFormat::Sdr => "SDR",
+            HdrFormat::Hdr10 => "HDR10",
+            HdrFormat::Hlg => "HLG",
+            HdrFormat::DolbyVision => "Dolby Vision",
+        }
+    }
+}
+
+/// 视频轨道的色彩参数，来自 MKV 的 Colour 元素或 MP4 的 colr / mdcv 盒子
+#[derive(Debug, Default)]
+pub(crate) struct ColourInfo {
+    pub transfer_characteristics: u64,
+    pub primaries: u64,
+    pub matrix_coefficients: u64,
+    pub has_mastering_metadata: bool,
+}
+
+impl ColourInfo {
+    /// 根据传输特性判断 HDR 格式。
+    /// 部分文件只写了母版元数据而没有传输特性，此时结合 BT.2020 色域视为 HDR10。
+    pub fn hdr_format(&self) -> HdrFormat {
+        match self.transfer_characteristics {
+            TRANSFER_PQ => HdrFormat::Hdr10,
+            TRANSFER_HLG => HdrFormat::Hlg,
+            _ if self.has_mastering_metadata
+                && (self.primaries == PRIMARIES_BT2020
+                    || matches!(self.matrix_coefficients, MATRIX_BT2020_NCL | MATRIX_BT2020_CL)) => HdrFormat::Hdr10,
+            _ => HdrFormat::Sdr,
+        }
+    }
+}
+
+/// 从编码配置记录（hvcC、avcC、av1C、vpcC）中读取位深。
+pub(crate) fn config_bit_depth(config_type: &[u8; 4], config: &[u8]) -> Option<u32> {
+    match config_type {
+        // HEVCDecoderConfigurationRecord：第 17 字节低 3 位为 bitDepthLumaMinus8
+        b"hvcC" => config.get(17).map(|b| (b & 0x07) as u32 + 8),
+        // AVCDecoderConfigurationRecord 只有 profile 可用，High 10 视为 10 位
+        b"avcC" => config.get(1).map(|profile| if *profile == 110 { 10 } else { 8 }),
+        // AV1CodecConfigurationRecord：high_bitdepth、twelve_bit 标志
+        b"av1C" => config.get(2).map(|b| match (b & 0x40 != 0, b & 0x20 != 0) {
+            (true, true) => 12,
+            (true, false) => 10,
+            _ => 8,
+        }),
+        // VPCodecConfigurationRecord 是 FullBox，位深在 version/flags、profile、level 之后
+        b"vpcC" => config.get(6).map(|b| (b >> 4) as u32),
+        _ => None,
+    }
+}
+
+/// 根据宽高返回清晰度分类，如 `2160p`、`1080p`，与界面中的分类规则一致。
+pub fn resolution_class(width: u32, height: u32) -> String {
+    match (width, height) {
+        (w, h) if w >= 3200 || h >= 2000 => "2160p".to_string(),
+        (w, h) if w >= 2200 || h >= 1300 => "1440p".to_string(),
+        (w, h) if w >= 1600 || h >= 900 => "1080p".to_string(),
+        (w, h) if w >= 1100 || h >= 650 => "720p".to_string(),
+        (_, h) if h > 0 => format!("{}p", h),
+        _ => String::new(),
+    }
+}
+
+/// 视频画质概况，用于按画质筛选和排序
+#[derive(Debug, Clone)]
+pub struct QualityProfile {
+    /// 清晰度分类，如 `2160p`
+    pub resolution: String,
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u32,
+    pub hdr_format: HdrFormat,
+    pub dolby_vision_profile: u8,
+}
+
+/// 将各容器的原始编码标识转换为通用名称，未知编码原样返回。
+pub fn codec_display_name(codec: &str) -> String {
+    let upper = codec.to_ascii_uppercase();
+    let name = match upper.as_str() {
+        "V_MPEGH/ISO/HEVC" | "HVC1" | "HEV1" | "DVH1" | "DVHE" | "HEVC" | "H265" | "X265" => "HEVC",
+        "V_MPEG4/ISO/AVC" | "AVC1" | "AVC3" | "DVA1" | "DVAV" | "H264" | "X264" | "AVC" => "H.264",
+        "V_AV1" | "AV01" => "AV1",
+        "V_VP9" | "VP09" => "VP9",
+        "V_VP8" | "VP08" => "VP8",
+        "V_MPEG4/ISO/SP" | "V_MPEG4/ISO/ASP" | "MP4V" | "XVID" | "DIVX" | "DX50" | "FMP4" => "MPEG-4",
+        "V_MPEG2" | "V_MPEG1" | "MPG2" | "MPG1" => "MPEG-2",
+        "V_MS/VFW/FOURCC" => "VFW",
+        "A_AAC" | "MP4A" | "AAC" => "AAC",
+        "A_AC3" | "AC-3" | "AC3" => "AC3",
+        "A_EAC3" | "EC-3" | "EAC3" => "E-AC3",
+        "A_DTS" | "DTS" => "DTS",
+        "A_TRUEHD" | "MLPA" => "TrueHD",
+        "A_FLAC" | "FLAC" | "FLA" => "FLAC",
+        "A_OPUS" | "OPUS" => "Opus",
+        "A_VORBIS" | "VORBIS" => "Vorbis",
+        "A_MPEG/L3" | ".MP3" | "MP3" => "MP3",
+        "A_MPEG/L2" | "MP2" => "MP2",
+        "S_TEXT/UTF8" | "S_TEXT/ASCII" => "SRT",
+        "S_TEXT/ASS" | "S_ASS" | "S_TEXT/SSA" | "S_SSA" => "ASS",
+        "S_HDMV/PGS" => "PGS",
+        "S_VOBSUB" => "VobSub",
+        "S_TEXT/WEBVTT" | "WVTT" => "WebVTT",
+        "TX3G" | "TEXT" => "TX3G",
+        _ if upper.starts_with("A_AAC") => "AAC",
+        _ if upper.starts_with("A_PCM") || upper == "PCM" || upper == "LPCM" || upper == "SOWT" || upper == "TWOS" => "PCM",
+        _ if upper.starts_with("A_DTS") => "DTS",
+        _ => return codec.to_string(),
+    };
+    name.to_string()
+}
+
+/// 章节标题的一种本地化写法
+#[derive(Debug, Clone)]
+pub struct ChapterTitle {
+    /// 语言代码，优先使用 BCP 47 标签
+    pub language: String,
+    pub title: String,
+}
+
+/// 单个章节
+#[derive(Debug, Clone)]
+pub struct ChapterInfo {
+    pub start_seconds: f64,
+    /// 章节结束时间，文件未提供时取下一章节的开始时间或视频时长
+    pub end_seconds: f64,
+    pub titles: Vec<ChapterTitle>,
+}
+
+/// 内嵌的封面图片
+#[derive(Debug, Clone)]
+pub struct CoverArt {
+    /// 如 `image/jpeg`、`image/png`
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+impl CoverArt {
+    /// 保存为文件时使用的扩展名。
+    pub fn extension(&self) -> &'static str {
+        match self.mime_type.as_str() {
+            "image/png" => "png",
+            "image/webp" => "webp",
+            "image/gif" => "gif",
+            "image/bmp" => "bmp",
+            _ => "jpg",
+        }
+    }
+}
+
+/// 根据文件头识别图片格式，无法识别时返回 None。
+fn image_mime_type(data: &[u8]) -> Option<&'static str> {
+    match data {
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        [b'B', b'M', ..] => Some("image/bmp"),
+        _ => None,
+    }
+}
+
+/// 按 Matroska 附件的封面命名约定排序：cover > cover_land > small_cover > 其他图片。
+/// 非图片附件（字体等）返回 None。
+fn cover_priority(file_name: &str, mime_type: &str) -> Option<u8> {
+    if !mime_type.starts_with("image/") {
+        return None;
+    }
+    let stem = file_name.rsplit_once('.').map_or(file_name, |(stem, _)| stem).to_ascii_lowercase();
+    Some(match stem.as_str() {
+        "cover" => 3,
+        "cover_land" => 2,
+        "small_cover" | "small_cover_land" => 1,
+        _ => 0,
+    })
+}
+
 /// 定义用于存储元数据信息的结构体
-#[derive(Debug)]
+#[derive(Debug, Default)]
 #[allow(dead_code)]
-pub struct MkvMetadata {
-    pub timecode_scale: u64,
-    pub duration: f64,
+pub struct VideoMetadata {
+    /// 容器格式，如 `matroska`、`webm`、`mp4`、`mov`、`avi`、`asf`、`flv`、`mpegts`、`m2ts`
+    pub container: String,
     pub video_duration_seconds: f64,
+    pub tracks: Vec<TrackInfo>,
+    pub chapters: Vec<ChapterInfo>,
 }
 
-/// 提取 MKV 文件的元数据信息。
-fn get_mkv_metadata(file_path: &str) -> Result<MkvMetadata, String> {
-    let file = File::open(file_path).map_err(|e| e.to_string())?;
-    let mut reader = BufReader::with_capacity(512 * 1024, file); // 增大缓冲区以提高性能。
+#[allow(dead_code)]
+impl VideoMetadata {
+    /// 第一条视频轨道
+    pub fn video_track(&self) -> Option<&TrackInfo> {
+        self.tracks.iter().find(|t| t.track_type == TrackType::Video)
+    }
+
+    /// 所有音频轨道
+    pub fn audio_tracks(&self) -> impl Iterator<Item = &TrackInfo> {
+        self.tracks.iter().filter(|t| t.track_type == TrackType::Audio)
+    }
+
+    /// 根据第一条视频轨道生成画质概况，没有视频轨道时返回 None。
+    pub fn quality_profile(&self) -> Option<QualityProfile> {
+        let video = self.video_track()?;
+        Some(QualityProfile {
+            resolution: resolution_class(video.width, video.height),
+            width: video.width,
+            height: video.height,
+            bit_depth: video.bit_depth,
+            hdr_format: video.hdr_format,
+            dolby_vision_profile: video.dolby_vision_profile,
+        })
+    }
+}
+
+/// 元数据提取函数
+type MetadataExtractor = fn(&str) -> Result<VideoMetadata, String>;
+/// 封面提取函数
+type CoverArtExtractor = fn(&str) -> Result<Option<CoverArt>, String>;
+
+/// 注册的容器格式：先通过文件头签名识别，再交给对应的解析器。
+struct ContainerFormat {
+    /// 该格式常见的扩展名（小写、不含点），扫描目录时据此过滤文件
+    extensions: &'static [&'static str],
+    /// 判断文件头是否属于该格式
+    matches: fn(&[u8]) -> bool,
+    extract_metadata: MetadataExtractor,
+    /// 不支持内嵌封面的格式为 None
+    extract_cover_art: Option<CoverArtExtractor>,
+}
+
+/// 容器注册表，按顺序匹配签名。WebM 与 Matroska 共用 EBML 签名，需要排在前面。
+static CONTAINER_FORMATS: &[ContainerFormat] = &[
+    ContainerFormat {
+        extensions: &["webm"],
+        matches: is_webm_signature,
+        extract_metadata: get_webm_metadata,
+        extract_cover_art: Some(get_mkv_cover_art),
+    },
+    ContainerFormat {
+        extensions: &["mkv", "mk3d"],
+        matches: is_ebml_signature,
+        extract_metadata: get_mkv_metadata,
+        extract_cover_art: Some(get_mkv_cover_art),
+    },
+    ContainerFormat {
+        extensions: &["avi"],
+        matches: avi::is_avi_signature,
+        extract_metadata: avi::get_avi_metadata,
+        extract_cover_art: None,
+    },
+    ContainerFormat {
+        extensions: &["mp4", "m4v", "mov", "3gp", "3g2"],
+        matches: mp4::is_mp4_signature,
+        extract_metadata: mp4::get_mp4_metadata,
+        extract_cover_art: Some(mp4::get_mp4_cover_art),
+    },
+    ContainerFormat {
+        extensions: &["wmv", "asf"],
+        matches: asf::is_asf_signature,
+        extract_metadata: asf::get_asf_metadata,
+        extract_cover_art: None,
+    },
+    ContainerFormat {
+        extensions: &["flv"],
+        matches: flv::is_flv_signature,
+        extract_metadata: flv::get_flv_metadata,
+        extract_cover_art: None,
+    },
+    ContainerFormat {
+        extensions: &["ts", "m2ts", "mts"],
+        matches: ts::is_ts_signature,
+        extract_metadata: ts::get_ts_metadata,
+        extract_cover_art: None,
+    },
+];
+
+/// 所有已注册容器格式的扩展名。
+pub fn supported_extensions() -> impl Iterator<Item = &'static str> {
+    CONTAINER_FORMATS.iter().flat_map(|format| format.extensions.iter().copied())
+}
+
+/// 通过文件头的签名识别容器类型，而不是依赖扩展名。
+fn detect_container(header: &[u8]) -> Option<&'static ContainerFormat> {
+    CONTAINER_FORMATS.iter().find(|format| (format.matches)(header))
+}
+
+/// 判断文件头是否以 EBML 头部开始（Matroska / WebM）。
+fn is_ebml_signature(header: &[u8]) -> bool {
+    header.len() >= 4 && bytes_to_u64(&header[..4]) == EBML_HEADER_ID as u64
+}
+
+/// 读取内存中 EBML 头部的 DocType，如 `matroska`、`webm`。
+fn ebml_doc_type(header: &[u8]) -> Option<String> {
+    let mut reader = header.get(4..)?;
+    let size = read_element_size(&mut reader).ok()??;
+    let body = reader.get(..usize::try_from(size).ok()?)?;
+    EbmlIter::new(body)
+        .find(|(element_id, _)| *element_id == DOC_TYPE_ID)
+        .map(|(_, doc_type)| ebml_string(doc_type))
+}
+
+fn is_webm_signature(header: &[u8]) -> bool {
+    is_ebml_signature(header) && ebml_doc_type(header).as_deref() == Some("webm")
+}
+
+/// 遍历内存中的 EBML 子元素，依次返回 (元素 ID, 内容)。
+struct EbmlIter<'a> {
+    data: &'a [u8],
+    position: usize,
+}
 
-    let mut header = [0u8; 4];
-    reader.read_exact(&mut header).map_err(|e| e.to_string())?;
-    if bytes_to_u64(&header) != EBML_HEADER_ID as u64 {
-        return Err("Invalid MKV file".into());
+impl<'a> EbmlIter<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        EbmlIter { data, position: 0 }
     }
+}
+
+impl<'a> Iterator for EbmlIter<'a> {
+    type Item = (u32, &'a [u8]);
 
-    let ebml_header_size = read_vint(&mut reader)?;
-    reader.seek(SeekFrom::Current(ebml_header_size as i64)).map_err(|e| e.to_string())?;
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut cursor = io::Cursor::new(self.data.get(self.position..)?);
+        let element_id = read_element_id(&mut cursor).ok()?;
+        let element_size = read_element_size(&mut cursor).ok()?;
+
+        let start = self.position + cursor.position() as usize;
+        // 大小未知的元素延伸到父元素末尾；大小超出父元素范围时停止遍历
+        let end = match element_size.map(usize::try_from) {
+            None => Some(self.data.len()),
+            Some(Ok(size)) => start.checked_add(size),
+            Some(Err(_)) => None,
+        };
+        let end = match end {
+            Some(end) if end <= self.data.len() => end,
+            _ => {
+                self.position = self.data.len();
+                return None;
+            }
+        };
 
-    let segment_id = read_element_id(&mut reader).map_err(|e| e.to_string())?;
-    if segment_id != SEGMENT_ID {
-        return Err("Invalid Segment element".into());
+        self.position = end;
+        Some((element_id, &self.data[start..end]))
     }
+}
 
-    let segment_size = read_vint(&mut reader)?;
-    let segment_end = reader.seek(SeekFrom::Current(0)).map_err(|e| e.to_string())? + segment_size;
+/// 读取 EBML 字符串，去掉末尾的空字节。
+fn ebml_string(buffer: &[u8]) -> String {
+    String::from_utf8_lossy(buffer).trim_end_matches('\0').to_string()
+}
 
-    let mut timecode_scale: Option<u64> = None;
-    let mut duration: Option<f64> = None;
+/// 读取 EBML 无符号整数，超过 8 字节视为无效。
+fn ebml_uint(buffer: &[u8]) -> Option<u64> {
+    if buffer.len() > 8 {
+        return None;
+    }
+    Some(bytes_to_u64(buffer))
+}
 
-    while reader.seek(SeekFrom::Current(0)).map_err(|e| e.to_string())? < segment_end {
-        let element_id = read_element_id(&mut reader)?;
-        let element_size = read_vint(&mut reader)?;
+/// 解析 Info 元素，返回 (TimecodeScale, Duration)。
+fn parse_mkv_info(info: &[u8]) -> (Option<u64>, Option<f64>) {
+    let mut timecode_scale = None;
+    let mut duration = None;
+    for (element_id, body) in EbmlIter::new(info) {
+        match element_id {
+            TIMECODE_SCALE_ID => timecode_scale = ebml_uint(body),
+            DURATION_ID => duration = Some(bytes_to_f64(body)),
+            _ => {}
+        }
+    }
+    (timecode_scale, duration)
+}
 
-        if element_id == INFO_ID {
-            let info_end = reader.seek(SeekFrom::Current(0)).map_err(|e| e.to_string())? + element_size;
-            while reader.seek(SeekFrom::Current(0)).map_err(|e| e.to_string())? < info_end {
-                let info_element_id = read_element_id(&mut reader)?;
-                let info_element_size = read_vint(&mut reader)?;
+/// 解析单个 TrackEntry 元素。
+fn parse_mkv_track_entry(entry: &[u8]) -> TrackInfo {
+    let mut track = TrackInfo::new(TrackType::Other, "");
+    // Matroska 规范中 Language 的默认值为 eng
+    track.language = "eng".to_string();
+    let mut codec_private: &[u8] = &[];
+    let mut colour = ColourInfo::default();
+    let mut dolby_vision_record = None;
 
-                match info_element_id {
-                    TIMECODE_SCALE_ID => {
-                        let mut buffer = [0u8; 8]; // 限制最大读取长度。
-                        reader.read_exact(&mut buffer[..info_element_size as usize]).map_err(|e| e.to_string())?;
-                        timecode_scale = Some(bytes_to_u64(&buffer[..info_element_size as usize]));
+    for (element_id, body) in EbmlIter::new(entry) {
+        match element_id {
+            TRACK_TYPE_ID => {
+                track.track_type = match ebml_uint(body) {
+                    Some(1) => TrackType::Video,
+                    Some(2) => TrackType::Audio,
+                    Some(17) => TrackType::Subtitle,
+                    _ => TrackType::Other,
+                }
+            }
+            CODEC_ID_ID => track.codec = ebml_string(body),
+            LANGUAGE_ID => track.language = ebml_string(body),
+            LANGUAGE_IETF_ID => track.language_ietf = ebml_string(body),
+            NAME_ID => track.name = ebml_string(body),
+            FLAG_DEFAULT_ID => track.default = ebml_uint(body).unwrap_or(1) != 0,
+            FLAG_FORCED_ID => track.forced = ebml_uint(body).unwrap_or(0) != 0,
+            VIDEO_ID => {
+                for (video_element_id, value) in EbmlIter::new(body) {
+                    match video_element_id {
+                        PIXEL_WIDTH_ID => track.width = ebml_uint(value).unwrap_or(0) as u32,
+                        PIXEL_HEIGHT_ID => track.height = ebml_uint(value).unwrap_or(0) as u32,
+                        COLOUR_ID => {
+                            for (colour_element_id, value) in EbmlIter::new(value) {
+                                match colour_element_id {
+                                    BITS_PER_CHANNEL_ID => track.bit_depth = ebml_uint(value).unwrap_or(0) as u32,
+                                    MATRIX_COEFFICIENTS_ID => colour.matrix_coefficients = ebml_uint(value).unwrap_or(2),
+                                    TRANSFER_CHARACTERISTICS_ID => colour.transfer_characteristics = ebml_uint(value).unwrap_or(2),
+                                    PRIMARIES_ID => colour.primaries = ebml_uint(value).unwrap_or(2),
+                                    MASTERING_METADATA_ID => colour.has_mastering_metadata = true,
+                                    _ => {}
+                                }
+                            }
+                        }
+                        _ => {}
                     }
-                    DURATION_ID => {
-                        let mut buffer = [0u8; 8]; // 限制最大读取长度。
-                        reader.read_exact(&mut buffer[..info_element_size as usize]).map_err(|e| e.to_string())?;
-                        duration = Some(bytes_to_f64(&buffer[..info_element_size as usize]));
+                }
+            }
+            CODEC_PRIVATE_ID => codec_private = body,
+            BLOCK_ADDITION_MAPPING_ID => {
+                // Dolby Vision 的配置记录保存在 BlockAddIDType 为 dvcC / dvvC / dvwC 的映射中
+                let mut is_dolby_vision = false;
+                let mut extra_data: &[u8] = &[];
+                for (mapping_element_id, value) in EbmlIter::new(body) {
+                    match mapping_element_id {
+                        BLOCK_ADD_ID_TYPE_ID => {
+                            let id_type = (ebml_uint(value).unwrap_or(0) as u32).to_be_bytes();
+                            is_dolby_vision = matches!(&id_type, b"dvcC" | b"dvvC" | b"dvwC");
+                        }
+                        BLOCK_ADD_ID_EXTRA_DATA_ID => extra_data = value,
+                        _ => {}
                     }
-                    _ => {
-                        // 打印未处理的元素信息
-                        println!("Unknown element ID: {:#X}, size: {}", info_element_id, info_element_size);
-                        reader.seek(SeekFrom::Current(info_element_size as i64)).map_err(|e| e.to_string())?;
+                }
+                if is_dolby_vision {
+                    dolby_vision_record = Some(extra_data);
+                }
+            }
+            AUDIO_ID => {
+                // SamplingFrequency 默认 8000Hz，Channels 默认 1
+                track.sampling_frequency = 8000.0;
+                track.channels = 1;
+                for (audio_element_id, value) in EbmlIter::new(body) {
+                    match audio_element_id {
+                        SAMPLING_FREQUENCY_ID => track.sampling_frequency = bytes_to_f64(value),
+                        CHANNELS_ID => track.channels = ebml_uint(value).unwrap_or(1) as u32,
+                        _ => {}
                     }
                 }
+            }
+            _ => {}
+        }
+    }
+
+    if track.track_type == TrackType::Video {
+        // Colour 中没有 BitsPerChannel 时从 CodecPrivate 中的解码配置读取
+        if track.bit_depth == 0 {
+            let config_type = match track.codec.as_str() {
+                "V_MPEGH/ISO/HEVC" => Some(b"hvcC"),
+                "V_MPEG4/ISO/AVC" => Some(b"avcC"),
+                "V_AV1" => Some(b"av1C"),
+                _ => None,
+            };
+            track.bit_depth = config_type.and_then(|t| config_bit_depth(t, codec_private)).unwrap_or(0);
+        }
+        track.hdr_format = colour.hdr_format();
+        if let Some(record) = dolby_vision_record {
+            track.set_dolby_vision(record);
+        }
+    }
+    track
+}
+
+/// 解析 Tracks 元素中的所有 TrackEntry。
+fn parse_mkv_tracks(tracks: &[u8]) -> Vec<TrackInfo> {
+    EbmlIter::new(tracks)
+        .filter(|(element_id, _)| *element_id == TRACK_ENTRY_ID)
+        .map(|(_, entry)| parse_mkv_track_entry(entry))
+        .collect()
+}
+
+/// 解析单个 ChapterAtom，隐藏或禁用的章节返回 None。
+fn parse_mkv_chapter_atom(atom: &[u8]) -> Option<ChapterInfo> {
+    let mut start = None;
+    let mut end = None;
+    let mut titles = Vec::new();
 
-                if timecode_scale.is_some() && duration.is_some() {
-                    break;
+    for (element_id, body) in EbmlIter::new(atom) {
+        match element_id {
+            CHAPTER_TIME_START_ID => start = ebml_uint(body),
+            CHAPTER_TIME_END_ID => end = ebml_uint(body),
+            CHAPTER_FLAG_HIDDEN_ID if ebml_uint(body).unwrap_or(0) != 0 => return None,
+            CHAPTER_FLAG_ENABLED_ID if ebml_uint(body).unwrap_or(1) == 0 => return None,
+            CHAPTER_DISPLAY_ID => {
+                let mut title = String::new();
+                // ChapLanguage 的默认值为 eng
+                let mut language = "eng".to_string();
+                let mut language_ietf = None;
+                for (display_element_id, value) in EbmlIter::new(body) {
+                    match display_element_id {
+                        CHAP_STRING_ID => title = ebml_string(value),
+                        CHAP_LANGUAGE_ID => language = ebml_string(value),
+                        CHAP_LANGUAGE_IETF_ID => language_ietf = Some(ebml_string(value)),
+                        _ => {}
+                    }
+                }
+                if !title.is_empty() {
+                    titles.push(ChapterTitle { language: language_ietf.unwrap_or(language), title });
                 }
             }
-        } else {
-            reader.seek(SeekFrom::Current(element_size as i64)).map_err(|e| e.to_string())?;
+            _ => {}
         }
+    }
 
-        if timecode_scale.is_some() && duration.is_some() {
-            break;
+    // 章节时间以纳秒为单位，不受 TimecodeScale 影响
+    Some(ChapterInfo {
+        start_seconds: start? as f64 / 1_000_000_000.0,
+        end_seconds: end.map(|end| end as f64 / 1_000_000_000.0).unwrap_or(0.0),
+        titles,
+    })
+}
+
+/// 解析 Chapters 元素，使用默认版本（EditionEntry），没有默认版本时使用第一个可见版本。
+fn parse_mkv_chapters(chapters: &[u8], video_duration_seconds: f64) -> Vec<ChapterInfo> {
+    let editions: Vec<(bool, &[u8])> = EbmlIter::new(chapters)
+        .filter(|(element_id, _)| *element_id == EDITION_ENTRY_ID)
+        .filter_map(|(_, edition)| {
+            let mut is_default = false;
+            for (element_id, body) in EbmlIter::new(edition) {
+                match element_id {
+                    EDITION_FLAG_HIDDEN_ID if ebml_uint(body).unwrap_or(0) != 0 => return None,
+                    EDITION_FLAG_DEFAULT_ID => is_default = ebml_uint(body).unwrap_or(0) != 0,
+                    _ => {}
+                }
+            }
+            Some((is_default, edition))
+        })
+        .collect();
+
+    let edition = match editions.iter().find(|(is_default, _)| *is_default).or(editions.first()) {
+        Some((_, edition)) => *edition,
+        None => return Vec::new(),
+    };
+
+    let mut result: Vec<ChapterInfo> = EbmlIter::new(edition)
+        .filter(|(element_id, _)| *element_id == CHAPTER_ATOM_ID)
+        .filter_map(|(_, atom)| parse_mkv_chapter_atom(atom))
+        .collect();
+    result.sort_by(|a, b| a.start_seconds.total_cmp(&b.start_seconds));
+
+    // 补全缺失的结束时间
+    for index in 0..result.len() {
+        if result[index].end_seconds <= result[index].start_seconds {
+            result[index].end_seconds = result.get(index + 1)
+                .map(|next| next.start_seconds)
+                .unwrap_or(video_duration_seconds);
+        }
+    }
+    result
+}
+
+/// 将主元素整体读入内存，大小超出上限时返回错误。
+fn read_master_element<R: Read>(reader: &mut R, element_size: u64) -> Result<Vec<u8>, String> {
+    if element_size > MAX_MASTER_ELEMENT_SIZE {
+        return Err(format!("Element too large: {} bytes", element_size));
+    }
+    let mut buffer = vec![0u8; element_size as usize];
+    reader.read_exact(&mut buffer).map_err(|e| e.to_string())?;
+    Ok(buffer)
+}
+
+/// 解析 SeekHead，返回 (元素 ID, 文件内绝对位置) 列表。
+fn parse_seek_head(seek_head: &[u8], segment_start: u64) -> Vec<(u32, u64)> {
+    EbmlIter::new(seek_head)
+        .filter(|(element_id, _)| *element_id == SEEK_ID)
+        .filter_map(|(_, seek)| {
+            let mut seek_id = None;
+            let mut seek_position = None;
+            for (element_id, body) in EbmlIter::new(seek) {
+                match element_id {
+                    SEEK_ID_ID if body.len() <= 4 => seek_id = Some(bytes_to_u64(body) as u32),
+                    SEEK_POSITION_ID => seek_position = ebml_uint(body),
+                    _ => {}
+                }
+            }
+            Some((seek_id?, segment_start.checked_add(seek_position?)?))
+        })
+        .collect()
+}
+
+/// 已打开的 Matroska 文件，记录 Segment 的范围。
+struct MkvFile {
+    reader: BufReader<File>,
+    segment_start: u64,
+    segment_end: u64,
+}
+
+impl MkvFile {
+    /// 打开文件并校验 EBML 头部，定位到 Segment 内容起始位置。
+    fn open(file_path: &str) -> Result<Self, String> {
+        let file = File::open(file_path).map_err(|e| e.to_string())?;
+        let file_len = file.metadata().map_err(|e| e.to_string())?.len();
+        let mut reader = BufReader::with_capacity(512 * 1024, file); // 增大缓冲区以提高性能。
+
+        if read_element_id(&mut reader)? != EBML_HEADER_ID {
+            return Err("Invalid MKV file".into());
+        }
+        let ebml_header_size = read_element_size(&mut reader)?
+            .filter(|size| *size <= MAX_EBML_HEADER_SIZE)
+            .ok_or("Invalid EBML header size")?;
+        reader.seek_relative(ebml_header_size as i64).map_err(|e| e.to_string())?;
+
+        let segment_id = read_element_id(&mut reader)?;
+        if segment_id != SEGMENT_ID {
+            return Err("Invalid Segment element".into());
+        }
+
+        let segment_size = read_element_size(&mut reader)?;
+        let segment_start = reader.stream_position().map_err(|e| e.to_string())?;
+        // 大小未知或超出文件长度时，以文件末尾作为 Segment 结束位置
+        let segment_end = segment_size
+            .and_then(|size| segment_start.checked_add(size))
+            .map_or(file_len, |end| end.min(file_len));
+
+        Ok(MkvFile { reader, segment_start, segment_end })
+    }
+
+    /// 读取 position 处的元素头，返回 (元素 ID, 大小, 内容起始位置)。
+    fn read_header_at(&mut self, position: u64) -> Result<(u32, Option<u64>, u64), String> {
+        self.reader.seek(SeekFrom::Start(position)).map_err(|e| e.to_string())?;
+        let element_id = read_element_id(&mut self.reader)?;
+        let element_size = read_element_size(&mut self.reader)?;
+        let data_start = self.reader.stream_position().map_err(|e| e.to_string())?;
+        Ok((element_id, element_size, data_start))
+    }
+
+    /// 定位 Segment 中指定的顶层元素，返回 元素 ID -> (内容起始位置, 大小)。
+    ///
+    /// 先顺序读取第一个 Cluster 之前的顶层元素，其余元素通过 SeekHead 定位，
+    /// 不会遍历 Cluster，因此在网络共享上读取大文件也只需少量跳转。
+    fn locate_elements(&mut self, wanted: &[u32]) -> Result<HashMap<u32, (u64, u64)>, String> {
+        let mut elements = HashMap::new();
+        let mut seek_entries: Vec<(u32, u64)> = Vec::new();
+
+        let mut position = self.segment_start;
+        while position < self.segment_end && elements.len() < wanted.len() {
+            let (element_id, element_size, data_start) = match self.read_header_at(position) {
+                Ok(header) => header,
+                Err(_) => break,
+            };
+            // 大小未知的元素无法跳过；Cluster 之后的元素交给 SeekHead
+            let element_size = match element_size {
+                Some(size) if element_id != CLUSTER_ID => size,
+                _ => break,
+            };
+            let data_end = match data_start.checked_add(element_size) {
+                Some(end) if end <= self.segment_end => end,
+                _ => break,
+            };
+
+            match element_id {
+                SEEK_HEAD_ID => {
+                    let seek_head = read_master_element(&mut self.reader, element_size)?;
+                    seek_entries.extend(parse_seek_head(&seek_head, self.segment_start));
+                }
+                VOID_ID | CRC32_ID => {}
+                _ if wanted.contains(&element_id) => {
+                    elements.entry(element_id).or_insert((data_start, element_size));
+                }
+                _ => {}
+            }
+            position = data_end;
         }
+
+        // 通过 SeekHead 查找尚未定位的元素，SeekHead 可能指向文件末尾的第二个 SeekHead
+        let mut visited = Vec::new();
+        let mut index = 0;
+        while index < seek_entries.len().min(MAX_SEEK_ENTRIES) && elements.len() < wanted.len() {
+            let (target_id, target_position) = seek_entries[index];
+            index += 1;
+
+            let needed = target_id == SEEK_HEAD_ID
+                || (wanted.contains(&target_id) && !elements.contains_key(&target_id));
+            if !needed || target_position >= self.segment_end || visited.contains(&target_position) {
+                continue;
+            }
+            visited.push(target_position);
+
+            let (element_id, element_size, data_start) = match self.read_header_at(target_position) {
+                Ok(header) => header,
+                Err(_) => continue,
+            };
+            let element_size = match element_size {
+                Some(size) if element_id == target_id && data_start.saturating_add(size) <= self.segment_end => size,
+                _ => continue,
+            };
+
+            if element_id == SEEK_HEAD_ID {
+                let seek_head = read_master_element(&mut self.reader, element_size)?;
+                seek_entries.extend(parse_seek_head(&seek_head, self.segment_start));
+            } else {
+                elements.insert(element_id, (data_start, element_size));
+            }
+        }
+
+        Ok(elements)
     }
 
-    let timecode_scale = timecode_scale.ok_or("Missing TimecodeScale in MKV metadata")?;
+    /// 读取 Segment 中指定的顶层元素内容。
+    fn read_elements(&mut self, wanted: &[u32]) -> Result<HashMap<u32, Vec<u8>>, String> {
+        let mut elements = HashMap::new();
+        for (element_id, (data_start, element_size)) in self.locate_elements(wanted)? {
+            self.reader.seek(SeekFrom::Start(data_start)).map_err(|e| e.to_string())?;
+            elements.insert(element_id, read_master_element(&mut self.reader, element_size)?);
+        }
+        Ok(elements)
+    }
+
+    /// 在 Attachments 中查找封面图片。
+    ///
+    /// 附件里常有体积很大的字体文件，这里只读取每个附件的文件名和 MIME 类型，
+    /// 选中封面后才读取其 FileData。
+    fn read_cover_art(&mut self) -> Result<Option<CoverArt>, String> {
+        let (attachments_start, attachments_size) = match self.locate_elements(&[ATTACHMENTS_ID])?.get(&ATTACHMENTS_ID) {
+            Some(location) => *location,
+            None => return Ok(None),
+        };
+        let attachments_end = attachments_start + attachments_size;
+
+        // (优先级, MIME 类型, FileData 起始位置, FileData 大小)
+        let mut best: Option<(u8, String, u64, u64)> = None;
+        let mut position = attachments_start;
+        while position < attachments_end {
+            let (element_id, element_size, data_start) = self.read_header_at(position)?;
+            let data_end = match element_size.and_then(|size| data_start.checked_add(size)) {
+                Some(end) if end <= attachments_end => end,
+                _ => break,
+            };
+
+            if element_id == ATTACHED_FILE_ID {
+                let mut file_name = String::new();
+                let mut mime_type = String::new();
+                let mut file_data = None;
+
+                let mut child = data_start;
+                while child < data_end {
+                    let (child_id, child_size, child_start) = self.read_header_at(child)?;
+                    let child_size = match child_size {
+                        Some(size) if child_start.saturating_add(size) <= data_end => size,
+                        _ => break,
+                    };
+                    match child_id {
+                        FILE_NAME_ID | FILE_MIME_TYPE_ID if child_size <= 1024 => {
+                            let body = read_master_element(&mut self.reader, child_size)?;
+                            if child_id == FILE_NAME_ID {
+                                file_name = ebml_string(&body);
+                            } else {
+                                mime_type = ebml_string(&body).to_ascii_lowercase();
+                            }
+                        }
+                        FILE_DATA_ID => file_data = Some((child_start, child_size)),
+                        _ => {}
+                    }
+                    child = child_start + child_size;
+                }
+
+                if let (Some(priority), Some((file_data_start, file_data_size))) =
+                    (cover_priority(&file_name, &mime_type), file_data)
+                {
+                    let better = !matches!(&best, Some((current, ..)) if *current >= priority);
+                    if better && file_data_size > 0 && file_data_size <= MAX_COVER_ART_SIZE {
+                        best = Some((priority, mime_type, file_data_start, file_data_size));
+                    }
+                }
+            }
+            position = data_end;
+        }
+
+        let (_, mime_type, data_start, data_size) = match best {
+            Some(cover) => cover,
+            None => return Ok(None),
+        };
+        self.reader.seek(SeekFrom::Start(data_start)).map_err(|e| e.to_string())?;
+        let mut data = vec![0u8; data_size as usize];
+        self.reader.read_exact(&mut data).map_err(|e| e.to_string())?;
+        Ok(Some(CoverArt { mime_type, data }))
+    }
+}
+
+/// 提取 MKV 文件的元数据信息。
+fn get_mkv_metadata(file_path: &str) -> Result<VideoMetadata, String> {
+    let mut mkv = MkvFile::open(file_path)?;
+    let elements = mkv.read_elements(&[INFO_ID, TRACKS_ID, CHAPTERS_ID])?;
+
+    let info = elements.get(&INFO_ID).ok_or("Missing Info in MKV metadata")?;
+    let (timecode_scale, duration) = parse_mkv_info(info);
+    let timecode_scale = timecode_scale.unwrap_or(DEFAULT_TIMECODE_SCALE);
     let duration = duration.ok_or("Missing Duration in MKV metadata")?;
 
     let video_duration_seconds = (duration * timecode_scale as f64) / 1_000_000_000.0;
 
-    Ok(MkvMetadata {
-        timecode_scale,
-        duration,
+    Ok(VideoMetadata {
+        container: "matroska".to_string(),
         video_duration_seconds,
+        tracks: elements.get(&TRACKS_ID).map(|tracks| parse_mkv_tracks(tracks)).unwrap_or_default(),
+        chapters: elements.get(&CHAPTERS_ID)
+            .map(|chapters| parse_mkv_chapters(chapters, video_duration_seconds))
+            .unwrap_or_default(),
+    })
+}
+
+/// 提取 WebM 文件的元数据信息，WebM 是 Matroska 的子集。
+fn get_webm_metadata(file_path: &str) -> Result<VideoMetadata, String> {
+    get_mkv_metadata(file_path).map(|metadata| VideoMetadata {
+        container: "webm".to_string(),
+        ..metadata
     })
 }
 
+/// 提取 MKV / WebM 附件中的封面图片。
+fn get_mkv_cover_art(file_path: &str) -> Result<Option<CoverArt>, String> {
+    MkvFile::open(file_path)?.read_cover_art()
+}
+
 /// 公共接口，用于调用元数据解析。
-pub fn mkv_metadata(file_path: &str) -> Result<MkvMetadata, String> {
+#[allow(dead_code)]
+pub fn mkv_metadata(file_path: &str) -> Result<VideoMetadata, String> {
     get_mkv_metadata(file_path)
 }
 
+/// 公共接口，根据文件签名选择对应的解析器。
+pub fn video_metadata(file_path: &str) -> Result<VideoMetadata, String> {
+    let format = sniff_container(file_path)?.ok_or("Unsupported container format")?;
+    (format.extract_metadata)(file_path)
+}
+
+/// 提取视频文件内嵌的封面图片（MKV 附件、MP4 的 covr），没有封面时返回 None。
+pub fn cover_art(file_path: &str) -> Result<Option<CoverArt>, String> {
+    match sniff_container(file_path)?.and_then(|format| format.extract_cover_art) {
+        Some(extract_cover_art) => extract_cover_art(file_path),
+        None => Ok(None),
+    }
+}
+
+/// 读取文件头判断容器格式。TS 需要连续几个包的同步字节，因此多读一些。
+fn sniff_container(file_path: &str) -> Result<Option<&'static ContainerFormat>, String> {
+    let file = File::open(file_path).map_err(|e| e.to_string())?;
+    let mut header = Vec::with_capacity(1024);
+    file.take(1024).read_to_end(&mut header).map_err(|e| e.to_string())?;
+    Ok(detect_container(&header))
+}
+
 
 #[cfg(test)]
 mod tests {
-    use std::io;
-
     use super::*;
 
     #[test]
     fn test_read_vint() {
         let data = vec![0x81];
         let mut cursor = io::Cursor::new(data);
-        let result = read_vint(&mut cursor);
-        assert_eq!(result.unwrap(), 1);
+        let result = read_vint_with_length(&mut cursor);
+        assert_eq!(result.unwrap(), (1, 1));
 
         let data = vec![0x40, 0x01];
         let mut cursor = io::Cursor::new(data);
-        let result = read_vint(&mut cursor);
-        assert_eq!(result.unwrap(), 1);
+        let result = read_vint_with_length(&mut cursor);
+        assert_eq!(result.unwrap(), (1, 2));
 
         let data = vec![0x20, 0x00, 0x01];
         let mut cursor = io::Cursor::new(data);
-        let result = read_vint(&mut cursor);
-        assert_eq!(result.unwrap(), 1);
+        let result = read_vint_with_length(&mut cursor);
+        assert_eq!(result.unwrap(), (1, 3));
+    }
+
+    #[test]
+    fn test_read_unknown_element_size() {
+        let mut cursor = io::Cursor::new(vec![0xFF]);
+        assert_eq!(read_element_size(&mut cursor).unwrap(), None);
+
+        let mut cursor = io::Cursor::new(vec![0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(read_element_size(&mut cursor).unwrap(), None);
+
+        let mut cursor = io::Cursor::new(vec![0x40, 0x7F]);
+        assert_eq!(read_element_size(&mut cursor).unwrap(), Some(0x7F));
     }
 
     #[test]
     fn test_read_element_id() {
-        let data = vec![0x1A];
+        // 0x1A 的首位标记在第 4 位，是 4 字节 ID 的开头
+        let data = vec![0x1A, 0x45, 0xDF, 0xA3];
         let mut cursor = io::Cursor::new(data);
         let result = read_element_id(&mut cursor);
-        assert_eq!(result.unwrap(), 0x1A);
+        assert_eq!(result.unwrap(), EBML_HEADER_ID);
+
+        let mut cursor = io::Cursor::new(vec![0x1A]);
+        assert!(read_element_id(&mut cursor).is_err());
+
+        let mut cursor = io::Cursor::new(vec![0x08, 0x00, 0x00, 0x00, 0x00]);
+        assert!(read_element_id(&mut cursor).is_err());
+
+        let data = vec![0x81];
+        let mut cursor = io::Cursor::new(data);
+        let result = read_element_id(&mut cursor);
+        assert_eq!(result.unwrap(), 0x81);
 
         let data = vec![0x40, 0x00];
         let mut cursor = io::Cursor::new(data);
@@ -209,35 +1170,228 @@ mod tests {
         assert_eq!(result.unwrap(), 0x200000);
     }
 
+    /// 构造 EBML 元素：ID + 1 字节大小 + 内容
+    fn element(id: u32, body: &[u8]) -> Vec<u8> {
+        let mut data: Vec<u8> = id.to_be_bytes().into_iter().skip_while(|&b| b == 0).collect();
+        data.push(0x80 | body.len() as u8);
+        data.extend_from_slice(body);
+        data
+    }
+
+    #[test]
+    fn test_parse_mkv_tracks() {
+        let mut video = element(TRACK_TYPE_ID, &[1]);
+        video.extend(element(CODEC_ID_ID, b"V_MPEGH/ISO/HEVC"));
+        video.extend(element(VIDEO_ID, &[element(PIXEL_WIDTH_ID, &[0x0F, 0x00]), element(PIXEL_HEIGHT_ID, &[0x08, 0x70])].concat()));
+
+        let mut audio = element(TRACK_TYPE_ID, &[2]);
+        audio.extend(element(CODEC_ID_ID, b"A_EAC3"));
+        audio.extend(element(LANGUAGE_ID, b"chi\0"));
+        audio.extend(element(FLAG_DEFAULT_ID, &[0]));
+        audio.extend(element(AUDIO_ID, &[element(CHANNELS_ID, &[6]), element(SAMPLING_FREQUENCY_ID, &48000f32.to_be_bytes())].concat()));
+
+        let mut subtitle = element(TRACK_TYPE_ID, &[17]);
+        subtitle.extend(element(CODEC_ID_ID, b"S_HDMV/PGS"));
+        subtitle.extend(element(NAME_ID, b"Forced"));
+        subtitle.extend(element(FLAG_FORCED_ID, &[1]));
+
+        let tracks = [element(TRACK_ENTRY_ID, &video), element(TRACK_ENTRY_ID, &audio), element(TRACK_ENTRY_ID, &subtitle)].concat();
+        let tracks = parse_mkv_tracks(&tracks);
+        assert_eq!(tracks.len(), 3);
+
+        assert_eq!(tracks[0].track_type, TrackType::Video);
+        assert_eq!(tracks[0].codec_name(), "HEVC");
+        assert_eq!((tracks[0].width, tracks[0].height), (3840, 2160));
+
+        assert_eq!(tracks[1].track_type, TrackType::Audio);
+        assert_eq!(tracks[1].language, "chi");
+        assert_eq!(tracks[1].channels, 6);
+        assert_eq!(tracks[1].sampling_frequency, 48000.0);
+        assert!(!tracks[1].default);
+
+        assert_eq!(tracks[2].track_type, TrackType::Subtitle);
+        assert_eq!(tracks[2].language, "eng");
+        assert_eq!(tracks[2].name, "Forced");
+        assert!(tracks[2].forced);
+    }
+
+    #[test]
+    fn test_parse_mkv_hdr() {
+        let colour = [element(BITS_PER_CHANNEL_ID, &[10]), element(TRANSFER_CHARACTERISTICS_ID, &[18]), element(PRIMARIES_ID, &[9])].concat();
+        let mut hlg = element(TRACK_TYPE_ID, &[1]);
+        hlg.extend(element(CODEC_ID_ID, b"V_MPEGH/ISO/HEVC"));
+        hlg.extend(element(VIDEO_ID, &[element(PIXEL_WIDTH_ID, &[0x0F, 0x00]), element(COLOUR_ID, &colour)].concat()));
+        let track = parse_mkv_track_entry(&hlg);
+        assert_eq!((track.bit_depth, track.hdr_format), (10, HdrFormat::Hlg));
+
+        // 只有母版元数据，位深来自 CodecPrivate，Dolby Vision 来自 BlockAdditionMapping
+        let mut hvcc = vec![0u8; 23];
+        hvcc[17] = 0xFA;
+        hvcc[21] = 0x0F;
+        let mapping = [element(BLOCK_ADD_ID_TYPE_ID, b"dvvC"), element(BLOCK_ADD_ID_EXTRA_DATA_ID, &[1, 0, 5 << 1, 0x20, 0])].concat();
+        let mut dolby_vision = element(TRACK_TYPE_ID, &[1]);
+        dolby_vision.extend(element(CODEC_ID_ID, b"V_MPEGH/ISO/HEVC"));
+        dolby_vision.extend(element(CODEC_PRIVATE_ID, &hvcc));
+        dolby_vision.extend(element(VIDEO_ID, &element(COLOUR_ID, &[element(PRIMARIES_ID, &[9]), element(MASTERING_METADATA_ID, &[])].concat())));
+        let track = parse_mkv_track_entry(&dolby_vision);
+        assert_eq!((track.bit_depth, track.hdr_format), (10, HdrFormat::Hdr10));
+
+        dolby_vision.extend(element(BLOCK_ADDITION_MAPPING_ID, &mapping));
+        let metadata = VideoMetadata { tracks: vec![parse_mkv_track_entry(&dolby_vision)], ..Default::default() };
+        let quality = metadata.quality_profile().unwrap();
+        assert_eq!((quality.hdr_format, quality.dolby_vision_profile), (HdrFormat::DolbyVision, 5));
+        assert_eq!(resolution_class(3840, 1600), "2160p");
+        assert_eq!(resolution_class(1920, 800), "1080p");
+    }
+
+    /// Segment 大小未知、Info 位于 Cluster 之后，只能通过 SeekHead 找到
+    #[test]
+    fn test_seek_head_after_cluster() {
+        let info = element(INFO_ID, &[element(TIMECODE_SCALE_ID, &[0x0F, 0x42, 0x40]), element(DURATION_ID, &90_000f64.to_be_bytes())].concat());
+        let cluster = element(CLUSTER_ID, &[0u8; 64]);
+        // SeekHead 本身 + CRC-32 + Void + Cluster 之后才是 Info
+        let seek_head_len = 4 + 1 + (2 + 1 + (2 + 1 + 4) + (2 + 1 + 2));
+        let crc = element(CRC32_ID, &[0; 4]);
+        let void = element(VOID_ID, &[0; 10]);
+        let info_position = (seek_head_len + crc.len() + void.len() + cluster.len()) as u16;
+        let seek = [element(SEEK_ID_ID, &INFO_ID.to_be_bytes()), element(SEEK_POSITION_ID, &info_position.to_be_bytes())].concat();
+        let seek_head = element(SEEK_HEAD_ID, &element(SEEK_ID, &seek));
+        assert_eq!(seek_head.len(), seek_head_len);
+
+        let mut data = element(EBML_HEADER_ID, &element(0x4282, b"matroska"));
+        data.extend_from_slice(&[0x18, 0x53, 0x80, 0x67, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        data.extend([seek_head, crc, void, cluster, info].concat());
+
+        let file = fixtures::TempFile::new("seek_head.mkv");
+        let result = mkv_metadata(file.write(&data));
+
+        let metadata = result.unwrap();
+        assert_eq!(metadata.video_duration_seconds, 90.0);
+        assert!(metadata.tracks.is_empty());
+    }
+
+    #[test]
+    fn test_mkv_cover_art() {
+        let attached_file = |name: &[u8], mime_type: &[u8], data: &[u8]| {
+            element(ATTACHED_FILE_ID, &[element(FILE_NAME_ID, name), element(FILE_MIME_TYPE_ID, mime_type), element(FILE_DATA_ID, data)].concat())
+        };
+        let attachments = [
+            attached_file(b"font.ttf", b"font/ttf", &[0; 16]),
+            attached_file(b"small_cover.png", b"image/png", &[0x89, b'P', b'N', b'G']),
+            attached_file(b"cover.jpg", b"image/jpeg", &[0xFF, 0xD8, 0xFF, 0xE0]),
+        ].concat();
+
+        let mut data = element(EBML_HEADER_ID, &element(0x4282, b"matroska"));
+        data.extend_from_slice(&[0x18, 0x53, 0x80, 0x67, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        data.extend(element(ATTACHMENTS_ID, &attachments));
+
+        let file = fixtures::TempFile::new("cover_art.mkv");
+        let result = cover_art(file.write(&data));
+
+        let cover = result.unwrap().unwrap();
+        assert_eq!(cover.mime_type, "image/jpeg");
+        assert_eq!(cover.data, [0xFF, 0xD8, 0xFF, 0xE0]);
+        assert_eq!(cover_priority("poster.png", "font/ttf"), None);
+    }
+
+    #[test]
+    fn test_detect_container() {
+        let extensions = |header: &[u8]| detect_container(header).map(|format| format.extensions[0]);
+        assert_eq!(extensions(&element(EBML_HEADER_ID, &element(DOC_TYPE_ID, b"webm"))), Some("webm"));
+        assert_eq!(extensions(&element(EBML_HEADER_ID, &element(DOC_TYPE_ID, b"matroska"))), Some("mkv"));
+        assert_eq!(extensions(b"\0\0\0\x20ftypisom"), Some("mp4"));
+        assert_eq!(extensions(b"RIFF\0\0\0\0AVI LIST"), Some("avi"));
+        assert_eq!(extensions(b"FLV\x01\x05\0\0\0\x09"), Some("flv"));
+        assert_eq!(extensions(&[0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C]), Some("wmv"));
+        assert_eq!(extensions(b"not a video"), None);
+        assert!(supported_extensions().any(|ext| ext == "m4v"));
+    }
+
+    #[test]
+    fn test_corrupt_element_size() {
+        // Tracks 声称有 2^56 - 2 字节，不能触发巨量分配或越界切片
+        let mut data = element(EBML_HEADER_ID, &[]);
+        data.extend_from_slice(&[0x18, 0x53, 0x80, 0x67, 0x88]);
+        data.extend_from_slice(&[0x16, 0x54, 0xAE, 0x6B, 0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE]);
+        data.extend_from_slice(&[0xAE; 16]);
+
+        let file = fixtures::TempFile::new("corrupt_size.mkv");
+        let result = mkv_metadata(file.write(&data));
+        assert!(result.is_err());
+
+        assert_eq!(EbmlIter::new(&[0xAE, 0x40, 0xFF, 0x00]).count(), 0);
+    }
+
+    #[test]
+    fn test_parse_mkv_chapters() {
+        let display = |title: &[u8], language: &[u8]| element(CHAPTER_DISPLAY_ID, &[element(CHAP_STRING_ID, title), element(CHAP_LANGUAGE_ID, language)].concat());
+        let first = [element(CHAPTER_TIME_START_ID, &[0]), display(b"Opening", b"eng"), display("片头".as_bytes(), b"chi")].concat();
+        let second = [element(CHAPTER_TIME_START_ID, &60_000_000_000u64.to_be_bytes()), display(b"Part 1", b"eng")].concat();
+        let hidden = [element(CHAPTER_TIME_START_ID, &[1]), element(CHAPTER_FLAG_HIDDEN_ID, &[1])].concat();
+
+        let hidden_edition = element(EDITION_ENTRY_ID, &[element(EDITION_FLAG_HIDDEN_ID, &[1]), element(CHAPTER_ATOM_ID, &first)].concat());
+        let edition = element(EDITION_ENTRY_ID, &[element(CHAPTER_ATOM_ID, &second), element(CHAPTER_ATOM_ID, &hidden), element(CHAPTER_ATOM_ID, &first)].concat());
+
+        let chapters = parse_mkv_chapters(&[hidden_edition, edition].concat(), 300.0);
+        assert_eq!(chapters.len(), 2);
+        assert_eq!((chapters[0].start_seconds, chapters[0].end_seconds), (0.0, 60.0));
+        assert_eq!((chapters[1].start_seconds, chapters[1].end_seconds), (60.0, 300.0));
+        assert_eq!(chapters[0].titles.len(), 2);
+        assert_eq!(chapters[0].titles[1].language, "chi");
+        assert_eq!(chapters[0].titles[1].title, "片头");
+    }
+
     #[test]
     fn test_get_video_metadata() {
-        // 创建一个临时文件，写入测试数据
-        let temp_file_path = "C:\\Users\\yzok0\\Videos\\Transformers.One.2024.HDR.2160p.WEB.h265-ETHEL[TGx]\\Transformers.One.2024.HDR.2160p.WEB.h265-ETHEL.mkv";
-        // let mut temp_file = File::create(temp_file_path).unwrap();
-        // temp_file.write_all(b"\x1A\x45\xDF\xA3").unwrap(); // 文件头
-        // temp_file.write_all(&[0x81, 0x00]).unwrap(); // EBML头部
-        // temp_file.write_all(&[0x18, 0x53, 0x80, 0x67]).unwrap(); // Segment元素
-        // temp_file.write_all(&[0x81, 0x00]).unwrap(); // Segment大小
-        // temp_file.write_all(&[0x15, 0x49, 0xA9, 0x66]).unwrap(); // Info元素
-        // temp_file.write_all(&[0x81, 0x00]).unwrap(); // Info大小
-        // temp_file.write_all(&[0x2A, 0xD7, 0xB1]).unwrap(); // TimecodeScale元素
-        // temp_file.write_all(&[0x81, 0x01]).unwrap(); // TimecodeScale大小和值
-        // temp_file.write_all(&[0x44, 0x89]).unwrap(); // Duration元素
-        // temp_file.write_all(&[0x84, 0x00, 0x00, 0x00, 0x01]).unwrap(); // Duration大小和值
-
-        // 测试 get_video_metadata 函数
-        let result = mkv_metadata(temp_file_path);
-        println!("{:?}", result);
-        assert!(result.is_ok());
-
-        let episode_pattern = regex::Regex::new(r"S\d{2}E\d{2}").ok(); // 匹配剧集编号 SxxExx
-        // let language_keywords = ["zh", "chs", "cn", "cht", "chinese", "chr", "简体", "简中", "繁中"];
-        // let language_pattern = regex::Regex::new(&format!(r"({})", language_keywords.join("|"))).ok(); // 匹配语言关键字    
-
-        if let Some(ep_pattern) = &episode_pattern {
-            println!("Episode: {}", ep_pattern.is_match(&temp_file_path));
-        }
-        // 删除临时文件
-        // std::fs::remove_file(temp_file_path).unwrap();
-    }
-}
\ No newline at end of file
+        let fixture = fixtures::Fixture::default();
+        let file = fixtures::TempFile::new("metadata_fixture");
+        for (data, container) in [(fixture.mkv(), "matroska"), (fixture.mp4(), "mp4"), (fixture.avi(), "avi")] {
+            let metadata = video_metadata(file.write(&data)).unwrap();
+            assert_eq!(metadata.container, container);
+            assert_eq!(metadata.video_duration_seconds, 90.0);
+
+            let video = metadata.video_track().unwrap();
+            assert_eq!((video.codec_name().as_str(), video.width, video.height), ("H.264", 1920, 1080));
+            let audio = metadata.audio_tracks().next().unwrap();
+            assert_eq!((audio.codec_name().as_str(), audio.channels, audio.sampling_frequency), ("AAC", 2, 48000.0));
+            if container != "avi" {
+                assert_eq!(audio.language, "eng");
+            }
+        }
+    }
+
+    /// 截断和改写字节后的样本交给所有解析器：不能 panic，截断的文件要么报错，要么时长不受影响
+    #[test]
+    fn test_corrupted_fixtures() {
+        let fixture = fixtures::Fixture::default();
+        let file = fixtures::TempFile::new("corrupted_fixture");
+        for (name, data) in [("mkv", fixture.mkv()), ("mp4", fixture.mp4()), ("avi", fixture.avi())] {
+            for corruption in fixtures::corruptions(&data) {
+                let path = file.write(&corruption.apply(&data));
+                for format in CONTAINER_FORMATS {
+                    let result = std::panic::catch_unwind(|| {
+                        let metadata = (format.extract_metadata)(path);
+                        let cover = format.extract_cover_art.map(|extract| extract(path));
+                        (metadata, cover)
+                    });
+                    let (metadata, _) = result.unwrap_or_else(|_| {
+                        panic!("{} parser panicked on {} {}", format.extensions[0], name, corruption)
+                    });
+
+                    if let (fixtures::Corruption::Truncated(_), Ok(metadata)) = (&corruption, metadata) {
+                        assert!(
+                            metadata.video_duration_seconds == 90.0 || format.extensions[0] != name,
+                            "{} parser returned {}s for {} {}",
+                            format.extensions[0], metadata.video_duration_seconds, name, corruption
+                        );
+                    }
+                }
+            }
+
+            // 空文件和只有文件头的文件
+            for length in [0, 4] {
+                assert!(video_metadata(file.write(&data[..length])).is_err(), "{} truncated to {} bytes", name, length);
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+use super::{TrackInfo, TrackType, VideoMetadata};
+
+/// Header Object 允许读取的最大字节数，正常文件只有几 KB 到几百 KB。
+const MAX_HEADER_SIZE: u64 = 16 * 1024 * 1024;
+/// Header Object 固定部分：GUID(16) + 大小(8) + 子对象数(4) + 保留(2)。
+const HEADER_OBJECT_LEN: usize = 30;
+/// 普通对象头：GUID(16) + 大小(8)。
+const OBJECT_HEADER_LEN: usize = 24;
+
+/// GUID 按 ASF 文件中的字节顺序（前三段小端）书写。
+const HEADER_OBJECT_GUID: [u8; 16] = [0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C];
+const FILE_PROPERTIES_GUID: [u8; 16] = [0xA1, 0xDC, 0xAB, 0x8C, 0x47, 0xA9, 0xCF, 0x11, 0x8E, 0xE4, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65];
+const STREAM_PROPERTIES_GUID: [u8; 16] = [0x91, 0x07, 0xDC, 0xB7, 0xB7, 0xA9, 0xCF, 0x11, 0x8E, 0xE6, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65];
+const AUDIO_MEDIA_GUID: [u8; 16] = [0x40, 0x9E, 0x69, 0xF8, 0x4D, 0x5B, 0xCF, 0x11, 0xA8, 0xFD, 0x00, 0x80, 0x5F, 0x5C, 0x44, 0x2B];
+const VIDEO_MEDIA_GUID: [u8; 16] = [0xC0, 0xEF, 0x19, 0xBC, 0x4D, 0x5B, 0xCF, 0x11, 0xA8, 0xFD, 0x00, 0x80, 0x5F, 0x5C, 0x44, 0x2B];
+
+/// 判断文件头是否为 ASF（WMV / WMA）Header Object。
+pub(super) fn is_asf_signature(header: &[u8]) -> bool {
+    header.get(..16) == Some(&HEADER_OBJECT_GUID[..])
+}
+
+/// 读取小端 u16，越界时返回 0。
+fn le_u16(data: &[u8], offset: usize) -> u16 {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .unwrap_or(0)
+}
+
+/// 读取小端 u32，越界时返回 0。
+fn le_u32(data: &[u8], offset: usize) -> u32 {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .unwrap_or(0)
+}
+
+/// 读取小端 u64，越界时返回 0。
+fn le_u64(data: &[u8], offset: usize) -> u64 {
+    data.get(offset..offset + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .unwrap_or(0)
+}
+
+/// 遍历内存中连续排列的 ASF 对象，依次返回 (GUID, 内容)。
+fn objects(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let size = le_u64(rest, 16);
+        // 大小非法时停止遍历，而不是越界切片
+        if rest.len() < OBJECT_HEADER_LEN || size < OBJECT_HEADER_LEN as u64 || size > rest.len() as u64 {
+            return None;
+        }
+        let (object, next) = rest.split_at(size as usize);
+        rest = next;
+        Some((&object[..16], &object[OBJECT_HEADER_LEN..]))
+    })
+}
+
+/// 解析 File Properties Object，返回播放时长（秒）。
+fn parse_file_properties(data: &[u8]) -> f64 {
+    // 广播流的时长字段无效
+    if le_u32(data, 64) & 0x01 != 0 {
+        return 0.0;
+    }
+    // Play Duration 以 100ns 为单位并包含 Preroll（毫秒）
+    let play_duration = le_u64(data, 40) as f64 / 10_000_000.0;
+    let preroll = le_u64(data, 56) as f64 / 1000.0;
+    (play_duration - preroll).max(0.0)
+}
+
+/// 解析 Stream Properties Object。
+fn parse_stream_properties(data: &[u8]) -> TrackInfo {
+    let type_specific_len = le_u32(data, 40) as usize;
+    let type_specific = data.get(54..54 + type_specific_len).unwrap_or(&[]);
+
+    match data.get(..16) {
+        Some(guid) if guid == AUDIO_MEDIA_GUID => {
+            // WAVEFORMATEX
+            let mut track = TrackInfo::new(TrackType::Audio, &super::avi::audio_codec_name(le_u16(type_specific, 0)));
+            track.channels = le_u16(type_specific, 2) as u32;
+            track.sampling_frequency = le_u32(type_specific, 4) as f64;
+            track
+        }
+        Some(guid) if guid == VIDEO_MEDIA_GUID => {
+            // 宽(4) + 高(4) + 标志(1) + 格式数据长度(2)，随后是 BITMAPINFOHEADER
+            let codec = type_specific.get(27..31)
+                .map(|fourcc| String::from_utf8_lossy(fourcc).trim_end_matches(['\0', ' ']).to_string())
+                .unwrap_or_default();
+            let mut track = TrackInfo::new(TrackType::Video, &codec);
+            track.width = le_u32(type_specific, 0);
+            track.height = le_u32(type_specific, 4);
+            track
+        }
+        _ => TrackInfo::new(TrackType::Other, ""),
+    }
+}
+
+/// 解析 Header Object 的子对象。
+fn parse_header(header: &[u8]) -> Result<VideoMetadata, String> {
+    let mut video_duration_seconds = None;
+    let mut tracks = Vec::new();
+
+    for (guid, data) in objects(header) {
+        if guid == FILE_PROPERTIES_GUID {
+            video_duration_seconds = Some(parse_file_properties(data));
+        } else if guid == STREAM_PROPERTIES_GUID {
+            tracks.push(parse_stream_properties(data));
+        }
+    }
+
+    Ok(VideoMetadata {
+        container: "asf".to_string(),
+        video_duration_seconds: video_duration_seconds.ok_or("Missing File Properties in ASF file")?,
+        tracks,
+        ..Default::default()
+    })
+}
+
+/// 提取 ASF（WMV）文件的元数据信息，只读取文件开头的 Header Object。
+pub(super) fn get_asf_metadata(file_path: &str) -> Result<VideoMetadata, String> {
+    let file = File::open(file_path).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(file);
+
+    let mut header_object = [0u8; HEADER_OBJECT_LEN];
+    reader.read_exact(&mut header_object).map_err(|e| e.to_string())?;
+    if !is_asf_signature(&header_object) {
+        return Err("Invalid ASF file".into());
+    }
+
+    let size = le_u64(&header_object, 16);
+    if size < HEADER_OBJECT_LEN as u64 || size > MAX_HEADER_SIZE {
+        return Err(format!("Invalid ASF header size: {}", size));
+    }
+    let mut header = vec![0u8; size as usize - HEADER_OBJECT_LEN];
+    reader.read_exact(&mut header).map_err(|e| e.to_string())?;
+    parse_header(&header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(guid: &[u8; 16], body: &[u8]) -> Vec<u8> {
+        let mut data = guid.to_vec();
+        data.extend_from_slice(&((body.len() + OBJECT_HEADER_LEN) as u64).to_le_bytes());
+        data.extend_from_slice(body);
+        data
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let mut file_properties = vec![0u8; 80];
+        file_properties[40..48].copy_from_slice(&(95 * 10_000_000u64).to_le_bytes());
+        file_properties[56..64].copy_from_slice(&5_000u64.to_le_bytes());
+
+        let mut video = vec![0u8; 54 + 51];
+        video[..16].copy_from_slice(&VIDEO_MEDIA_GUID);
+        video[40..44].copy_from_slice(&51u32.to_le_bytes());
+        video[54..58].copy_from_slice(&1280u32.to_le_bytes());
+        video[58..62].copy_from_slice(&720u32.to_le_bytes());
+        video[54 + 27..54 + 31].copy_from_slice(b"WMV3");
+
+        let mut audio = vec![0u8; 54 + 18];
+        audio[..16].copy_from_slice(&AUDIO_MEDIA_GUID);
+        audio[40..44].copy_from_slice(&18u32.to_le_bytes());
+        audio[54..56].copy_from_slice(&0x0161u16.to_le_bytes());
+        audio[56..58].copy_from_slice(&2u16.to_le_bytes());
+        audio[58..62].copy_from_slice(&44_100u32.to_le_bytes());
+
+        let header = [
+            object(&FILE_PROPERTIES_GUID, &file_properties),
+            object(&STREAM_PROPERTIES_GUID, &video),
+            object(&STREAM_PROPERTIES_GUID, &audio),
+        ].concat();
+
+        let metadata = parse_header(&header).unwrap();
+        assert_eq!(metadata.container, "asf");
+        assert_eq!(metadata.video_duration_seconds, 90.0);
+
+        let video = metadata.video_track().unwrap();
+        assert_eq!((video.codec.as_str(), video.width, video.height), ("WMV3", 1280, 720));
+        let audio = metadata.audio_tracks().next().unwrap();
+        assert_eq!((audio.codec.as_str(), audio.channels), ("wma", 2));
+    }
+
+    #[test]
+    fn test_truncated_header_does_not_panic() {
+        let header = object(&FILE_PROPERTIES_GUID, &[0u8; 80]);
+        assert!(parse_header(&header[..50]).is_err());
+        assert_eq!(objects(&[0xFF; 40]).count(), 0);
+    }
+}
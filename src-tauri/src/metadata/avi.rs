@@ -0,0 +1,285 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+use super::{TrackInfo, TrackType, VideoMetadata};
+
+/// hdrl 列表允许读取的最大字节数，正常文件只有几 KB。
+const MAX_HDRL_SIZE: u32 = 4 * 1024 * 1024;
+
+/// 判断文件头是否为 `RIFF....AVI `。
+pub(super) fn is_avi_signature(header: &[u8]) -> bool {
+    header.len() >= 12 && &header[..4] == b"RIFF" && &header[8..12] == b"AVI "
+}
+
+/// 读取小端 u16，越界时返回 0。
+fn le_u16(data: &[u8], offset: usize) -> u16 {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .unwrap_or(0)
+}
+
+/// 读取小端 u32，越界时返回 0。
+fn le_u32(data: &[u8], offset: usize) -> u32 {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .unwrap_or(0)
+}
+
+/// 读取 FourCC，去掉末尾空白和空字节。
+fn fourcc(data: &[u8], offset: usize) -> String {
+    data.get(offset..offset + 4)
+        .map(|b| String::from_utf8_lossy(b).trim_end_matches(['\0', ' ']).to_string())
+        .unwrap_or_default()
+}
+
+/// 遍历内存中的 RIFF 块，依次返回 (块 ID, 内容)。
+/// LIST 块的内容包含 4 字节的列表类型。
+struct ChunkIter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for ChunkIter<'a> {
+    type Item = ([u8; 4], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.len() < 8 {
+            return None;
+        }
+
+        let mut chunk_id = [0u8; 4];
+        chunk_id.copy_from_slice(&self.data[..4]);
+        let size = le_u32(self.data, 4) as usize;
+        if size > self.data.len() - 8 {
+            self.data = &[];
+            return None;
+        }
+
+        let body = &self.data[8..8 + size];
+        // RIFF 块按 2 字节对齐
+        let next = (8 + size + (size & 1)).min(self.data.len());
+        self.data = &self.data[next..];
+        Some((chunk_id, body))
+    }
+}
+
+fn chunks(data: &[u8]) -> ChunkIter<'_> {
+    ChunkIter { data }
+}
+
+/// 查找指定类型的 LIST 块，返回去掉列表类型后的内容。
+fn find_list<'a>(data: &'a [u8], list_type: &[u8; 4]) -> Option<&'a [u8]> {
+    chunks(data)
+        .find(|(id, body)| id == b"LIST" && body.get(..4) == Some(&list_type[..]))
+        .map(|(_, body)| &body[4..])
+}
+
+/// 查找指定 ID 的块。
+fn find_chunk<'a>(data: &'a [u8], chunk_id: &[u8; 4]) -> Option<&'a [u8]> {
+    chunks(data).find(|(id, _)| id == chunk_id).map(|(_, body)| body)
+}
+
+/// 将 WAVEFORMATEX 的 wFormatTag 转换为编码名称。
+pub(super) fn audio_codec_name(format_tag: u16) -> String {
+    match format_tag {
+        0x0001 => "pcm".to_string(),
+        0x0050 => "mp2".to_string(),
+        0x0055 => "mp3".to_string(),
+        0x00FF | 0x1610 => "aac".to_string(),
+        0x2000 => "ac3".to_string(),
+        0x2001 => "dts".to_string(),
+        0x0161 | 0x0162 => "wma".to_string(),
+        0x566F => "vorbis".to_string(),
+        tag => format!("0x{:04X}", tag),
+    }
+}
+
+/// 解析 strl 列表（strh + strf），返回轨道信息和按流头计算的时长（秒）。
+fn parse_stream(strl: &[u8]) -> Option<(TrackInfo, f64)> {
+    let strh = find_chunk(strl, b"strh")?;
+    let strf = find_chunk(strl, b"strf").unwrap_or(&[]);
+
+    let scale = le_u32(strh, 20);
+    let rate = le_u32(strh, 24);
+    let length = le_u32(strh, 32);
+    let seconds = if rate > 0 {
+        length as f64 * scale as f64 / rate as f64
+    } else {
+        0.0
+    };
+
+    let track = match strh.get(..4) {
+        Some(b"vids") => {
+            // BITMAPINFOHEADER：biCompression 为空时退回 strh 中的 fccHandler
+            let mut codec = fourcc(strf, 16);
+            if codec.is_empty() {
+                codec = fourcc(strh, 4);
+            }
+            let mut track = TrackInfo::new(TrackType::Video, &codec);
+            track.width = le_u32(strf, 4);
+            // 高度为负表示自上而下的位图
+            track.height = (le_u32(strf, 8) as i32).unsigned_abs();
+            track
+        }
+        Some(b"auds") => {
+            // WAVEFORMATEX
+            let mut track = TrackInfo::new(TrackType::Audio, &audio_codec_name(le_u16(strf, 0)));
+            track.channels = le_u16(strf, 2) as u32;
+            track.sampling_frequency = le_u32(strf, 4) as f64;
+            track
+        }
+        Some(b"txts") => TrackInfo::new(TrackType::Subtitle, &fourcc(strh, 4)),
+        _ => TrackInfo::new(TrackType::Other, &fourcc(strh, 4)),
+    };
+
+    Some((track, seconds))
+}
+
+/// 解析 hdrl 列表内容。
+fn parse_hdrl(hdrl: &[u8]) -> Result<VideoMetadata, String> {
+    let avih = find_chunk(hdrl, b"avih").ok_or("Missing avih chunk in AVI file")?;
+    let micro_sec_per_frame = le_u32(avih, 0);
+    let mut total_frames = le_u32(avih, 16);
+
+    // OpenDML（>1GB）文件中 avih 只统计第一个 RIFF 块，dmlh 才是总帧数
+    if let Some(dmlh) = find_list(hdrl, b"odml").and_then(|odml| find_chunk(odml, b"dmlh")) {
+        let odml_frames = le_u32(dmlh, 0);
+        if odml_frames > total_frames {
+            total_frames = odml_frames;
+        }
+    }
+
+    let mut tracks = Vec::new();
+    let mut video_stream_seconds = 0.0;
+    for (id, body) in chunks(hdrl) {
+        if id != *b"LIST" || body.get(..4) != Some(b"strl") {
+            continue;
+        }
+        if let Some((track, seconds)) = parse_stream(&body[4..]) {
+            if track.track_type == TrackType::Video && video_stream_seconds == 0.0 {
+                video_stream_seconds = seconds;
+            }
+            tracks.push(track);
+        }
+    }
+
+    let header_seconds = total_frames as f64 * micro_sec_per_frame as f64 / 1_000_000.0;
+    let video_duration_seconds = if header_seconds > 0.0 {
+        header_seconds.max(video_stream_seconds)
+    } else {
+        video_stream_seconds
+    };
+
+    // avih 中的宽高作为视频流缺少 strf 时的兜底
+    if let Some(video) = tracks.iter_mut().find(|t| t.track_type == TrackType::Video) {
+        if video.width == 0 || video.height == 0 {
+            video.width = le_u32(avih, 32);
+            video.height = le_u32(avih, 36);
+        }
+    }
+
+    Ok(VideoMetadata {
+        container: "avi".to_string(),
+        video_duration_seconds,
+        tracks,
+        ..Default::default()
+    })
+}
+
+/// 提取 AVI 文件的元数据信息。
+/// hdrl 列表位于 movi 之前，只需读取文件开头的少量数据。
+pub(super) fn get_avi_metadata(file_path: &str) -> Result<VideoMetadata, String> {
+    let file = File::open(file_path).map_err(|e| e.to_string())?;
+    let file_len = file.metadata().map_err(|e| e.to_string())?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut header = [0u8; 12];
+    reader.read_exact(&mut header).map_err(|e| e.to_string())?;
+    if !is_avi_signature(&header) {
+        return Err("Invalid AVI file".into());
+    }
+
+    let mut position = 12u64;
+    while position + 12 <= file_len {
+        reader.seek(SeekFrom::Start(position)).map_err(|e| e.to_string())?;
+        let mut chunk_header = [0u8; 12];
+        reader.read_exact(&mut chunk_header).map_err(|e| e.to_string())?;
+        let size = le_u32(&chunk_header, 4);
+
+        if &chunk_header[..4] == b"LIST" {
+            match &chunk_header[8..12] {
+                b"hdrl" => {
+                    if size > MAX_HDRL_SIZE || position + 8 + size as u64 > file_len {
+                        return Err(format!("Invalid hdrl list size: {}", size));
+                    }
+                    let mut hdrl = vec![0u8; size.saturating_sub(4) as usize];
+                    reader.read_exact(&mut hdrl).map_err(|e| e.to_string())?;
+                    return parse_hdrl(&hdrl);
+                }
+                // 到达数据区仍未找到 hdrl，文件不完整
+                b"movi" => break,
+                _ => {}
+            }
+        }
+
+        position += 8 + size as u64 + (size & 1) as u64;
+    }
+
+    Err("Missing hdrl list in AVI file".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut data = id.to_vec();
+        data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        data.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            data.push(0);
+        }
+        data
+    }
+
+    fn list(list_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut content = list_type.to_vec();
+        content.extend_from_slice(body);
+        chunk(b"LIST", &content)
+    }
+
+    #[test]
+    fn test_parse_hdrl_with_odml() {
+        let mut avih = vec![0u8; 56];
+        avih[0..4].copy_from_slice(&40_000u32.to_le_bytes()); // 25fps
+        avih[16..20].copy_from_slice(&1_000u32.to_le_bytes());
+
+        let mut strh = vec![0u8; 56];
+        strh[..4].copy_from_slice(b"vids");
+        let mut strf = vec![0u8; 40];
+        strf[4..8].copy_from_slice(&720u32.to_le_bytes());
+        strf[8..12].copy_from_slice(&(-480i32).to_le_bytes());
+        strf[16..20].copy_from_slice(b"XVID");
+        let mut strl = chunk(b"strh", &strh);
+        strl.extend(chunk(b"strf", &strf));
+
+        let mut hdrl = chunk(b"avih", &avih);
+        hdrl.extend(list(b"strl", &strl));
+        hdrl.extend(list(b"odml", &chunk(b"dmlh", &90_000u32.to_le_bytes())));
+
+        let metadata = parse_hdrl(&hdrl).unwrap();
+        assert_eq!(metadata.container, "avi");
+        assert_eq!(metadata.video_duration_seconds, 3600.0);
+
+        let video = metadata.video_track().unwrap();
+        assert_eq!(video.codec, "XVID");
+        assert_eq!((video.width, video.height), (720, 480));
+    }
+
+    #[test]
+    fn test_oversized_chunk_stops_iteration() {
+        let mut data = b"avih".to_vec();
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(chunks(&data).count(), 0);
+        assert!(parse_hdrl(&data).is_err());
+    }
+}
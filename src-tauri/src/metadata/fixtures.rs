@@ -0,0 +1,244 @@
+//! 测试用的最小容器文件构造器，以及由其派生的截断 / 损坏样本。
+
+use std::path::PathBuf;
+
+use super::*;
+
+/// 描述一个包含一条视频轨和一条音频轨的最小视频文件。
+pub(super) struct Fixture {
+    pub duration_seconds: u32,
+    pub width: u32,
+    pub height: u32,
+    pub channels: u32,
+    pub sampling_frequency: u32,
+    /// ISO 639-2 语言代码，AVI 不保存语言
+    pub language: &'static str,
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Fixture {
+            duration_seconds: 90,
+            width: 1920,
+            height: 1080,
+            channels: 2,
+            sampling_frequency: 48_000,
+            language: "eng",
+        }
+    }
+}
+
+/// 按最短长度编码 EBML 元素大小。
+fn ebml_size(size: usize) -> Vec<u8> {
+    let length = (1..=8).find(|length| (size as u64) < (1u64 << (7 * length)) - 1).unwrap();
+    let mut data = (size as u64).to_be_bytes()[8 - length..].to_vec();
+    data[0] |= 0x80 >> (length - 1);
+    data
+}
+
+/// 构造 EBML 元素：ID + 大小 + 内容
+fn ebml(id: u32, body: &[u8]) -> Vec<u8> {
+    let mut data: Vec<u8> = id.to_be_bytes().into_iter().skip_while(|&b| b == 0).collect();
+    data.extend(ebml_size(body.len()));
+    data.extend_from_slice(body);
+    data
+}
+
+/// 构造 ISO-BMFF 盒子：4 字节大小 + 4 字节类型 + 内容
+fn mp4_box(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut data = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(name);
+    data.extend_from_slice(body);
+    data
+}
+
+/// 构造 RIFF 块，奇数长度补齐一个字节
+fn riff_chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut data = id.to_vec();
+    data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    data.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        data.push(0);
+    }
+    data
+}
+
+/// 构造 RIFF LIST 块
+fn riff_list(list_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    riff_chunk(b"LIST", &[&list_type[..], body].concat())
+}
+
+impl Fixture {
+    /// EBML 头 + Segment(Info, Tracks, Cluster)
+    pub fn mkv(&self) -> Vec<u8> {
+        let info = [
+            ebml(TIMECODE_SCALE_ID, &[0x0F, 0x42, 0x40]),
+            ebml(DURATION_ID, &(self.duration_seconds as f64 * 1000.0).to_be_bytes()),
+        ].concat();
+
+        let video = [
+            ebml(TRACK_TYPE_ID, &[1]),
+            ebml(CODEC_ID_ID, b"V_MPEG4/ISO/AVC"),
+            ebml(VIDEO_ID, &[
+                ebml(PIXEL_WIDTH_ID, &(self.width as u16).to_be_bytes()),
+                ebml(PIXEL_HEIGHT_ID, &(self.height as u16).to_be_bytes()),
+            ].concat()),
+        ].concat();
+        let audio = [
+            ebml(TRACK_TYPE_ID, &[2]),
+            ebml(CODEC_ID_ID, b"A_AAC"),
+            ebml(LANGUAGE_ID, self.language.as_bytes()),
+            ebml(AUDIO_ID, &[
+                ebml(CHANNELS_ID, &[self.channels as u8]),
+                ebml(SAMPLING_FREQUENCY_ID, &(self.sampling_frequency as f32).to_be_bytes()),
+            ].concat()),
+        ].concat();
+        let tracks = [ebml(TRACK_ENTRY_ID, &video), ebml(TRACK_ENTRY_ID, &audio)].concat();
+
+        let segment = [
+            ebml(INFO_ID, &info),
+            ebml(TRACKS_ID, &tracks),
+            ebml(CLUSTER_ID, &[0u8; 32]),
+        ].concat();
+
+        [ebml(EBML_HEADER_ID, &ebml(DOC_TYPE_ID, b"matroska")), ebml(SEGMENT_ID, &segment)].concat()
+    }
+
+    /// ftyp + moov(mvhd, 视频 trak, 音频 trak) + mdat
+    pub fn mp4(&self) -> Vec<u8> {
+        let trak = |handler: &[u8; 4], codec: &[u8; 4], entry: &[u8]| {
+            let mut mdhd = vec![0u8; 24];
+            mdhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+            mdhd[16..20].copy_from_slice(&(self.duration_seconds * 1000).to_be_bytes());
+            let packed = self.language.bytes().fold(0u16, |acc, c| (acc << 5) | (c - 0x60) as u16);
+            mdhd[20..22].copy_from_slice(&packed.to_be_bytes());
+
+            let mut hdlr = vec![0u8; 24];
+            hdlr[8..12].copy_from_slice(handler);
+
+            let stsd = [&[0, 0, 0, 0, 0, 0, 0, 1][..], &mp4_box(codec, entry)].concat();
+            let minf = mp4_box(b"minf", &mp4_box(b"stbl", &mp4_box(b"stsd", &stsd)));
+            let mdia = [mp4_box(b"mdhd", &mdhd), mp4_box(b"hdlr", &hdlr), minf].concat();
+            mp4_box(b"trak", &mp4_box(b"mdia", &mdia))
+        };
+
+        // VisualSampleEntry 固定部分 78 字节，宽高位于 24..28
+        let mut visual = vec![0u8; 78];
+        visual[24..26].copy_from_slice(&(self.width as u16).to_be_bytes());
+        visual[26..28].copy_from_slice(&(self.height as u16).to_be_bytes());
+        // AudioSampleEntry 固定部分 28 字节，采样率为 16.16 定点数
+        let mut sound = vec![0u8; 28];
+        sound[16..18].copy_from_slice(&(self.channels as u16).to_be_bytes());
+        sound[24..28].copy_from_slice(&(self.sampling_frequency << 16).to_be_bytes());
+
+        let mut mvhd = vec![0u8; 100];
+        mvhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+        mvhd[16..20].copy_from_slice(&(self.duration_seconds * 1000).to_be_bytes());
+        let moov = [mp4_box(b"mvhd", &mvhd), trak(b"vide", b"avc1", &visual), trak(b"soun", b"mp4a", &sound)].concat();
+
+        [
+            mp4_box(b"ftyp", b"isom\0\0\x02\0isomavc1"),
+            mp4_box(b"moov", &moov),
+            mp4_box(b"mdat", &[0u8; 32]),
+        ].concat()
+    }
+
+    /// RIFF AVI(hdrl(avih, 视频 strl, 音频 strl), movi)，帧率 25fps
+    pub fn avi(&self) -> Vec<u8> {
+        let frames = self.duration_seconds * 25;
+
+        let mut avih = vec![0u8; 56];
+        avih[0..4].copy_from_slice(&40_000u32.to_le_bytes());
+        avih[16..20].copy_from_slice(&frames.to_le_bytes());
+        avih[32..36].copy_from_slice(&self.width.to_le_bytes());
+        avih[36..40].copy_from_slice(&self.height.to_le_bytes());
+
+        let strh = |stream_type: &[u8; 4], handler: &[u8; 4], scale: u32, rate: u32, length: u32| {
+            let mut strh = vec![0u8; 56];
+            strh[..4].copy_from_slice(stream_type);
+            strh[4..8].copy_from_slice(handler);
+            strh[20..24].copy_from_slice(&scale.to_le_bytes());
+            strh[24..28].copy_from_slice(&rate.to_le_bytes());
+            strh[32..36].copy_from_slice(&length.to_le_bytes());
+            riff_chunk(b"strh", &strh)
+        };
+
+        let mut bitmap_info = vec![0u8; 40];
+        bitmap_info[4..8].copy_from_slice(&self.width.to_le_bytes());
+        bitmap_info[8..12].copy_from_slice(&self.height.to_le_bytes());
+        bitmap_info[16..20].copy_from_slice(b"H264");
+        let video = [strh(b"vids", b"H264", 1, 25, frames), riff_chunk(b"strf", &bitmap_info)].concat();
+
+        let mut wave_format = vec![0u8; 18];
+        wave_format[0..2].copy_from_slice(&0x00FFu16.to_le_bytes());
+        wave_format[2..4].copy_from_slice(&(self.channels as u16).to_le_bytes());
+        wave_format[4..8].copy_from_slice(&self.sampling_frequency.to_le_bytes());
+        let audio = [
+            strh(b"auds", b"\0\0\0\0", 1, self.sampling_frequency, self.duration_seconds * self.sampling_frequency),
+            riff_chunk(b"strf", &wave_format),
+        ].concat();
+
+        let hdrl = [riff_chunk(b"avih", &avih), riff_list(b"strl", &video), riff_list(b"strl", &audio)].concat();
+        let body = [&b"AVI "[..], &riff_list(b"hdrl", &hdrl), &riff_list(b"movi", &[0u8; 32])].concat();
+        riff_chunk(b"RIFF", &body)
+    }
+}
+
+/// 由合法样本派生的损坏样本。
+pub(super) enum Corruption {
+    /// 只保留前 n 个字节，模拟未下载完或复制中断的文件
+    Truncated(usize),
+    /// 把某个字节改写为指定值，覆盖大小字段溢出、ID 非法、版本号未知等情况
+    Overwritten(usize, u8),
+}
+
+impl Corruption {
+    pub fn apply(&self, data: &[u8]) -> Vec<u8> {
+        match *self {
+            Corruption::Truncated(length) => data[..length].to_vec(),
+            Corruption::Overwritten(offset, value) => {
+                let mut data = data.to_vec();
+                data[offset] = value;
+                data
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Corruption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Corruption::Truncated(length) => write!(f, "truncated to {} bytes", length),
+            Corruption::Overwritten(offset, value) => write!(f, "byte {} set to 0x{:02X}", offset, value),
+        }
+    }
+}
+
+/// 生成损坏样本：每个截断长度，以及每个字节分别改写为 0x00 / 0x7F / 0xFF。
+pub(super) fn corruptions(data: &[u8]) -> impl Iterator<Item = Corruption> {
+    let truncated = (0..data.len()).map(Corruption::Truncated);
+    let overwritten = (0..data.len()).flat_map(|offset| {
+        [0x00, 0x7F, 0xFF].into_iter().map(move |value| Corruption::Overwritten(offset, value))
+    });
+    truncated.chain(overwritten)
+}
+
+/// 临时文件，离开作用域时删除。
+pub(super) struct TempFile(PathBuf);
+
+impl TempFile {
+    pub fn new(name: &str) -> Self {
+        TempFile(std::env::temp_dir().join(format!("local_video_{}_{}", std::process::id(), name)))
+    }
+
+    pub fn write(&self, data: &[u8]) -> &str {
+        std::fs::write(&self.0, data).unwrap();
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
@@ -0,0 +1,331 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+use super::{TrackInfo, TrackType, VideoMetadata};
+
+/// 脚本数据标签允许读取的最大字节数。
+const MAX_SCRIPT_TAG_SIZE: usize = 1024 * 1024;
+/// 查找 onMetaData 时最多检查的标签数，onMetaData 通常是第一个标签。
+const MAX_LEADING_TAGS: usize = 8;
+/// 标签头：类型(1) + 数据大小(3) + 时间戳(3) + 扩展时间戳(1) + 流 ID(3)。
+const TAG_HEADER_LEN: usize = 11;
+const TAG_TYPE_SCRIPT: u8 = 18;
+/// AMF0 值最大嵌套层数，防止恶意文件造成栈溢出。
+const MAX_AMF_DEPTH: usize = 16;
+
+/// 判断文件头是否为 `FLV` 版本 1。
+pub(super) fn is_flv_signature(header: &[u8]) -> bool {
+    header.len() >= 4 && &header[..3] == b"FLV" && header[3] == 1
+}
+
+/// 读取大端 24 位整数，越界时返回 0。
+fn be_u24(data: &[u8], offset: usize) -> u32 {
+    data.get(offset..offset + 3)
+        .map(|b| u32::from_be_bytes([0, b[0], b[1], b[2]]))
+        .unwrap_or(0)
+}
+
+/// AMF0 值，只保留元数据需要的类型。
+#[derive(Debug, PartialEq)]
+enum AmfValue {
+    Number(f64),
+    Boolean(bool),
+    String(String),
+    Object(Vec<(String, AmfValue)>),
+    /// null、数组、日期等不关心的值
+    Other,
+}
+
+/// 从 data 开头取出 len 个字节。
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if data.len() < len {
+        return None;
+    }
+    let (head, rest) = data.split_at(len);
+    *data = rest;
+    Some(head)
+}
+
+fn take_u16(data: &mut &[u8]) -> Option<usize> {
+    take(data, 2).map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+}
+
+fn take_u32(data: &mut &[u8]) -> Option<usize> {
+    take(data, 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+}
+
+fn take_string(data: &mut &[u8], len: usize) -> Option<String> {
+    take(data, len).map(|b| String::from_utf8_lossy(b).to_string())
+}
+
+/// 读取对象或 ECMA 数组的属性，直到 0x00 0x00 0x09 结束标记。
+fn read_amf_properties(data: &mut &[u8], depth: usize) -> Option<Vec<(String, AmfValue)>> {
+    let mut properties = Vec::new();
+    loop {
+        let key_len = take_u16(data)?;
+        if key_len == 0 && data.first() == Some(&0x09) {
+            *data = &data[1..];
+            return Some(properties);
+        }
+        let key = take_string(data, key_len)?;
+        let value = read_amf_value(data, depth + 1)?;
+        properties.push((key, value));
+    }
+}
+
+/// 读取一个 AMF0 值，遇到不支持的类型或数据不足时返回 None。
+fn read_amf_value(data: &mut &[u8], depth: usize) -> Option<AmfValue> {
+    if depth > MAX_AMF_DEPTH {
+        return None;
+    }
+    let marker = *take(data, 1)?.first()?;
+    match marker {
+        0x00 => take(data, 8).map(|b| AmfValue::Number(f64::from_be_bytes(b.try_into().unwrap()))),
+        0x01 => take(data, 1).map(|b| AmfValue::Boolean(b[0] != 0)),
+        0x02 => {
+            let len = take_u16(data)?;
+            take_string(data, len).map(AmfValue::String)
+        }
+        0x03 => read_amf_properties(data, depth).map(AmfValue::Object),
+        // null / undefined
+        0x05 | 0x06 => Some(AmfValue::Other),
+        // reference
+        0x07 => take(data, 2).map(|_| AmfValue::Other),
+        // ECMA 数组的元素个数不可靠，按结束标记读取
+        0x08 => {
+            take_u32(data)?;
+            read_amf_properties(data, depth).map(AmfValue::Object)
+        }
+        0x0A => {
+            let count = take_u32(data)?;
+            for _ in 0..count {
+                read_amf_value(data, depth + 1)?;
+            }
+            Some(AmfValue::Other)
+        }
+        // date: 毫秒(8) + 时区(2)
+        0x0B => take(data, 10).map(|_| AmfValue::Other),
+        // long string / XML document
+        0x0C | 0x0F => {
+            let len = take_u32(data)?;
+            take_string(data, len).map(AmfValue::String)
+        }
+        _ => None,
+    }
+}
+
+/// 将 FLV 的 videocodecid 转换为编码名称。
+fn video_codec_name(codec_id: f64) -> String {
+    match codec_id as u32 {
+        2 => "H.263".to_string(),
+        3 => "Screen".to_string(),
+        4 | 5 => "VP6".to_string(),
+        7 => "H.264".to_string(),
+        12 => "HEVC".to_string(),
+        13 => "AV1".to_string(),
+        id => id.to_string(),
+    }
+}
+
+/// 将 FLV 的 audiocodecid 转换为编码名称。
+fn audio_codec_name(codec_id: f64) -> String {
+    match codec_id as u32 {
+        0 | 3 => "PCM".to_string(),
+        1 => "ADPCM".to_string(),
+        2 | 14 => "MP3".to_string(),
+        4..=6 => "Nellymoser".to_string(),
+        10 => "AAC".to_string(),
+        11 => "Speex".to_string(),
+        id => id.to_string(),
+    }
+}
+
+/// 编码 ID 可能是数字，也可能是 Enhanced FLV 的 FourCC 字符串。
+fn codec_from(value: Option<&AmfValue>, name_of: fn(f64) -> String) -> Option<String> {
+    match value? {
+        AmfValue::Number(id) => Some(name_of(*id)),
+        AmfValue::String(fourcc) => Some(fourcc.clone()),
+        _ => None,
+    }
+}
+
+/// 解析脚本数据标签中的 onMetaData。
+///
+/// `flags` 为文件头中的音视频标志，onMetaData 缺少编码信息时据此补充轨道。
+fn parse_on_metadata(script: &[u8], flags: u8) -> Option<VideoMetadata> {
+    let mut data = script;
+    if read_amf_value(&mut data, 0)? != AmfValue::String("onMetaData".to_string()) {
+        return None;
+    }
+    let properties = match read_amf_value(&mut data, 0)? {
+        AmfValue::Object(properties) => properties,
+        _ => return None,
+    };
+
+    let get = |key: &str| properties.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+    let number = |key: &str| match get(key) {
+        Some(AmfValue::Number(value)) if value.is_finite() && *value > 0.0 => *value,
+        _ => 0.0,
+    };
+
+    let mut tracks = Vec::new();
+    let video_codec = codec_from(get("videocodecid"), video_codec_name);
+    if video_codec.is_some() || flags & 0x01 != 0 {
+        let mut track = TrackInfo::new(TrackType::Video, &video_codec.unwrap_or_default());
+        track.width = number("width") as u32;
+        track.height = number("height") as u32;
+        tracks.push(track);
+    }
+    let audio_codec = codec_from(get("audiocodecid"), audio_codec_name);
+    if audio_codec.is_some() || flags & 0x04 != 0 {
+        let mut track = TrackInfo::new(TrackType::Audio, &audio_codec.unwrap_or_default());
+        track.sampling_frequency = number("audiosamplerate");
+        track.channels = match (number("audiochannels"), get("stereo")) {
+            (channels, _) if channels > 0.0 => channels as u32,
+            (_, Some(AmfValue::Boolean(stereo))) => if *stereo { 2 } else { 1 },
+            _ => 0,
+        };
+        tracks.push(track);
+    }
+
+    Some(VideoMetadata {
+        container: "flv".to_string(),
+        video_duration_seconds: number("duration"),
+        tracks,
+        ..Default::default()
+    })
+}
+
+/// 通过文件末尾的 PreviousTagSize 找到最后一个标签，返回其时间戳（秒）。
+fn last_tag_timestamp<R: Read + Seek>(reader: &mut R, file_len: u64) -> Option<f64> {
+    reader.seek(SeekFrom::Start(file_len.checked_sub(4)?)).ok()?;
+    let mut previous_tag_size = [0u8; 4];
+    reader.read_exact(&mut previous_tag_size).ok()?;
+    let tag_start = file_len.checked_sub(4 + u32::from_be_bytes(previous_tag_size) as u64)?;
+
+    reader.seek(SeekFrom::Start(tag_start)).ok()?;
+    let mut tag_header = [0u8; TAG_HEADER_LEN];
+    reader.read_exact(&mut tag_header).ok()?;
+    let timestamp = be_u24(&tag_header, 4) | (tag_header[7] as u32) << 24;
+    Some(timestamp as f64 / 1000.0)
+}
+
+/// 提取 FLV 文件的元数据信息。
+/// 优先读取开头的 onMetaData，缺少时长时再用最后一个标签的时间戳估算。
+pub(super) fn get_flv_metadata(file_path: &str) -> Result<VideoMetadata, String> {
+    let file = File::open(file_path).map_err(|e| e.to_string())?;
+    let file_len = file.metadata().map_err(|e| e.to_string())?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut header = [0u8; 9];
+    reader.read_exact(&mut header).map_err(|e| e.to_string())?;
+    if !is_flv_signature(&header) {
+        return Err("Invalid FLV file".into());
+    }
+    let flags = header[4];
+    // 文件头之后是 4 字节的 PreviousTagSize0
+    let mut position = u32::from_be_bytes([header[5], header[6], header[7], header[8]]) as u64 + 4;
+
+    let mut metadata = None;
+    for _ in 0..MAX_LEADING_TAGS {
+        if position + TAG_HEADER_LEN as u64 > file_len {
+            break;
+        }
+        reader.seek(SeekFrom::Start(position)).map_err(|e| e.to_string())?;
+        let mut tag_header = [0u8; TAG_HEADER_LEN];
+        reader.read_exact(&mut tag_header).map_err(|e| e.to_string())?;
+        let data_size = be_u24(&tag_header, 1) as usize;
+
+        if tag_header[0] & 0x1F == TAG_TYPE_SCRIPT && data_size <= MAX_SCRIPT_TAG_SIZE {
+            let mut script = vec![0u8; data_size];
+            reader.read_exact(&mut script).map_err(|e| e.to_string())?;
+            metadata = parse_on_metadata(&script, flags);
+            if metadata.is_some() {
+                break;
+            }
+        }
+        position += (TAG_HEADER_LEN + data_size + 4) as u64;
+    }
+
+    // 没有 onMetaData 时只能根据文件头标志判断有哪些轨道
+    let mut metadata = metadata.unwrap_or_else(|| {
+        let mut tracks = Vec::new();
+        if flags & 0x01 != 0 {
+            tracks.push(TrackInfo::new(TrackType::Video, ""));
+        }
+        if flags & 0x04 != 0 {
+            tracks.push(TrackInfo::new(TrackType::Audio, ""));
+        }
+        VideoMetadata {
+            container: "flv".to_string(),
+            tracks,
+            ..Default::default()
+        }
+    });
+    if metadata.video_duration_seconds <= 0.0 {
+        metadata.video_duration_seconds = last_tag_timestamp(&mut reader, file_len).unwrap_or(0.0);
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amf_string(value: &str) -> Vec<u8> {
+        let mut data = vec![0x02];
+        data.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        data.extend_from_slice(value.as_bytes());
+        data
+    }
+
+    fn amf_property(key: &str, value: &[u8]) -> Vec<u8> {
+        let mut data = (key.len() as u16).to_be_bytes().to_vec();
+        data.extend_from_slice(key.as_bytes());
+        data.extend_from_slice(value);
+        data
+    }
+
+    fn amf_number(value: f64) -> Vec<u8> {
+        let mut data = vec![0x00];
+        data.extend_from_slice(&value.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn test_parse_on_metadata() {
+        let mut script = amf_string("onMetaData");
+        script.extend_from_slice(&[0x08, 0, 0, 0, 6]);
+        script.extend(amf_property("duration", &amf_number(42.5)));
+        script.extend(amf_property("width", &amf_number(640.0)));
+        script.extend(amf_property("height", &amf_number(360.0)));
+        script.extend(amf_property("videocodecid", &amf_number(7.0)));
+        script.extend(amf_property("audiocodecid", &amf_number(10.0)));
+        script.extend(amf_property("stereo", &[0x01, 1]));
+        // 嵌套的严格数组和 null 值需要被正确跳过
+        script.extend(amf_property("keyframes", &[0x0A, 0, 0, 0, 2, 0x05, 0x05]));
+        script.extend_from_slice(&[0, 0, 0x09]);
+
+        let metadata = parse_on_metadata(&script, 0x05).unwrap();
+        assert_eq!(metadata.container, "flv");
+        assert_eq!(metadata.video_duration_seconds, 42.5);
+
+        let video = metadata.video_track().unwrap();
+        assert_eq!((video.codec.as_str(), video.width, video.height), ("H.264", 640, 360));
+        let audio = metadata.audio_tracks().next().unwrap();
+        assert_eq!((audio.codec.as_str(), audio.channels), ("AAC", 2));
+    }
+
+    #[test]
+    fn test_truncated_amf_does_not_panic() {
+        let mut script = amf_string("onMetaData");
+        script.extend_from_slice(&[0x03]);
+        script.extend(amf_property("duration", &amf_number(1.0)));
+        assert!(parse_on_metadata(&script[..script.len() - 3], 0).is_none());
+        assert!(parse_on_metadata(&script, 0).is_none());
+
+        // 超过嵌套层数限制
+        let nested = [[0x0A, 0, 0, 0, 1]; MAX_AMF_DEPTH + 2].concat();
+        assert_eq!(read_amf_value(&mut nested.as_slice(), 0), None);
+    }
+}
@@ -0,0 +1,457 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+use super::{config_bit_depth, ColourInfo, CoverArt, HdrFormat, TrackInfo, TrackType, VideoMetadata};
+
+/// moov 盒子允许读取的最大字节数，防止损坏的文件导致巨量内存分配。
+const MAX_MOOV_SIZE: u64 = 64 * 1024 * 1024;
+
+/// 可能出现在 ISO-BMFF 文件开头的盒子类型。
+const TOP_LEVEL_SIGNATURES: &[&[u8; 4]] = &[b"ftyp", b"moov", b"mdat", b"free", b"skip", b"wide", b"pnot"];
+
+/// 判断文件头第 4~8 字节是否为 MP4/MOV 的盒子类型。
+pub(super) fn is_mp4_signature(header: &[u8]) -> bool {
+    header.get(4..8)
+        .is_some_and(|box_type| TOP_LEVEL_SIGNATURES.iter().any(|sig| &sig[..] == box_type))
+}
+
+/// 读取大端 u16，越界时返回 0。
+fn be_u16(data: &[u8], offset: usize) -> u16 {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .unwrap_or(0)
+}
+
+/// 读取大端 u32，越界时返回 0。
+fn be_u32(data: &[u8], offset: usize) -> u32 {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .unwrap_or(0)
+}
+
+/// 读取大端 u64，越界时返回 0。
+fn be_u64(data: &[u8], offset: usize) -> u64 {
+    data.get(offset..offset + 8)
+        .map(super::bytes_to_u64)
+        .unwrap_or(0)
+}
+
+/// 遍历内存中连续排列的盒子，依次返回 (类型, 内容)。
+struct BoxIter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> BoxIter<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BoxIter { data }
+    }
+}
+
+impl<'a> Iterator for BoxIter<'a> {
+    type Item = ([u8; 4], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.len() < 8 {
+            return None;
+        }
+
+        let mut box_type = [0u8; 4];
+        box_type.copy_from_slice(&self.data[4..8]);
+        let (header_len, size) = match be_u32(self.data, 0) {
+            1 if self.data.len() >= 16 => (16, be_u64(self.data, 8)),
+            0 => (8, self.data.len() as u64),
+            size => (8, size as u64),
+        };
+
+        // 盒子大小非法时停止遍历，而不是越界切片
+        if size < header_len as u64 || size > self.data.len() as u64 {
+            self.data = &[];
+            return None;
+        }
+
+        let size = size as usize;
+        let body = &self.data[header_len..size];
+        self.data = &self.data[size..];
+        Some((box_type, body))
+    }
+}
+
+/// 查找指定类型的第一个子盒子。
+fn find_box<'a>(data: &'a [u8], name: &[u8; 4]) -> Option<&'a [u8]> {
+    BoxIter::new(data).find(|(box_type, _)| box_type == name).map(|(_, body)| body)
+}
+
+/// 按路径逐级查找子盒子，例如 `["mdia", "minf", "stbl"]`。
+fn find_path<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> Option<&'a [u8]> {
+    path.iter().try_fold(data, |current, name| find_box(current, name))
+}
+
+/// 解析 mvhd / mdhd 盒子，返回 (timescale, duration)。
+/// 两者在 version/flags 之后的前几个字段布局一致。
+fn parse_media_header(data: &[u8]) -> Result<(u32, u64), String> {
+    match data.first() {
+        Some(0) if data.len() >= 20 => Ok((be_u32(data, 12), be_u32(data, 16) as u64)),
+        Some(1) if data.len() >= 32 => Ok((be_u32(data, 20), be_u64(data, 24))),
+        _ => Err("Invalid media header box".into()),
+    }
+}
+
+/// 解析 mdhd 中的语言字段（ISO 639-2/T，每个字符 5 位）。
+fn parse_language(mdhd: &[u8]) -> String {
+    let offset = if mdhd.first() == Some(&1) { 32 } else { 20 };
+    let packed = be_u16(mdhd, offset);
+    // 小于 0x400 的值是 QuickTime 的 Macintosh 语言码，不做转换
+    if packed < 0x400 {
+        return "und".to_string();
+    }
+    (0..3)
+        .rev()
+        .map(|i| (((packed >> (i * 5)) & 0x1F) as u8 + 0x60) as char)
+        .collect()
+}
+
+/// 根据 hdlr 盒子的 handler_type 判断轨道类型。
+fn parse_handler(hdlr: &[u8]) -> TrackType {
+    match hdlr.get(8..12) {
+        Some(b"vide") => TrackType::Video,
+        Some(b"soun") => TrackType::Audio,
+        Some(b"sbtl") | Some(b"subt") | Some(b"text") | Some(b"clcp") => TrackType::Subtitle,
+        _ => TrackType::Other,
+    }
+}
+
+/// 解析单个 trak 盒子，返回轨道信息和以秒为单位的轨道时长。
+fn parse_trak(trak: &[u8]) -> Option<(TrackInfo, f64)> {
+    let mdia = find_box(trak, b"mdia")?;
+    let track_type = find_box(mdia, b"hdlr").map(parse_handler).unwrap_or(TrackType::Other);
+
+    // stsd: version/flags(4) + entry_count(4)，随后是第一个样本描述
+    let stsd = find_path(mdia, &[b"minf", b"stbl", b"stsd"]).unwrap_or(&[]);
+    let (codec, entry) = stsd.get(8..)
+        .and_then(|entries| BoxIter::new(entries).next())
+        .map(|(codec, entry)| (String::from_utf8_lossy(&codec).trim_end().to_string(), entry))
+        .unwrap_or_default();
+
+    let mut track = TrackInfo::new(track_type, &codec);
+    let mut seconds = 0.0;
+    if let Some(mdhd) = find_box(mdia, b"mdhd") {
+        track.language = parse_language(mdhd);
+        if let Ok((timescale, duration)) = parse_media_header(mdhd) {
+            if timescale > 0 {
+                seconds = duration as f64 / timescale as f64;
+            }
+        }
+    }
+
+    match track_type {
+        TrackType::Video => {
+            // VisualSampleEntry: 8 字节公共头 + 16 字节保留字段后是宽高
+            track.width = be_u16(entry, 24) as u32;
+            track.height = be_u16(entry, 26) as u32;
+            parse_visual_extensions(&mut track, entry);
+        }
+        TrackType::Audio => {
+            // QuickTime 第二版声音描述把声道和采样率放在扩展字段中
+            if be_u16(entry, 8) == 2 {
+                track.sampling_frequency = entry.get(32..40)
+                    .map(|b| f64::from_be_bytes(b.try_into().unwrap()))
+                    .unwrap_or(0.0);
+                track.channels = be_u32(entry, 40);
+            } else {
+                track.channels = be_u16(entry, 16) as u32;
+                track.sampling_frequency = (be_u32(entry, 24) >> 16) as f64;
+            }
+        }
+        _ => {}
+    }
+
+    Some((track, seconds))
+}
+
+/// 解析 VisualSampleEntry 固定字段之后的扩展盒子，读取位深、色彩和 Dolby Vision 信息。
+fn parse_visual_extensions(track: &mut TrackInfo, entry: &[u8]) {
+    let mut colour = ColourInfo::default();
+    let mut dolby_vision_record = None;
+
+    // VisualSampleEntry 的固定字段共 78 字节
+    for (box_type, body) in BoxIter::new(entry.get(78..).unwrap_or(&[])) {
+        match &box_type {
+            b"colr" => {
+                // nclx（ISO）与 nclc（QuickTime）的前三个字段相同
+                if matches!(body.get(..4), Some(b"nclx") | Some(b"nclc")) {
+                    colour.primaries = be_u16(body, 4) as u64;
+                    colour.transfer_characteristics = be_u16(body, 6) as u64;
+                    colour.matrix_coefficients = be_u16(body, 8) as u64;
+                }
+            }
+            b"mdcv" | b"SmDm" => colour.has_mastering_metadata = true,
+            b"dvcC" | b"dvvC" | b"dvwC" => dolby_vision_record = Some(body),
+            b"hvcC" | b"avcC" | b"av1C" | b"vpcC" => {
+                if let Some(bit_depth) = config_bit_depth(&box_type, body) {
+                    track.bit_depth = bit_depth;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    track.hdr_format = colour.hdr_format();
+    if let Some(record) = dolby_vision_record {
+        track.set_dolby_vision(record);
+    } else if matches!(track.codec.as_str(), "dvh1" | "dvhe" | "dva1" | "dvav" | "dav1") {
+        // 单层 Dolby Vision 的样本描述类型本身就表明了格式
+        track.hdr_format = HdrFormat::DolbyVision;
+    }
+}
+
+/// 根据 ftyp 的 major brand 区分 mp4 / mov / 3gp。
+fn container_name(ftyp: Option<&[u8]>) -> &'static str {
+    match ftyp.and_then(|f| f.get(..4)) {
+        Some(b"qt  ") => "mov",
+        Some(brand) if brand.starts_with(b"3g") => "3gp",
+        Some(_) => "mp4",
+        // 早期 QuickTime 文件没有 ftyp
+        None => "mov",
+    }
+}
+
+/// 解析 moov 盒子内容。
+fn parse_moov(moov: &[u8], ftyp: Option<&[u8]>) -> Result<VideoMetadata, String> {
+    let mvhd = find_box(moov, b"mvhd").ok_or("Missing mvhd box in MP4 file")?;
+    let (timescale, duration) = parse_media_header(mvhd)?;
+
+    let mut video_duration_seconds = if timescale > 0 {
+        duration as f64 / timescale as f64
+    } else {
+        0.0
+    };
+
+    // 分片 MP4 的 mvhd 时长通常为 0，改用 mvex/mehd 中的总时长
+    if video_duration_seconds <= 0.0 && timescale > 0 {
+        if let Some(mehd) = find_path(moov, &[b"mvex", b"mehd"]) {
+            let fragment_duration = match mehd.first() {
+                Some(1) => be_u64(mehd, 4),
+                _ => be_u32(mehd, 4) as u64,
+            };
+            video_duration_seconds = fragment_duration as f64 / timescale as f64;
+        }
+    }
+
+    let mut tracks = Vec::new();
+    let mut longest_track = 0.0f64;
+    for (box_type, trak) in BoxIter::new(moov) {
+        if &box_type != b"trak" {
+            continue;
+        }
+        if let Some((track, seconds)) = parse_trak(trak) {
+            longest_track = longest_track.max(seconds);
+            tracks.push(track);
+        }
+    }
+
+    if video_duration_seconds <= 0.0 {
+        video_duration_seconds = longest_track;
+    }
+
+    Ok(VideoMetadata {
+        container: container_name(ftyp).to_string(),
+        video_duration_seconds,
+        tracks,
+        ..Default::default()
+    })
+}
+
+/// 解析 moov/udta/meta/ilst/covr 中的封面图片。
+fn parse_cover_art(moov: &[u8]) -> Option<CoverArt> {
+    let meta = find_path(moov, &[b"udta", b"meta"])?;
+    // ISO 的 meta 是带 version/flags 的 FullBox，QuickTime 写入的 meta 则没有
+    let meta = if meta.get(4..8) == Some(b"hdlr") { meta } else { meta.get(4..)? };
+    let data = find_path(meta, &[b"ilst", b"covr", b"data"])?;
+
+    // data: 类型标识(4) + 区域(4) + 图片内容
+    let image = data.get(8..).filter(|image| !image.is_empty())?;
+    let mime_type = match be_u32(data, 0) & 0x00FF_FFFF {
+        13 => "image/jpeg",
+        14 => "image/png",
+        27 => "image/bmp",
+        _ => super::image_mime_type(image)?,
+    };
+
+    Some(CoverArt {
+        mime_type: mime_type.to_string(),
+        data: image.to_vec(),
+    })
+}
+
+/// 读取 ftyp 和 moov 盒子内容。
+/// 只读取顶层盒子头部，跳过 mdat，找到 moov 后整体读入内存。
+fn read_moov(file_path: &str) -> Result<(Option<Vec<u8>>, Vec<u8>), String> {
+    let file = File::open(file_path).map_err(|e| e.to_string())?;
+    let file_len = file.metadata().map_err(|e| e.to_string())?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut ftyp: Option<Vec<u8>> = None;
+    let mut position = 0u64;
+    while position + 8 <= file_len {
+        reader.seek(SeekFrom::Start(position)).map_err(|e| e.to_string())?;
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header).map_err(|e| e.to_string())?;
+
+        let box_type = [header[4], header[5], header[6], header[7]];
+        let (header_len, size) = match be_u32(&header, 0) {
+            1 => {
+                let mut large_size = [0u8; 8];
+                reader.read_exact(&mut large_size).map_err(|e| e.to_string())?;
+                (16u64, u64::from_be_bytes(large_size))
+            }
+            0 => (8u64, file_len - position),
+            size => (8u64, size as u64),
+        };
+
+        if size < header_len {
+            return Err(format!("Invalid MP4 box size: {}", size));
+        }
+
+        let body_len = size - header_len;
+        match &box_type {
+            b"ftyp" | b"moov" => {
+                let limit = if &box_type == b"moov" { MAX_MOOV_SIZE } else { 1024 };
+                if body_len > limit || position + size > file_len {
+                    return Err(format!("Invalid {} box size: {}", String::from_utf8_lossy(&box_type), size));
+                }
+                let mut body = vec![0u8; body_len as usize];
+                reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+                if &box_type == b"moov" {
+                    return Ok((ftyp, body));
+                }
+                ftyp = Some(body);
+            }
+            _ => {}
+        }
+
+        position = position.checked_add(size).ok_or("Invalid MP4 box size")?;
+    }
+
+    Err("Missing moov box in MP4 file".into())
+}
+
+/// 提取 MP4 / MOV 文件的元数据信息。
+pub(super) fn get_mp4_metadata(file_path: &str) -> Result<VideoMetadata, String> {
+    let (ftyp, moov) = read_moov(file_path)?;
+    parse_moov(&moov, ftyp.as_deref())
+}
+
+/// 提取 MP4 / MOV 文件中 iTunes 风格的封面图片。
+pub(super) fn get_mp4_cover_art(file_path: &str) -> Result<Option<CoverArt>, String> {
+    let (_, moov) = read_moov(file_path)?;
+    Ok(parse_cover_art(&moov))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 构造一个盒子：4 字节大小 + 4 字节类型 + 内容
+    fn mp4_box(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut data = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(name);
+        data.extend_from_slice(body);
+        data
+    }
+
+    fn video_trak() -> Vec<u8> {
+        let mut mdhd = vec![0u8; 24];
+        mdhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+        mdhd[16..20].copy_from_slice(&5000u32.to_be_bytes());
+        // "eng" = (5, 14, 7)
+        mdhd[20..22].copy_from_slice(&((5u16 << 10) | (14 << 5) | 7).to_be_bytes());
+
+        let mut hdlr = vec![0u8; 24];
+        hdlr[8..12].copy_from_slice(b"vide");
+
+        let mut entry = vec![0u8; 78];
+        entry[24..26].copy_from_slice(&1920u16.to_be_bytes());
+        entry[26..28].copy_from_slice(&1080u16.to_be_bytes());
+        let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        stsd.extend(mp4_box(b"hvc1", &entry));
+
+        let stbl = mp4_box(b"stbl", &mp4_box(b"stsd", &stsd));
+        let minf = mp4_box(b"minf", &stbl);
+        let mut mdia = mp4_box(b"mdhd", &mdhd);
+        mdia.extend(mp4_box(b"hdlr", &hdlr));
+        mdia.extend(minf);
+        mp4_box(b"trak", &mp4_box(b"mdia", &mdia))
+    }
+
+    #[test]
+    fn test_parse_moov() {
+        let mut mvhd = vec![0u8; 100];
+        mvhd[12..16].copy_from_slice(&600u32.to_be_bytes());
+        mvhd[16..20].copy_from_slice(&(600u32 * 125).to_be_bytes());
+        let mut moov = mp4_box(b"mvhd", &mvhd);
+        moov.extend(video_trak());
+
+        let metadata = parse_moov(&moov, Some(b"isom")).unwrap();
+        assert_eq!(metadata.container, "mp4");
+        assert_eq!(metadata.video_duration_seconds, 125.0);
+
+        let video = metadata.video_track().unwrap();
+        assert_eq!(video.codec, "hvc1");
+        assert_eq!(video.language, "eng");
+        assert_eq!((video.width, video.height), (1920, 1080));
+    }
+
+    #[test]
+    fn test_parse_cover_art() {
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 1, 2, 3];
+        let mut data = 13u32.to_be_bytes().to_vec();
+        data.extend_from_slice(&[0, 0, 0, 0]);
+        data.extend_from_slice(&jpeg);
+        let ilst = mp4_box(b"ilst", &mp4_box(b"covr", &mp4_box(b"data", &data)));
+
+        // ISO FullBox 形式的 meta
+        let mut meta = vec![0, 0, 0, 0];
+        meta.extend(mp4_box(b"hdlr", &[0u8; 25]));
+        meta.extend(ilst.clone());
+        let moov = mp4_box(b"udta", &mp4_box(b"meta", &meta));
+        let cover = parse_cover_art(&moov).unwrap();
+        assert_eq!(cover.mime_type, "image/jpeg");
+        assert_eq!(cover.data, jpeg);
+
+        // QuickTime 形式的 meta 没有 version/flags
+        let mut meta = mp4_box(b"hdlr", &[0u8; 25]);
+        meta.extend(ilst);
+        let moov = mp4_box(b"udta", &mp4_box(b"meta", &meta));
+        assert_eq!(parse_cover_art(&moov).unwrap().extension(), "jpg");
+
+        assert!(parse_cover_art(&mp4_box(b"udta", &[])).is_none());
+    }
+
+    #[test]
+    fn test_parse_visual_extensions() {
+        let mut entry = vec![0u8; 78];
+        let mut hvcc = vec![0u8; 23];
+        hvcc[0] = 1;
+        hvcc[17] = 0xFA; // bitDepthLumaMinus8 = 2
+        hvcc[21] = 0x0F; // lengthSizeMinusOne = 3，不应影响位深
+        entry.extend(mp4_box(b"hvcC", &hvcc));
+        entry.extend(mp4_box(b"colr", &[b"nclx".as_slice(), &[0, 9, 0, 16, 0, 9, 0x80]].concat()));
+
+        let mut track = TrackInfo::new(TrackType::Video, "hvc1");
+        parse_visual_extensions(&mut track, &entry);
+        assert_eq!((track.bit_depth, track.hdr_format), (10, HdrFormat::Hdr10));
+
+        // 带 dvcC 的双层文件：profile 8
+        entry.extend(mp4_box(b"dvcC", &[1, 0, 8 << 1, 0x35, 0x10, 0, 0, 0]));
+        let mut track = TrackInfo::new(TrackType::Video, "hvc1");
+        parse_visual_extensions(&mut track, &entry);
+        assert_eq!((track.hdr_format, track.dolby_vision_profile), (HdrFormat::DolbyVision, 8));
+    }
+
+    #[test]
+    fn test_truncated_box_does_not_panic() {
+        let moov = mp4_box(b"mvhd", &[0u8; 100]);
+        assert!(parse_moov(&moov[..50], None).is_err());
+        assert_eq!(BoxIter::new(&[0, 0, 0, 200, b't', b'r', b'a', b'k']).count(), 0);
+    }
+}
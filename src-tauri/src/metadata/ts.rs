@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+use super::{TrackInfo, TrackType, VideoMetadata};
+
+/// 标准 TS 包长度；M2TS 在每个包前额外有 4 字节的 TP_extra_header。
+const TS_PACKET_SIZE: usize = 188;
+const M2TS_PACKET_SIZE: usize = 192;
+const SYNC_BYTE: u8 = 0x47;
+/// 判断同步时要求连续对齐的包数。
+const SYNC_PACKETS: usize = 3;
+/// 文件开头和末尾各读取的字节数，时长只根据这两段数据估算。
+const PROBE_SIZE: u64 = 4 * 1024 * 1024;
+const PAT_PID: u16 = 0x0000;
+/// PCR 基准和 PTS 都是 33 位的 90kHz 时钟。
+const CLOCK_RATE: f64 = 90_000.0;
+const TIMESTAMP_WRAP: u64 = 1 << 33;
+
+/// 检查 data 从 offset 开始是否连续出现同步字节。
+fn is_synced(data: &[u8], offset: usize, packet_size: usize) -> bool {
+    let sync_position = packet_size - TS_PACKET_SIZE;
+    (0..SYNC_PACKETS).all(|i| data.get(offset + i * packet_size + sync_position) == Some(&SYNC_BYTE))
+}
+
+/// 根据文件头判断包长度（188 或 192），不是 TS 时返回 None。
+fn detect_packet_size(header: &[u8]) -> Option<usize> {
+    [TS_PACKET_SIZE, M2TS_PACKET_SIZE]
+        .into_iter()
+        .find(|&packet_size| is_synced(header, 0, packet_size))
+}
+
+/// 判断文件头是否为 MPEG-TS / M2TS。需要至少 3 个包的数据。
+pub(super) fn is_ts_signature(header: &[u8]) -> bool {
+    detect_packet_size(header).is_some()
+}
+
+/// 在一段不一定从包边界开始的数据中找到第一个包的起始位置。
+fn sync_offset(data: &[u8], packet_size: usize) -> Option<usize> {
+    (0..packet_size).find(|&offset| is_synced(data, offset, packet_size))
+}
+
+/// 依次返回 data 中的 188 字节 TS 包，跳过 M2TS 的 4 字节前缀和失去同步的包。
+fn packets(data: &[u8], packet_size: usize) -> impl Iterator<Item = &[u8]> {
+    let offset = sync_offset(data, packet_size).unwrap_or(data.len());
+    data[offset..]
+        .chunks_exact(packet_size)
+        .map(move |chunk| &chunk[packet_size - TS_PACKET_SIZE..])
+        .filter(|packet| packet[0] == SYNC_BYTE)
+}
+
+/// 解析后的 TS 包
+struct Packet<'a> {
+    pid: u16,
+    payload_unit_start: bool,
+    adaptation_field: &'a [u8],
+    payload: &'a [u8],
+}
+
+fn parse_packet(packet: &[u8]) -> Option<Packet<'_>> {
+    // transport_error_indicator 置位的包内容不可信
+    if packet.len() < TS_PACKET_SIZE || packet[1] & 0x80 != 0 {
+        return None;
+    }
+
+    let pid = (((packet[1] & 0x1F) as u16) << 8) | packet[2] as u16;
+    let adaptation_field_control = (packet[3] >> 4) & 0x03;
+
+    let mut payload_start = 4;
+    let mut adaptation_field: &[u8] = &[];
+    if adaptation_field_control & 0x02 != 0 {
+        let length = packet[4] as usize;
+        adaptation_field = packet.get(5..5 + length)?;
+        payload_start = 5 + length;
+    }
+    let payload = if adaptation_field_control & 0x01 != 0 {
+        packet.get(payload_start..)?
+    } else {
+        &[]
+    };
+
+    Some(Packet {
+        pid,
+        payload_unit_start: packet[1] & 0x40 != 0,
+        adaptation_field,
+        payload,
+    })
+}
+
+/// 读取适配字段中的 PCR 基准值（90kHz）。
+fn parse_pcr(adaptation_field: &[u8]) -> Option<u64> {
+    if adaptation_field.len() < 7 || adaptation_field[0] & 0x10 == 0 {
+        return None;
+    }
+    let a = adaptation_field;
+    Some(((a[1] as u64) << 25) | ((a[2] as u64) << 17) | ((a[3] as u64) << 9) | ((a[4] as u64) << 1) | ((a[5] as u64) >> 7))
+}
+
+/// 读取 PES 头中的 PTS，只在 payload_unit_start 的包中出现。
+fn parse_pts(payload: &[u8]) -> Option<u64> {
+    if payload.len() < 14 || payload[..3] != [0x00, 0x00, 0x01] || payload[7] & 0x80 == 0 {
+        return None;
+    }
+    let p = &payload[9..14];
+    Some(
+        (((p[0] >> 1) & 0x07) as u64) << 30
+            | (p[1] as u64) << 22
+            | ((p[2] >> 1) as u64) << 15
+            | (p[3] as u64) << 7
+            | (p[4] >> 1) as u64,
+    )
+}
+
+/// 把跨越多个包的 PSI 段拼接完整。
+#[derive(Default)]
+struct SectionBuffer {
+    data: Vec<u8>,
+    started: bool,
+}
+
+impl SectionBuffer {
+    /// 追加一个包的负载，段完整时返回段内容（包含 3 字节段头）。
+    fn push(&mut self, packet: &Packet) -> Option<Vec<u8>> {
+        if packet.payload_unit_start {
+            let pointer = *packet.payload.first()? as usize;
+            self.data = packet.payload.get(1 + pointer..)?.to_vec();
+            self.started = true;
+        } else if self.started {
+            self.data.extend_from_slice(packet.payload);
+        } else {
+            return None;
+        }
+
+        if self.data.len() < 3 {
+            return None;
+        }
+        let section_length = (((self.data[1] & 0x0F) as usize) << 8) | self.data[2] as usize;
+        if self.data.len() < 3 + section_length {
+            return None;
+        }
+        self.started = false;
+        Some(self.data[..3 + section_length].to_vec())
+    }
+}
+
+/// 段内容中去掉段头、固定字段和末尾 CRC 后的部分。
+fn section_body(section: &[u8], table_id: u8, header_len: usize) -> Option<&[u8]> {
+    if section.first() != Some(&table_id) || section.len() < header_len + 4 {
+        return None;
+    }
+    Some(&section[header_len..section.len() - 4])
+}
+
+/// 解析 PAT，返回第一个节目的 PMT PID。
+fn parse_pat(section: &[u8]) -> Option<u16> {
+    section_body(section, 0x00, 8)?
+        .chunks_exact(4)
+        .find(|entry| entry[0] != 0 || entry[1] != 0) // program_number 0 指向 NIT
+        .map(|entry| (((entry[2] & 0x1F) as u16) << 8) | entry[3] as u16)
+}
+
+/// PMT 中的一条基本流
+struct ElementaryStream<'a> {
+    stream_type: u8,
+    pid: u16,
+    descriptors: &'a [u8],
+}
+
+/// 解析 PMT，返回 (PCR PID, 基本流列表)。
+fn parse_pmt(section: &[u8]) -> Option<(u16, Vec<ElementaryStream<'_>>)> {
+    let body = section_body(section, 0x02, 12)?;
+    let pcr_pid = (((section[8] & 0x1F) as u16) << 8) | section[9] as u16;
+    let program_info_length = (((section[10] & 0x0F) as usize) << 8) | section[11] as usize;
+
+    let mut streams = Vec::new();
+    let mut position = program_info_length;
+    while position + 5 <= body.len() {
+        let es_info_length = (((body[position + 3] & 0x0F) as usize) << 8) | body[position + 4] as usize;
+        let descriptors = match body.get(position + 5..position + 5 + es_info_length) {
+            Some(descriptors) => descriptors,
+            None => break,
+        };
+        streams.push(ElementaryStream {
+            stream_type: body[position],
+            pid: (((body[position + 1] & 0x1F) as u16) << 8) | body[position + 2] as u16,
+            descriptors,
+        });
+        position += 5 + es_info_length;
+    }
+    Some((pcr_pid, streams))
+}
+
+/// 遍历描述符，返回 (tag, 内容)。
+fn descriptors(data: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let (&tag, &length) = (rest.first()?, rest.get(1)?);
+        let body = rest.get(2..2 + length as usize)?;
+        rest = &rest[2 + length as usize..];
+        Some((tag, body))
+    })
+}
+
+/// 根据 PMT 中的 stream_type 和描述符判断轨道类型和编码。
+fn stream_track(stream: &ElementaryStream) -> TrackInfo {
+    let (track_type, codec) = match stream.stream_type {
+        0x01 => (TrackType::Video, "MPEG-1"),
+        0x02 => (TrackType::Video, "MPEG-2"),
+        0x10 => (TrackType::Video, "MPEG-4"),
+        0x1B => (TrackType::Video, "H.264"),
+        0x24 => (TrackType::Video, "HEVC"),
+        0x33 => (TrackType::Video, "VVC"),
+        0xEA => (TrackType::Video, "VC-1"),
+        // 3D 蓝光的 MVC 附属视图不作为独立视频轨道
+        0x20 => (TrackType::Other, "MVC"),
+        0x03 | 0x04 => (TrackType::Audio, "MP2"),
+        0x0F | 0x11 => (TrackType::Audio, "AAC"),
+        0x80 => (TrackType::Audio, "LPCM"),
+        0x81 => (TrackType::Audio, "AC3"),
+        0x82 | 0x85 | 0xA2 => (TrackType::Audio, "DTS"),
+        0x86 => (TrackType::Audio, "DTS-HD MA"),
+        0x83 => (TrackType::Audio, "TrueHD"),
+        0x84 | 0x87 | 0xA1 => (TrackType::Audio, "E-AC3"),
+        0x90 => (TrackType::Subtitle, "PGS"),
+        0x92 => (TrackType::Subtitle, "TextST"),
+        // 私有 PES 需要通过描述符判断内容
+        0x06 => descriptors(stream.descriptors)
+            .find_map(|(tag, body)| match tag {
+                0x6A => Some((TrackType::Audio, "AC3")),
+                0x7A => Some((TrackType::Audio, "E-AC3")),
+                0x7B => Some((TrackType::Audio, "DTS")),
+                0x59 => Some((TrackType::Subtitle, "DVB")),
+                0x56 => Some((TrackType::Subtitle, "Teletext")),
+                0x05 => match body.get(..4) {
+                    Some(b"AC-3") => Some((TrackType::Audio, "AC3")),
+                    Some(b"EAC3") => Some((TrackType::Audio, "E-AC3")),
+                    Some(b"DTS1") | Some(b"DTS2") | Some(b"DTS3") => Some((TrackType::Audio, "DTS")),
+                    Some(b"Opus") => Some((TrackType::Audio, "Opus")),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .unwrap_or((TrackType::Other, "private")),
+        _ => (TrackType::Other, ""),
+    };
+
+    let mut track = TrackInfo::new(track_type, codec);
+    if codec.is_empty() {
+        track.codec = format!("0x{:02X}", stream.stream_type);
+    }
+    // ISO_639_language_descriptor：3 字节语言代码 + 1 字节音频类型
+    if let Some((_, body)) = descriptors(stream.descriptors).find(|(tag, body)| *tag == 0x0A && body.len() >= 3) {
+        let language = String::from_utf8_lossy(&body[..3]).trim().to_ascii_lowercase();
+        if !language.is_empty() {
+            track.language = language;
+        }
+    }
+    track
+}
+
+/// 两个 33 位时间戳之间的差值，处理计数器回绕。
+fn timestamp_delta(first: u64, last: u64) -> u64 {
+    (last + TIMESTAMP_WRAP - first) % TIMESTAMP_WRAP
+}
+
+/// 记录每个 PID 第一次出现的 PCR 和 PTS。
+#[derive(Default)]
+struct Timestamps {
+    pcr: HashMap<u16, u64>,
+    pts: HashMap<u16, u64>,
+}
+
+/// 解析文件头尾两段数据，返回元数据。
+///
+/// 节目表和起始时间戳取自 head，结束时间戳取自 tail。
+/// 优先使用 PCR 计算时长，没有 PCR 时退回视频（或第一条）基本流的 PTS。
+fn parse_ts(head: &[u8], tail: &[u8], packet_size: usize) -> Result<VideoMetadata, String> {
+    let mut pat = SectionBuffer::default();
+    let mut pmt = SectionBuffer::default();
+    let mut pmt_pid = None;
+    let mut program = None;
+    let mut first = Timestamps::default();
+
+    for packet in packets(head, packet_size).filter_map(parse_packet) {
+        if let Some(pcr) = parse_pcr(packet.adaptation_field) {
+            first.pcr.entry(packet.pid).or_insert(pcr);
+        }
+        if packet.payload_unit_start {
+            if let Some(pts) = parse_pts(packet.payload) {
+                first.pts.entry(packet.pid).or_insert(pts);
+            }
+        }
+
+        if packet.pid == PAT_PID && pmt_pid.is_none() {
+            pmt_pid = pat.push(&packet).as_deref().and_then(parse_pat);
+        } else if Some(packet.pid) == pmt_pid && program.is_none() {
+            program = pmt.push(&packet);
+        }
+    }
+
+    let section = program.ok_or("Missing PMT in transport stream")?;
+    let (pcr_pid, streams) = parse_pmt(&section).ok_or("Invalid PMT in transport stream")?;
+    let tracks: Vec<TrackInfo> = streams.iter().map(stream_track).collect();
+
+    // 选择计算时长的时钟：PCR PID，其次是视频流或第一条流的 PTS
+    let pts_pid = streams.iter()
+        .zip(&tracks)
+        .find(|(_, track)| track.track_type == TrackType::Video)
+        .or_else(|| streams.iter().zip(&tracks).next())
+        .map(|(stream, _)| stream.pid);
+    let (clock_pid, use_pcr, start) = match (first.pcr.get(&pcr_pid), pts_pid.and_then(|pid| first.pts.get(&pid).map(|pts| (pid, pts)))) {
+        (Some(&pcr), _) => (pcr_pid, true, pcr),
+        (None, Some((pid, &pts))) => (pid, false, pts),
+        (None, None) => return Err("Missing PCR/PTS in transport stream".into()),
+    };
+
+    // PTS 在 B 帧存在时不单调，取尾部最大的偏移。
+    // 小文件的尾部与头部重叠，重排后的 PTS 可能略小于起点，
+    // 差值超过半个时钟周期时视为早于起点而不是回绕
+    let mut duration = 0u64;
+    for packet in packets(tail, packet_size).filter_map(parse_packet) {
+        if packet.pid != clock_pid {
+            continue;
+        }
+        let timestamp = if use_pcr {
+            parse_pcr(packet.adaptation_field)
+        } else if packet.payload_unit_start {
+            parse_pts(packet.payload)
+        } else {
+            None
+        };
+        if let Some(delta) = timestamp.map(|timestamp| timestamp_delta(start, timestamp)) {
+            if delta < TIMESTAMP_WRAP / 2 {
+                duration = duration.max(delta);
+            }
+        }
+    }
+
+    Ok(VideoMetadata {
+        container: if packet_size == M2TS_PACKET_SIZE { "m2ts" } else { "mpegts" }.to_string(),
+        video_duration_seconds: duration as f64 / CLOCK_RATE,
+        tracks,
+        ..Default::default()
+    })
+}
+
+/// 提取 MPEG-TS / M2TS 文件的元数据信息。
+/// 只读取文件开头和末尾各 4MB，不遍历整个文件。
+pub(super) fn get_ts_metadata(file_path: &str) -> Result<VideoMetadata, String> {
+    let mut file = File::open(file_path).map_err(|e| e.to_string())?;
+    let file_len = file.metadata().map_err(|e| e.to_string())?.len();
+
+    let mut head = Vec::new();
+    (&mut file).take(PROBE_SIZE).read_to_end(&mut head).map_err(|e| e.to_string())?;
+    let packet_size = detect_packet_size(&head).ok_or("Invalid MPEG-TS file")?;
+
+    let tail_start = file_len.saturating_sub(PROBE_SIZE);
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(tail_start)).map_err(|e| e.to_string())?;
+    file.take(PROBE_SIZE).read_to_end(&mut tail).map_err(|e| e.to_string())?;
+
+    parse_ts(&head, &tail, packet_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 构造一个 188 字节的 TS 包，不足部分用适配字段填充。
+    fn ts_packet(pid: u16, payload_unit_start: bool, pcr: Option<u64>, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![SYNC_BYTE, ((pid >> 8) as u8 & 0x1F) | if payload_unit_start { 0x40 } else { 0 }, pid as u8, 0x30];
+        let mut adaptation = vec![if pcr.is_some() { 0x10 } else { 0x00 }];
+        if let Some(pcr) = pcr {
+            adaptation.extend_from_slice(&[(pcr >> 25) as u8, (pcr >> 17) as u8, (pcr >> 9) as u8, (pcr >> 1) as u8, ((pcr & 1) << 7) as u8 | 0x7E, 0]);
+        }
+        adaptation.resize(TS_PACKET_SIZE - 5 - payload.len(), 0xFF);
+        packet.push(adaptation.len() as u8);
+        packet.extend(adaptation);
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    /// 构造 PSI 段（pointer_field + 段头 + 内容 + 4 字节 CRC 占位）。
+    fn section(table_id: u8, body: &[u8]) -> Vec<u8> {
+        let length = body.len() + 5 + 4;
+        let mut data = vec![0, table_id, 0xB0 | (length >> 8) as u8, length as u8, 0, 1, 0xC1, 0, 0];
+        data.extend_from_slice(body);
+        data.extend_from_slice(&[0; 4]);
+        data
+    }
+
+    fn stream(stream_type: u8, pid: u16, descriptors: &[u8]) -> Vec<u8> {
+        let mut data = vec![stream_type, 0xE0 | (pid >> 8) as u8, pid as u8, 0xF0, descriptors.len() as u8];
+        data.extend_from_slice(descriptors);
+        data
+    }
+
+    fn program_packets() -> Vec<u8> {
+        let pat = section(0x00, &[0, 1, 0xE1, 0x00]);
+        let mut pmt_body = vec![0xE1, 0x01, 0xF0, 0x00];
+        pmt_body.extend(stream(0x1B, 0x1011, &[]));
+        pmt_body.extend(stream(0x81, 0x1100, &[0x0A, 4, b'e', b'n', b'g', 0]));
+        pmt_body.extend(stream(0x06, 0x1101, &[0x6A, 1, 0, 0x0A, 4, b'j', b'p', b'n', 0]));
+        pmt_body.extend(stream(0x90, 0x1200, &[0x0A, 4, b'c', b'h', b'i', 0]));
+        [ts_packet(PAT_PID, true, None, &pat), ts_packet(0x100, true, None, &section(0x02, &pmt_body))].concat()
+    }
+
+    #[test]
+    fn test_parse_ts_program_and_pcr_duration() {
+        let mut head = program_packets();
+        head.extend(ts_packet(0x101, false, Some(TIMESTAMP_WRAP - 90_000), &[]));
+        let tail = [ts_packet(0x101, false, Some(50 * 90_000), &[]), ts_packet(0x101, false, Some(59 * 90_000), &[]), ts_packet(0x1FFF, false, None, &[])].concat();
+
+        let metadata = parse_ts(&head, &tail, TS_PACKET_SIZE).unwrap();
+        assert_eq!(metadata.container, "mpegts");
+        // PCR 在文件中途回绕
+        assert_eq!(metadata.video_duration_seconds, 60.0);
+
+        let summary: Vec<_> = metadata.tracks.iter().map(|t| (t.track_type, t.codec.as_str(), t.language.as_str())).collect();
+        assert_eq!(summary, vec![
+            (TrackType::Video, "H.264", "und"),
+            (TrackType::Audio, "AC3", "eng"),
+            (TrackType::Audio, "AC3", "jpn"),
+            (TrackType::Subtitle, "PGS", "chi"),
+        ]);
+    }
+
+    #[test]
+    fn test_m2ts_pts_fallback() {
+        let pes = |pts: u64| {
+            vec![0, 0, 1, 0xE0, 0, 0, 0x80, 0x80, 5,
+                 0x21 | ((pts >> 29) & 0x0E) as u8, (pts >> 22) as u8, 0x01 | (pts >> 14) as u8 & 0xFE, (pts >> 7) as u8, 0x01 | (pts << 1) as u8]
+        };
+        let m2ts = |packet: Vec<u8>| [vec![0; 4], packet].concat();
+
+        // PCR PID 上没有 PCR，改用视频流 PTS
+        let mut head: Vec<u8> = program_packets().chunks(TS_PACKET_SIZE).flat_map(|p| m2ts(p.to_vec())).collect();
+        head.extend(m2ts(ts_packet(0x1011, true, None, &pes(90_000))));
+        let padding = || m2ts(ts_packet(0x1FFF, false, None, &[]));
+        let tail = [padding(), m2ts(ts_packet(0x1011, true, None, &pes(90_000 * 121))), m2ts(ts_packet(0x1011, true, None, &pes(90_000 * 120))), padding()].concat();
+
+        assert!(is_ts_signature(&head));
+        // 尾部数据不从包边界开始
+        let metadata = parse_ts(&head, &tail[5..], M2TS_PACKET_SIZE).unwrap();
+        assert_eq!(metadata.container, "m2ts");
+        assert_eq!(metadata.video_duration_seconds, 120.0);
+    }
+
+    #[test]
+    fn test_reordered_pts_before_start() {
+        let pes = |pts: u64| {
+            vec![0, 0, 1, 0xE0, 0, 0, 0x80, 0x80, 5,
+                 0x21 | ((pts >> 29) & 0x0E) as u8, (pts >> 22) as u8, 0x01 | (pts >> 14) as u8 & 0xFE, (pts >> 7) as u8, 0x01 | (pts << 1) as u8]
+        };
+
+        // 起点是 I 帧，其后的 B 帧 PTS 更小；小文件的尾部包含这些包
+        let mut head = program_packets();
+        head.extend(ts_packet(0x1011, true, None, &pes(90_000 * 10)));
+        head.extend(ts_packet(0x1011, true, None, &pes(90_000 * 9)));
+        let tail = [head.clone(), ts_packet(0x1011, true, None, &pes(90_000 * 70)), ts_packet(0x1011, true, None, &pes(90_000 * 69))].concat();
+
+        let metadata = parse_ts(&head, &tail, TS_PACKET_SIZE).unwrap();
+        assert_eq!(metadata.video_duration_seconds, 60.0);
+    }
+
+    #[test]
+    fn test_garbage_does_not_panic() {
+        assert!(!is_ts_signature(&[SYNC_BYTE; 100]));
+        let mut data = vec![SYNC_BYTE; TS_PACKET_SIZE * 4];
+        data[TS_PACKET_SIZE + 4] = 0xFF;
+        assert!(parse_ts(&data, &data, TS_PACKET_SIZE).is_err());
+    }
+}
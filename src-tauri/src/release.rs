@@ -0,0 +1,394 @@
+//! 发布名称解析
+//!
+//! 把 `Movie.Name.2010.2160p.UHD.BluRay.x265.10bit.HDR.DTS-HD.MA.5.1-GROUP` 这样的文件名切分为词，
+//! 按标签表识别清晰度、片源、编码、音频、HDR、版本和发布组。
+//! 第一个标签之前的词为标题，其中最后一个年份为年份；剧集编号（如 `S01E02`）同样作为标题的结束。
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::db::VideoRelease;
+
+/// 标签类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TagKind {
+    Resolution,
+    Source,
+    VideoCodec,
+    Audio,
+    Hdr,
+    Edition,
+    Repack,
+    /// 位深、多音轨、流媒体平台等，不记录但作为标题的结束
+    Ignored,
+}
+
+/// 标签表：规范化后的词（小写、去掉 `-`、`'` 等符号）→ (类型, 显示名称)
+const TAGS: &[(&str, TagKind, &str)] = &[
+    ("2160p", TagKind::Resolution, "2160p"),
+    ("4k", TagKind::Resolution, "2160p"),
+    ("1440p", TagKind::Resolution, "1440p"),
+    ("1080p", TagKind::Resolution, "1080p"),
+    ("1080i", TagKind::Resolution, "1080p"),
+    ("720p", TagKind::Resolution, "720p"),
+    ("576p", TagKind::Resolution, "576p"),
+    ("576i", TagKind::Resolution, "576p"),
+    ("480p", TagKind::Resolution, "480p"),
+    ("480i", TagKind::Resolution, "480p"),
+    ("remux", TagKind::Source, "Remux"),
+    ("bdremux", TagKind::Source, "Remux"),
+    ("bluray", TagKind::Source, "BluRay"),
+    ("uhdbluray", TagKind::Source, "BluRay"),
+    ("bdrip", TagKind::Source, "BluRay"),
+    ("brrip", TagKind::Source, "BluRay"),
+    ("bd", TagKind::Source, "BluRay"),
+    ("webdl", TagKind::Source, "WEB-DL"),
+    ("web", TagKind::Source, "WEB-DL"),
+    ("webrip", TagKind::Source, "WEBRip"),
+    ("hdtv", TagKind::Source, "HDTV"),
+    ("pdtv", TagKind::Source, "HDTV"),
+    ("hdrip", TagKind::Source, "HDRip"),
+    ("dvd", TagKind::Source, "DVD"),
+    ("dvdrip", TagKind::Source, "DVD"),
+    ("dvd5", TagKind::Source, "DVD"),
+    ("dvd9", TagKind::Source, "DVD"),
+    ("dvdscr", TagKind::Source, "CAM"),
+    ("screener", TagKind::Source, "CAM"),
+    ("cam", TagKind::Source, "CAM"),
+    ("camrip", TagKind::Source, "CAM"),
+    ("hdcam", TagKind::Source, "CAM"),
+    ("hdts", TagKind::Source, "CAM"),
+    ("telesync", TagKind::Source, "CAM"),
+    ("telecine", TagKind::Source, "CAM"),
+    ("x264", TagKind::VideoCodec, "H.264"),
+    ("h264", TagKind::VideoCodec, "H.264"),
+    ("avc", TagKind::VideoCodec, "H.264"),
+    ("x265", TagKind::VideoCodec, "H.265"),
+    ("h265", TagKind::VideoCodec, "H.265"),
+    ("hevc", TagKind::VideoCodec, "H.265"),
+    ("av1", TagKind::VideoCodec, "AV1"),
+    ("vp9", TagKind::VideoCodec, "VP9"),
+    ("xvid", TagKind::VideoCodec, "XviD"),
+    ("divx", TagKind::VideoCodec, "DivX"),
+    ("mpeg2", TagKind::VideoCodec, "MPEG-2"),
+    ("vc1", TagKind::VideoCodec, "VC-1"),
+    ("aac", TagKind::Audio, "AAC"),
+    ("ac3", TagKind::Audio, "AC3"),
+    ("dd", TagKind::Audio, "AC3"),
+    ("eac3", TagKind::Audio, "E-AC3"),
+    ("ddp", TagKind::Audio, "E-AC3"),
+    ("dd+", TagKind::Audio, "E-AC3"),
+    ("dts", TagKind::Audio, "DTS"),
+    ("dtses", TagKind::Audio, "DTS"),
+    ("dtshd", TagKind::Audio, "DTS-HD"),
+    ("dtshdhra", TagKind::Audio, "DTS-HD"),
+    ("dtshdma", TagKind::Audio, "DTS-HD MA"),
+    ("dtsma", TagKind::Audio, "DTS-HD MA"),
+    ("dtsx", TagKind::Audio, "DTS:X"),
+    ("truehd", TagKind::Audio, "TrueHD"),
+    ("truehdatmos", TagKind::Audio, "TrueHD Atmos"),
+    ("atmos", TagKind::Audio, "Atmos"),
+    ("flac", TagKind::Audio, "FLAC"),
+    ("mp3", TagKind::Audio, "MP3"),
+    ("opus", TagKind::Audio, "Opus"),
+    ("lpcm", TagKind::Audio, "LPCM"),
+    ("pcm", TagKind::Audio, "LPCM"),
+    ("hdr", TagKind::Hdr, "HDR"),
+    ("hdr10", TagKind::Hdr, "HDR"),
+    ("hdr10+", TagKind::Hdr, "HDR"),
+    ("hdr10plus", TagKind::Hdr, "HDR"),
+    ("dv", TagKind::Hdr, "HDR"),
+    ("dovi", TagKind::Hdr, "HDR"),
+    ("dolbyvision", TagKind::Hdr, "HDR"),
+    ("hlg", TagKind::Hdr, "HDR"),
+    ("extended", TagKind::Edition, "Extended"),
+    ("extendedcut", TagKind::Edition, "Extended"),
+    ("extendededition", TagKind::Edition, "Extended"),
+    ("directorscut", TagKind::Edition, "Director's Cut"),
+    ("theatrical", TagKind::Edition, "Theatrical"),
+    ("theatricalcut", TagKind::Edition, "Theatrical"),
+    ("unrated", TagKind::Edition, "Unrated"),
+    ("uncut", TagKind::Edition, "Uncut"),
+    ("remastered", TagKind::Edition, "Remastered"),
+    ("imax", TagKind::Edition, "IMAX"),
+    ("criterion", TagKind::Edition, "Criterion"),
+    ("specialedition", TagKind::Edition, "Special Edition"),
+    ("ultimateedition", TagKind::Edition, "Ultimate Edition"),
+    ("ultimatecut", TagKind::Edition, "Ultimate Edition"),
+    ("collectorsedition", TagKind::Edition, "Collector's Edition"),
+    ("anniversaryedition", TagKind::Edition, "Anniversary Edition"),
+    ("repack", TagKind::Repack, "REPACK"),
+    ("repack2", TagKind::Repack, "REPACK"),
+    ("proper", TagKind::Repack, "PROPER"),
+    ("rerip", TagKind::Repack, "REPACK"),
+    ("8bit", TagKind::Ignored, ""),
+    ("10bit", TagKind::Ignored, ""),
+    ("12bit", TagKind::Ignored, ""),
+    ("hi10p", TagKind::Ignored, ""),
+    ("sdr", TagKind::Ignored, ""),
+    ("uhd", TagKind::Ignored, ""),
+    ("3d", TagKind::Ignored, ""),
+    ("multi", TagKind::Ignored, ""),
+    ("dual", TagKind::Ignored, ""),
+    ("dualaudio", TagKind::Ignored, ""),
+    ("subbed", TagKind::Ignored, ""),
+    ("dubbed", TagKind::Ignored, ""),
+    ("internal", TagKind::Ignored, ""),
+    ("limited", TagKind::Ignored, ""),
+    ("amzn", TagKind::Ignored, ""),
+    ("nf", TagKind::Ignored, ""),
+    ("dsnp", TagKind::Ignored, ""),
+    ("hmax", TagKind::Ignored, ""),
+    ("atvp", TagKind::Ignored, ""),
+    ("itunes", TagKind::Ignored, ""),
+    ("ma10p", TagKind::Ignored, ""),
+];
+
+/// 也常出现在标题中的标签，如 `Charlotte's.Web.2006`，只在年份或其他标签之后识别
+const WEAK_TAGS: &[&str] = &[
+    "web", "cam", "bd", "dd", "dv", "avc", "atmos", "opus", "uhd", "sdr", "3d", "dual", "multi", "limited",
+    "internal", "proper", "extended", "theatrical", "unrated", "uncut", "remastered", "imax", "criterion",
+];
+
+/// 一个标签最多由几个词组成，如 `DTS-HD.MA.5.1`
+const MAX_TAG_TOKENS: usize = 4;
+
+/// 切分文件名的分隔符；`-` 不切分，`WEB-DL`、`x264-GROUP` 保持为一个词
+static TOKEN_SEPARATOR: Lazy<Regex> = Lazy::new(|| Regex::new(r"[\s._()\[\]{},]+").unwrap());
+
+/// 剧集编号，如 `S01E02`、`S01`、`1x05`、`E12`
+static EPISODE_TOKEN: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^(s\d{1,2}(e\d{1,4})*|\d{1,2}x\d{2,3}|ep?\d{1,4})$").unwrap());
+
+/// 年份
+static YEAR_TOKEN: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(19|20)\d{2}$").unwrap());
+
+/// 声道数，如 `5.1` 切分后的 `5`、`1`，以及 `2ch`、`6ch`
+static CHANNEL_TOKEN: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^(\d|\dch)$").unwrap());
+
+/// 解析发布名称，`name` 不包含扩展名。返回的 `video_id` 为空
+pub(crate) fn parse_release_name(name: &str) -> VideoRelease {
+    let mut release = VideoRelease::default();
+    let mut name = name.trim();
+
+    // 动漫字幕组的命名以 `[字幕组]` 开头
+    if let Some((group, rest)) = name.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+        release.release_group = group.trim().to_string();
+        name = rest.trim();
+    }
+    // 结尾的 `[rarbg]` 之类的站点标记不属于发布名称
+    while let Some(start) = name.rfind('[').filter(|_| name.ends_with(']')) {
+        if TOKEN_SEPARATOR.split(&name[start + 1..name.len() - 1]).any(|token| tag(&normalize(token)).is_some()) {
+            break;
+        }
+        name = name[..start].trim_end();
+    }
+
+    let mut tokens: Vec<&str> = TOKEN_SEPARATOR.split(name).filter(|token| !token.is_empty()).collect();
+
+    // 结尾的 `-GROUP` 为发布组，只有前面是标签时才成立，避免把 `Spider-Man` 当作发布组
+    let mut trailing_group = None;
+    if let Some(last) = tokens.last().copied() {
+        if let Some((head, group)) = last.rsplit_once('-') {
+            if tag(&normalize(last)).is_none() && !head.is_empty() && !group.is_empty() {
+                let len = tokens.len();
+                tokens[len - 1] = head;
+                trailing_group = Some((last, group));
+            }
+        }
+    }
+
+    let mut title_end = tokens.len();
+    let mut tag_start = None;
+    let mut year_seen = false;
+    let mut last_tag_end = 0;
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = tokens[index];
+        year_seen |= index > 0 && YEAR_TOKEN.is_match(token);
+        // 标题至少保留一个词，如 `1917`、`Cam`
+        if index > 0 && (EPISODE_TOKEN.is_match(token) || (token == "-" && is_number(tokens.get(index + 1)))) {
+            title_end = title_end.min(index);
+            index += 1;
+            continue;
+        }
+        if token == "-" {
+            index += 1;
+            continue;
+        }
+        let strong = tag_start.is_some() || year_seen;
+        let matched = (1..=MAX_TAG_TOKENS.min(tokens.len() - index)).rev().find_map(|count| {
+            let key: String = tokens[index..index + count].iter().map(|token| normalize(token)).collect();
+            if !strong && WEAK_TAGS.contains(&key.as_str()) {
+                return None;
+            }
+            tag_with_channels(&key).map(|found| (count, found))
+        });
+        let Some((count, (kind, value))) = matched.filter(|_| index > 0) else {
+            index += 1;
+            continue;
+        };
+        title_end = title_end.min(index);
+        tag_start.get_or_insert(index);
+        apply_tag(&mut release, kind, value);
+        index += count;
+        // 音频后的声道数
+        if kind == TagKind::Audio {
+            while tokens.get(index).is_some_and(|token| CHANNEL_TOKEN.is_match(token)) {
+                index += 1;
+            }
+        }
+        last_tag_end = index;
+    }
+
+    if let Some((original, group)) = trailing_group {
+        if tag_start.is_some() && last_tag_end == tokens.len() {
+            release.release_group = group.to_string();
+        } else if let Some(last) = tokens.last_mut() {
+            // 不是发布组，恢复原来的词
+            *last = original;
+        }
+    }
+
+    // 标题中最后一个年份之后的内容不属于标题，开头的年份是标题的一部分，如 `1917`、`2012`
+    let title_tokens: Vec<&str> = tokens[..title_end].iter().copied().filter(|token| *token != "-").collect();
+    let year_index = title_tokens.iter().rposition(|token| YEAR_TOKEN.is_match(token)).filter(|&index| index > 0);
+    if let Some(year_index) = year_index {
+        release.year = title_tokens[year_index].to_string();
+    }
+    release.title = title_tokens[..year_index.unwrap_or(title_tokens.len())].join(" ");
+    release
+}
+
+/// 为视频生成发布信息，`name` 为不含扩展名的文件名或光盘文件夹名
+pub(crate) fn build_video_release(video_id: &str, name: &str) -> VideoRelease {
+    VideoRelease { video_id: video_id.to_string(), ..parse_release_name(name) }
+}
+
+fn apply_tag(release: &mut VideoRelease, kind: TagKind, value: &str) {
+    let field = match kind {
+        TagKind::Resolution => &mut release.resolution,
+        // Remux 总是来自蓝光，同时标注时以 Remux 为准
+        TagKind::Source if value == "Remux" => &mut release.source,
+        TagKind::Source => {
+            if release.source.is_empty() {
+                release.source = value.to_string();
+            }
+            return;
+        }
+        TagKind::VideoCodec => &mut release.video_codec,
+        TagKind::Audio if value == "Atmos" && !release.audio_codec.is_empty() => {
+            if !release.audio_codec.ends_with("Atmos") {
+                release.audio_codec.push_str(" Atmos");
+            }
+            return;
+        }
+        TagKind::Audio => &mut release.audio_codec,
+        TagKind::Edition => &mut release.edition,
+        TagKind::Hdr => {
+            release.hdr = true;
+            return;
+        }
+        TagKind::Repack => {
+            release.repack = true;
+            return;
+        }
+        TagKind::Ignored => return,
+    };
+    // 同一类型出现多次时保留第一个，如多音轨只记录主音轨
+    if field.is_empty() || kind == TagKind::Source {
+        *field = value.to_string();
+    }
+}
+
+/// 查找标签
+fn tag(key: &str) -> Option<(TagKind, &'static str)> {
+    TAGS.iter().find(|(name, _, _)| *name == key).map(|(_, kind, value)| (*kind, *value))
+}
+
+/// 查找标签，音频标签允许带声道数，如 `DDP5.1`、`AAC2.0`、`TrueHD.7.1`
+fn tag_with_channels(key: &str) -> Option<(TagKind, &'static str)> {
+    tag(key).or_else(|| {
+        let digits = key.len() - key.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+        if !(1..=2).contains(&digits) {
+            return None;
+        }
+        tag(&key[..key.len() - digits]).filter(|(kind, _)| *kind == TagKind::Audio)
+    })
+}
+
+/// 规范化词：小写并去掉 `-`、`'`、`:` 等符号，`WEB-DL` → `webdl`，`Director's` → `directors`
+fn normalize(token: &str) -> String {
+    token.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '+')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn is_number(token: Option<&&str>) -> bool {
+    token.is_some_and(|token| !token.is_empty() && token.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (文件名, 标题, 年份, 清晰度, 片源, 视频编码, 音频编码, HDR, 版本, 发布组, REPACK)
+    type Case = (&'static str, &'static str, &'static str, &'static str, &'static str, &'static str, &'static str, bool, &'static str, &'static str, bool);
+
+    const CASES: &[Case] = &[
+        ("The.Matrix.1999.1080p.BluRay.x264-SPARKS", "The Matrix", "1999", "1080p", "BluRay", "H.264", "", false, "", "SPARKS", false),
+        ("Dune.Part.Two.2024.2160p.UHD.BluRay.REMUX.DV.HDR.HEVC.TrueHD.Atmos.7.1-FGT", "Dune Part Two", "2024", "2160p", "Remux", "H.265", "TrueHD Atmos", true, "", "FGT", false),
+        ("Blade.Runner.2049.2017.2160p.UHD.BluRay.x265.10bit.HDR.DTS-HD.MA.5.1-SWTYBLZ", "Blade Runner 2049", "2017", "2160p", "BluRay", "H.265", "DTS-HD MA", true, "", "SWTYBLZ", false),
+        ("Oppenheimer.2023.IMAX.2160p.WEB-DL.DDP5.1.Atmos.H.265-FLUX", "Oppenheimer", "2023", "2160p", "WEB-DL", "H.265", "E-AC3 Atmos", false, "IMAX", "FLUX", false),
+        ("The.Lord.of.the.Rings.The.Fellowship.of.the.Ring.2001.EXTENDED.1080p.BluRay.x264.DTS-ES-GROUP", "The Lord of the Rings The Fellowship of the Ring", "2001", "1080p", "BluRay", "H.264", "DTS", false, "Extended", "GROUP", false),
+        ("Apocalypse.Now.1979.Final.Cut.REMASTERED.1080p.BluRay.x265.AAC2.0", "Apocalypse Now", "1979", "1080p", "BluRay", "H.265", "AAC", false, "Remastered", "", false),
+        ("Blade Runner (1982) Director's Cut 1080p BluRay FLAC", "Blade Runner", "1982", "1080p", "BluRay", "", "FLAC", false, "Director's Cut", "", false),
+        ("1917.2019.1080p.WEBRip.x264.AAC5.1-RARBG", "1917", "2019", "1080p", "WEBRip", "H.264", "AAC", false, "", "RARBG", false),
+        ("2001.A.Space.Odyssey.1968.720p.BRRip.XviD.AC3", "2001 A Space Odyssey", "1968", "720p", "BluRay", "XviD", "AC3", false, "", "", false),
+        ("1917.1080p", "1917", "", "1080p", "", "", "", false, "", "", false),
+        ("Spider-Man.Into.the.Spider-Verse.2018.PROPER.1080p.WEB-DL.DD5.1.H264-NTG", "Spider-Man Into the Spider-Verse", "2018", "1080p", "WEB-DL", "H.264", "AC3", false, "", "NTG", true),
+        ("Mission Impossible - Fallout (2018) [1080p] [x265] [10bit]", "Mission Impossible Fallout", "2018", "1080p", "", "H.265", "", false, "", "", false),
+        ("Inception (2010) [2160p] [4K] [BluRay] [HDR10] [rarbg]", "Inception", "2010", "2160p", "BluRay", "", "", true, "", "", false),
+        ("Some-Movie", "Some-Movie", "", "", "", "", "", false, "", "", false),
+        ("Movie.Title.2015.REPACK.720p.HDTV.x264-KILLERS", "Movie Title", "2015", "720p", "HDTV", "H.264", "", false, "", "KILLERS", true),
+        ("The.Office.US.S05E14.720p.HDTV.x264-CTU", "The Office US", "", "720p", "HDTV", "H.264", "", false, "", "CTU", false),
+        ("Breaking.Bad.S01E01.Pilot.1080p.AMZN.WEB-DL.DDP5.1.H.264-NTb", "Breaking Bad", "", "1080p", "WEB-DL", "H.264", "E-AC3", false, "", "NTb", false),
+        ("[SubsPlease] Sousou no Frieren - 05 (1080p) [ABCD1234]", "Sousou no Frieren", "", "1080p", "", "", "", false, "", "SubsPlease", false),
+        ("[VCB-Studio] Fate Zero [Ma10p_1080p]", "Fate Zero", "", "1080p", "", "", "", false, "", "VCB-Studio", false),
+        ("Avatar.The.Way.of.Water.2022.HDR10+.2160p.DSNP.WEBRip.Opus.AV1", "Avatar The Way of Water", "2022", "2160p", "WEBRip", "AV1", "Opus", true, "", "", false),
+        ("Movie.2008.UNRATED.DVDRip.XviD-DiAMOND", "Movie", "2008", "", "DVD", "XviD", "", false, "Unrated", "DiAMOND", false),
+        ("Movie.2023.HDCAM.x264-SUNSCREEN", "Movie", "2023", "", "CAM", "H.264", "", false, "", "SUNSCREEN", false),
+        ("电影名称.2019.1080p.国语中字", "电影名称", "2019", "1080p", "", "", "", false, "", "", false),
+        ("Charlotte's.Web.2006.720p.BluRay", "Charlotte's Web", "2006", "720p", "BluRay", "", "", false, "", "", false),
+        ("The.Extended.Family.2021.1080p.WEB.H264-GROUP", "The Extended Family", "2021", "1080p", "WEB-DL", "H.264", "", false, "", "GROUP", false),
+        ("Plain Title", "Plain Title", "", "", "", "", "", false, "", "", false),
+    ];
+
+    #[test]
+    fn test_parse_release_name() {
+        for &(name, title, year, resolution, source, video_codec, audio_codec, hdr, edition, release_group, repack) in CASES {
+            let expected = VideoRelease {
+                video_id: String::new(),
+                title: title.to_string(),
+                year: year.to_string(),
+                resolution: resolution.to_string(),
+                source: source.to_string(),
+                video_codec: video_codec.to_string(),
+                audio_codec: audio_codec.to_string(),
+                hdr,
+                edition: edition.to_string(),
+                release_group: release_group.to_string(),
+                repack,
+            };
+            assert_eq!(parse_release_name(name), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_build_video_release() {
+        let release = build_video_release("abc", "Movie.2010.1080p.BluRay");
+        assert_eq!(release.video_id, "abc");
+        assert_eq!(release.title, "Movie");
+        assert_eq!(release.source, "BluRay");
+    }
+}
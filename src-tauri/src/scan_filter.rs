@@ -0,0 +1,369 @@
+//! 扫描规则过滤
+//!
+//! 遍历目录时按资料库的扫描规则判断，被排除的目录不会进入，被排除的文件不会被读取和解析。
+
+use std::{fs, path::{Path, PathBuf}};
+
+use regex::Regex;
+use walkdir::WalkDir;
+
+use crate::{db::ScanRules, disc};
+
+/// 目录中存在这些文件时，整个目录（包括子目录）不扫描
+pub(crate) const IGNORE_MARKERS: [&str; 2] = [".ignore", ".nomedia"];
+
+/// 编译后的扫描规则，模式相对根目录匹配，路径分隔符统一为 `/`
+pub(crate) struct ScanFilter {
+    root: PathBuf,
+    rules: ScanRules,
+    extensions: Vec<String>,
+    includes: Vec<Regex>,
+    excludes: Vec<Regex>,
+}
+
+impl ScanFilter {
+    /// 编译扫描规则
+    ///
+    /// # 参数
+    /// * `root` - 规则的根目录，通常为资料库根目录
+    /// * `rules` - 扫描规则
+    /// * `extensions` - 视频扩展名（小写，不含点）
+    ///
+    /// # 返回
+    /// * `Result<ScanFilter, String>` - 成功返回过滤器，模式无效时返回错误
+    pub fn new(root: &Path, rules: &ScanRules, extensions: &[String]) -> Result<Self, String> {
+        let compile = |patterns: &[String]| patterns.iter().map(|pattern| compile_pattern(pattern)).collect::<Result<Vec<_>, _>>();
+        Ok(ScanFilter {
+            root: root.to_path_buf(),
+            rules: rules.clone(),
+            extensions: extensions.to_vec(),
+            includes: compile(&rules.include_patterns)?,
+            excludes: compile(&rules.exclude_patterns)?,
+        })
+    }
+
+    /// 遍历目录，返回符合规则的视频文件和光盘文件夹
+    ///
+    /// `dir` 为根目录或其子目录，被排除的目录在遍历时直接跳过，不会读取其中的内容。
+    /// 光盘文件夹作为一个整体返回，不再进入其中查找 `.m2ts`、`.VOB` 等片段。
+    pub fn walk<'a>(&'a self, dir: &Path) -> impl Iterator<Item = PathBuf> + 'a {
+        let mut entries = WalkDir::new(dir)
+            .follow_links(self.rules.follow_symlinks)
+            .into_iter();
+        std::iter::from_fn(move || loop {
+            let entry = match entries.next()? {
+                Ok(entry) => entry,
+                Err(_) => continue,
+            };
+            let path = entry.path();
+            if !entry.file_type().is_dir() {
+                if self.accepts_file(path) {
+                    return Some(entry.into_path());
+                }
+            } else if disc::folder_kind(path).is_some() {
+                entries.skip_current_dir();
+                if self.accepts_disc(path) {
+                    return Some(entry.into_path());
+                }
+            } else if entry.depth() > 0 && !self.accepts_dir(path) {
+                entries.skip_current_dir();
+            }
+        })
+    }
+
+    /// 判断单个路径是否符合规则，用于监听到的文件变化
+    ///
+    /// 除路径本身外，根目录与路径之间的每一级目录也需要符合规则。
+    pub fn accepts_path(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let mut components: Vec<_> = relative.components().collect();
+        if components.pop().is_none() {
+            // 根目录本身
+            return true;
+        }
+        let mut dir = self.root.clone();
+        for component in components {
+            dir.push(component);
+            if !self.accepts_dir(&dir) {
+                return false;
+            }
+        }
+        if !path.is_dir() {
+            self.accepts_file(path)
+        } else if disc::folder_kind(path).is_some() {
+            self.accepts_disc(path)
+        } else {
+            self.accepts_dir(path)
+        }
+    }
+
+    /// 判断是否进入目录
+    fn accepts_dir(&self, dir: &Path) -> bool {
+        let relative = self.relative(dir);
+        if self.rules.max_depth > 0 && depth(&relative) >= self.rules.max_depth as usize {
+            return false;
+        }
+        if self.rules.skip_hidden && is_hidden(dir) {
+            return false;
+        }
+        if IGNORE_MARKERS.iter().any(|marker| dir.join(marker).exists()) {
+            return false;
+        }
+        // 以 `/` 结尾，`**/Extras/**` 这样的模式也能匹配目录本身
+        let relative = format!("{}/", relative);
+        !self.excludes.iter().any(|re| re.is_match(&relative))
+    }
+
+    /// 判断文件是否需要入库，只读取文件大小，不读取内容
+    fn accepts_file(&self, path: &Path) -> bool {
+        let is_video = path.extension()
+            .is_some_and(|ext| self.extensions.contains(&ext.to_string_lossy().to_lowercase()));
+        // 符号链接按指向的文件计算大小
+        is_video
+            && self.accepts_entry(path)
+            && (self.rules.min_file_size == 0
+                || fs::metadata(path).is_ok_and(|metadata| metadata.len() >= self.rules.min_file_size))
+    }
+
+    /// 判断光盘文件夹是否需要入库，层级按视频文件计算，其他规则与目录相同
+    fn accepts_disc(&self, dir: &Path) -> bool {
+        let relative = format!("{}/", self.relative(dir));
+        self.accepts_entry(dir)
+            && !IGNORE_MARKERS.iter().any(|marker| dir.join(marker).exists())
+            && !self.excludes.iter().any(|re| re.is_match(&relative))
+    }
+
+    /// 判断视频文件或光盘文件夹是否符合隐藏、样片、层级和模式规则
+    fn accepts_entry(&self, path: &Path) -> bool {
+        if self.rules.skip_hidden && is_hidden(path) {
+            return false;
+        }
+        let is_sample = path.file_name()
+            .is_some_and(|name| name.to_string_lossy().to_ascii_lowercase().contains("sample"));
+        if self.rules.skip_samples && is_sample {
+            return false;
+        }
+
+        let relative = self.relative(path);
+        if self.rules.max_depth > 0 && depth(&relative) > self.rules.max_depth as usize {
+            return false;
+        }
+        if !self.includes.is_empty() && !self.includes.iter().any(|re| re.is_match(&relative)) {
+            return false;
+        }
+        !self.excludes.iter().any(|re| re.is_match(&relative))
+    }
+
+    /// 相对根目录的路径，分隔符统一为 `/`
+    fn relative(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        relative.components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// 相对路径的层级数
+fn depth(relative: &str) -> usize {
+    relative.split('/').filter(|part| !part.is_empty()).count()
+}
+
+/// 名称以 `.` 开头的目录或文件
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// 编译规则中的模式，不区分大小写
+///
+/// `re:` 开头的为正则表达式，在相对路径中查找；其余为 glob：
+/// 不含 `/` 的匹配任意一级目录或文件的名称，如 `*.partial.mkv`、`Extras`；
+/// 含 `/` 的从根目录开始匹配，`**` 可匹配多级目录，如 `**/Extras/**`。
+/// 匹配到的目录中的所有文件都会被匹配。
+///
+/// # 参数
+/// * `pattern` - 模式
+///
+/// # 返回
+/// * `Result<Regex, String>` - 成功返回正则表达式，模式无效时返回错误
+pub(crate) fn compile_pattern(pattern: &str) -> Result<Regex, String> {
+    let pattern = pattern.trim();
+    if let Some(regex) = pattern.strip_prefix("re:") {
+        return Regex::new(&format!("(?i){}", regex)).map_err(|e| format!("无效的正则表达式 {}：{}", pattern, e));
+    }
+
+    let glob = pattern.replace('\\', "/");
+    let glob = glob.trim_matches('/');
+    if glob.is_empty() {
+        return Err(format!("无效的模式：{}", pattern));
+    }
+    let body = glob_to_regex(glob);
+    let regex = if glob.contains('/') {
+        format!("(?i)^{}(?:/|$)", body)
+    } else {
+        format!("(?i)(?:^|/){}(?:/|$)", body)
+    };
+    Regex::new(&regex).map_err(|e| format!("无效的模式 {}：{}", pattern, e))
+}
+
+/// 将 glob 转换为正则表达式：`**/` 匹配零或多级目录，`**` 匹配任意字符，`*`、`?` 不跨越目录
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    regex.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    regex.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_compile_pattern() {
+        let cases = [
+            ("*.partial.mkv", "Movie/Movie.partial.mkv", true),
+            ("*.partial.mkv", "Movie.PARTIAL.MKV", true),
+            ("*.partial.mkv", "Movie.mkv", false),
+            ("Extras", "Movie/Extras/", true),
+            ("Extras", "Movie/Extras/Interview.mkv", true),
+            ("Extras", "Movie/Extras Edition.mkv", false),
+            ("**/Extras/**", "Extras/", true),
+            ("**/Extras/**", "Movie (2020)/Extras/Interview.mkv", true),
+            ("**/Extras/**", "Movie (2020)/Movie.mkv", false),
+            ("Movies/*.mkv", "Movies/A.mkv", true),
+            ("Movies/*.mkv", "Movies/Sub/A.mkv", false),
+            ("Movies/*.mkv", "Other/Movies/A.mkv", false),
+            ("/Trash/", "Trash/A.mkv", true),
+            ("re:\\bS\\d{2}E\\d{2}\\b", "Show/Show.S01E02.mkv", true),
+            ("re:^tmp/", "Show/tmp/A.mkv", false),
+        ];
+        for (pattern, path, expected) in cases {
+            let regex = compile_pattern(pattern).unwrap();
+            assert_eq!(regex.is_match(path), expected, "{} ~ {}", pattern, path);
+        }
+
+        assert!(compile_pattern("re:(").is_err());
+        assert!(compile_pattern(" / ").is_err());
+    }
+
+    /// 按规则遍历临时目录，返回排序后的相对路径
+    fn walk(dir: &TempDir, rules: &ScanRules) -> Vec<String> {
+        let filter = ScanFilter::new(&dir.0, rules, &["mkv".to_string()]).unwrap();
+        let mut files: Vec<_> = filter.walk(&dir.0).map(|path| filter.relative(&path)).collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_walk_rules() {
+        let dir = TempDir::new("walk_rules", &[
+            ("Movie.mkv", vec![0u8; 100]),
+            ("Movie-sample.mkv", vec![0u8; 100]),
+            ("Small.mkv", vec![0u8; 10]),
+            ("notes.txt", vec![0u8; 100]),
+            (".hidden/Hidden.mkv", vec![0u8; 100]),
+            ("Show/Season 1/Show.S01E01.mkv", vec![0u8; 100]),
+            ("Show/Extras/Interview.mkv", vec![0u8; 100]),
+            ("Ignored/.ignore", Vec::new()),
+            ("Ignored/Ignored.mkv", vec![0u8; 100]),
+            ("Downloads/Movie.partial.mkv", vec![0u8; 100]),
+        ]);
+
+        assert_eq!(walk(&dir, &ScanRules::default()), [
+            "Downloads/Movie.partial.mkv",
+            "Movie.mkv",
+            "Show/Extras/Interview.mkv",
+            "Show/Season 1/Show.S01E01.mkv",
+            "Small.mkv",
+        ]);
+
+        let rules = ScanRules {
+            exclude_patterns: vec!["**/Extras/**".to_string(), "*.partial.mkv".to_string()],
+            min_file_size: 50,
+            skip_hidden: false,
+            skip_samples: false,
+            ..Default::default()
+        };
+        assert_eq!(walk(&dir, &rules), [
+            ".hidden/Hidden.mkv",
+            "Movie-sample.mkv",
+            "Movie.mkv",
+            "Show/Season 1/Show.S01E01.mkv",
+        ]);
+
+        let rules = ScanRules { max_depth: 2, ..Default::default() };
+        assert_eq!(walk(&dir, &rules), ["Downloads/Movie.partial.mkv", "Movie.mkv", "Small.mkv"]);
+
+        let rules = ScanRules { include_patterns: vec!["Show/**".to_string()], ..Default::default() };
+        assert_eq!(walk(&dir, &rules), ["Show/Extras/Interview.mkv", "Show/Season 1/Show.S01E01.mkv"]);
+    }
+
+    #[test]
+    fn test_accepts_path() {
+        let dir = TempDir::new("accepts_path", &[
+            ("Movie.mkv", vec![0u8; 100]),
+            ("Ignored/.ignore", Vec::new()),
+            ("Ignored/Sub/Movie.mkv", vec![0u8; 100]),
+            (".hidden/Movie.mkv", vec![0u8; 100]),
+            ("Show/Extras/Interview.mkv", vec![0u8; 100]),
+        ]);
+        let rules = ScanRules { exclude_patterns: vec!["Extras".to_string()], ..Default::default() };
+        let filter = ScanFilter::new(&dir.0, &rules, &["mkv".to_string()]).unwrap();
+
+        assert!(filter.accepts_path(&dir.0));
+        assert!(filter.accepts_path(&dir.0.join("Movie.mkv")));
+        assert!(filter.accepts_path(&dir.0.join("Show")));
+        assert!(!filter.accepts_path(&dir.0.join("Ignored/Sub/Movie.mkv")));
+        assert!(!filter.accepts_path(&dir.0.join(".hidden/Movie.mkv")));
+        assert!(!filter.accepts_path(&dir.0.join("Show/Extras")));
+        assert!(!filter.accepts_path(&dir.0.join("Show/Extras/Interview.mkv")));
+        assert!(!filter.accepts_path(Path::new("/elsewhere/Movie.mkv")));
+    }
+
+    #[test]
+    fn test_walk_disc_folders() {
+        let dir = TempDir::new("walk_discs", &[
+            ("Movie.mkv", vec![0u8; 100]),
+            ("Blu-ray (2010)/BDMV/index.bdmv", vec![0u8; 10]),
+            ("Blu-ray (2010)/BDMV/STREAM/00001.m2ts", vec![0u8; 100]),
+            ("Collection/DVD/VIDEO_TS/VIDEO_TS.IFO", vec![0u8; 10]),
+            ("Collection/DVD/VIDEO_TS/VTS_01_1.VOB", vec![0u8; 100]),
+            ("Broken/BDMV/STREAM/00001.m2ts", vec![0u8; 100]),
+        ]);
+        let extensions = ["mkv".to_string(), "m2ts".to_string(), "vob".to_string()];
+        let filter = ScanFilter::new(&dir.0, &ScanRules::default(), &extensions).unwrap();
+        let mut files: Vec<_> = filter.walk(&dir.0).map(|path| filter.relative(&path)).collect();
+        files.sort();
+        assert_eq!(files, ["Blu-ray (2010)", "Broken/BDMV/STREAM/00001.m2ts", "Collection/DVD", "Movie.mkv"]);
+
+        // 光盘文件夹本身发生变化时作为一个整体返回
+        let disc = dir.0.join("Blu-ray (2010)");
+        assert_eq!(filter.walk(&disc).collect::<Vec<_>>(), std::slice::from_ref(&disc));
+        assert!(filter.accepts_path(&disc));
+
+        let rules = ScanRules { exclude_patterns: vec!["Collection".to_string()], ..Default::default() };
+        let filter = ScanFilter::new(&dir.0, &rules, &extensions).unwrap();
+        assert!(!filter.accepts_path(&dir.0.join("Collection/DVD")));
+    }
+}
@@ -0,0 +1,71 @@
+//! 测试共用的工具：内存数据库和测试视频
+
+use std::{fs, path::PathBuf};
+
+use rusqlite::Connection;
+
+use crate::db::{self, VideoInfo};
+
+/// 创建已建好所有表的内存数据库
+pub(crate) fn memory_db() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    db::create_tables(&conn).unwrap();
+    conn
+}
+
+/// 创建测试视频，标题取文件名，其他字段为空
+pub(crate) fn test_video(id: &str, path: &str) -> VideoInfo {
+    let title = std::path::Path::new(path).file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().to_string());
+    VideoInfo {
+        id: id.to_string(),
+        original_title: title.clone(),
+        title,
+        thumbnail: String::new(),
+        duration: String::new(),
+        path: path.to_string(),
+        category: String::new(),
+        description: String::new(),
+        create_time: 0,
+        last_play_time: 0,
+        play_count: 0,
+        favorite: false,
+        tags: String::new(),
+        is_series: false,
+        season: 0,
+        episode: 0,
+        episode_title: String::new(),
+        episode_overview: String::new(),
+        file_size: 0,
+        mtime: 0,
+        fingerprint: String::new(),
+        status: db::STATUS_OK.to_string(),
+        library_id: 0,
+        part_of: String::new(),
+        part_number: 0,
+        episode_end: 0,
+        air_date: String::new(),
+        parts: Vec::new(),
+    }
+}
+
+/// 临时目录，离开作用域时删除
+pub(crate) struct TempDir(pub PathBuf);
+
+impl TempDir {
+    /// 在系统临时目录下创建目录并写入文件，文件路径相对于该目录
+    pub(crate) fn new(name: &str, files: &[(&str, Vec<u8>)]) -> Self {
+        let root = std::env::temp_dir().join(format!("local_video_{}_{}", std::process::id(), name));
+        for (file, data) in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, data).unwrap();
+        }
+        TempDir(root)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
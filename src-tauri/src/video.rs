@@ -1,7 +1,8 @@
 // Module: video
 use std::path::{Path, PathBuf};
-use crate::db::VideoInfo;
-use crate::{api, metadata};
+use crate::db::{VideoChapter, VideoInfo, VideoPart, VideoQuality, VideoTrack, STATUS_MISSING, STATUS_OFFLINE, STATUS_OK};
+use crate::{api, disc, metadata, release};
+use crate::metadata::VideoMetadata;
 use crate::{ log_debug, log_error, log_info };
 use regex::Regex;
 use once_cell::sync::Lazy;
@@ -9,8 +10,12 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 use serde_json::Value;
 use std::{fs, thread};
+use std::io::{Read, Seek, SeekFrom};
 use std::time::Duration;
 
+/// 资料库未指定语言时，获取 TMDb 信息使用的语言
+pub(crate) const DEFAULT_TMDB_LANGUAGE: &str = "zh-CN";
+
 static TV_SHOW_CACHE: Lazy<Mutex<HashMap<String, Value>>> = Lazy::new(|| {
     let cache = Mutex::new(HashMap::new());
     start_cache_cleaner();
@@ -32,31 +37,197 @@ fn clean_cache() {
     log_info!("TV_SHOW_CACHE has been cleared.");
 }
 
-/// 获取视频时长
+/// 读取视频元数据，根据文件签名自动选择解析器
 /// 
 /// # 参数
 /// * `path` - 视频路径
 /// # 返回
-/// * `Result<String, String>` - 成功返回过滤后的视频时长，失败返回错误信息
-pub(crate) fn get_duration(path: &str) -> Result<String, String> {
-    log_debug!("Getting video duration for: {}", path);
-    let duration = match metadata::mkv_metadata(path) {
+/// * `Option<VideoMetadata>` - 解析失败时记录日志并返回 None
+pub(crate) fn probe_video(path: &str) -> Option<VideoMetadata> {
+    log_debug!("Probing video metadata for: {}", path);
+    match metadata::video_metadata(path) {
         Ok(metadata) => {
-            println!("metadata: {:?}", metadata);
-            metadata.video_duration_seconds
+            log_debug!("metadata: {:?}", metadata);
+            Some(metadata)
         },
         Err(e) => {
-            log_error!("Failed to get video duration: {}", e);
-            0.0
+            log_error!("Failed to get video metadata: {}", e);
+            None
         }
-    };
+    }
+}
+
+/// 读取文件大小和修改时间，用于判断文件在两次扫描之间是否变化
+/// 
+/// # 参数
+/// * `path` - 视频路径
+/// # 返回
+/// * `Option<(i64, i64)>` - 成功返回 (文件大小, 修改时间的Unix时间戳)，文件无法访问时返回None
+pub(crate) fn file_state(path: &Path) -> Option<(i64, i64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let mtime = metadata.modified().ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0);
+    Some((metadata.len() as i64, mtime))
+}
+
+/// 外置磁盘和网络共享常用的挂载目录
+const MOUNT_CONTAINERS: &[&str] = &["/Volumes", "/mnt", "/media", "/run/media"];
+/// 按用户再分一层的挂载目录，如 `/media/<用户>/<磁盘>`
+const USER_MOUNT_CONTAINERS: &[&str] = &["/media", "/run/media"];
+
+/// 判断目录是否为挂载目录本身，磁盘未挂载时文件路径只剩下这一级
+fn is_mount_container(dir: &Path) -> bool {
+    MOUNT_CONTAINERS.iter().any(|container| dir == Path::new(container))
+        || USER_MOUNT_CONTAINERS.iter().any(|container| dir.parent() == Some(Path::new(container)))
+}
+
+/// 检查视频文件状态，区分文件被删除和所在磁盘未挂载。
+/// 
+/// 文件不存在时向上查找仍然存在的目录：盘符、网络共享根目录都不存在，
+/// 或者只剩下根目录、挂载目录（如 `/Volumes`、`/media/<用户>`）时视为离线，否则视为文件已删除。
+/// 
+/// # 参数
+/// * `path` - 视频路径
+/// # 返回
+/// * `&'static str` - ok / missing / offline
+pub(crate) fn file_status(path: &Path) -> &'static str {
+    if path.exists() {
+        return STATUS_OK;
+    }
+    match path.ancestors().skip(1).find(|dir| dir.exists()) {
+        None => STATUS_OFFLINE,
+        Some(dir) if dir.parent().is_none() || is_mount_container(dir) => STATUS_OFFLINE,
+        Some(_) => STATUS_MISSING,
+    }
+}
+
+/// 在临时目录中写入 m3u 播放列表，用于把多段视频交给系统默认播放器
+/// 
+/// # 参数
+/// * `id` - 视频ID，用于生成播放列表文件名
+/// * `paths` - 按播放顺序排列的视频路径
+/// # 返回
+/// * `Result<PathBuf, String>` - 成功返回播放列表路径，失败返回错误信息
+pub(crate) fn write_playlist<'a>(id: &str, paths: impl Iterator<Item = &'a str>) -> Result<PathBuf, String> {
+    let playlist = std::env::temp_dir().join(format!("local_video_{}.m3u", id));
+    let mut content = String::from("#EXTM3U\n");
+    for path in paths {
+        content.push_str(path);
+        content.push('\n');
+    }
+    fs::write(&playlist, content).map_err(|e| e.to_string())?;
+    Ok(playlist)
+}
+
+/// 内容指纹每个采样块的大小
+const FINGERPRINT_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// 计算文件的内容指纹：文件大小加上头部、中部、尾部各 64KB 数据的 MD5。
+/// 只读取少量数据，移动或改名后的文件指纹不变。
+/// 
+/// # 参数
+/// * `path` - 视频路径
+/// # 返回
+/// * `Result<String, String>` - 成功返回指纹字符串，失败返回错误信息
+pub(crate) fn content_fingerprint(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    let chunk_size = size.min(FINGERPRINT_CHUNK_SIZE);
+    let last = size - chunk_size;
+
+    let mut context = md5::Context::new();
+    let mut buffer = vec![0u8; chunk_size as usize];
+    for offset in [0, last / 2, last] {
+        file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
+        file.read_exact(&mut buffer).map_err(|e| e.to_string())?;
+        context.consume(&buffer);
+    }
+    Ok(format!("{:016x}-{:x}", size, context.compute()))
+}
 
+/// 将秒数格式化为 HH:MM:SS
+pub(crate) fn format_duration(duration: f64) -> String {
     let hours = duration as u64 / 3600;
     let minutes = duration as u64 % 3600 / 60;
     let seconds = duration as u64 % 60;
-    log_debug!("Duration: {:02}:{:02}:{:02}", hours, minutes, seconds);
-    Ok(format!("{:02}:{:02}:{:02}", hours, minutes, seconds))
+    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+}
+
+/// 将 HH:MM:SS 格式的时长解析为秒数，无法解析时为 0
+pub(crate) fn parse_duration(duration: &str) -> f64 {
+    duration.split(':')
+        .map(|part| part.trim().parse::<f64>())
+        .try_fold(0.0, |total, part| part.map(|value| total * 60.0 + value))
+        .unwrap_or(0.0)
+}
+
+/// 获取视频时长
+/// 
+/// # 参数
+/// * `path` - 视频路径
+/// # 返回
+/// * `Result<String, String>` - 成功返回过滤后的视频时长，失败返回错误信息
+pub(crate) fn get_duration(path: &str) -> Result<String, String> {
+    log_debug!("Getting video duration for: {}", path);
+    let duration = probe_video(path)
+        .map(|metadata| metadata.video_duration_seconds)
+        .unwrap_or(0.0);
+
+    let formatted = format_duration(duration);
+    log_debug!("Duration: {}", formatted);
+    Ok(formatted)
+}
+
+/// 将元数据中的轨道转换为数据库中的轨道记录
+pub(crate) fn build_video_tracks(video_id: &str, metadata: &VideoMetadata) -> Vec<VideoTrack> {
+    metadata.tracks.iter().enumerate().map(|(index, track)| VideoTrack {
+        video_id: video_id.to_string(),
+        track_index: index as i32,
+        track_type: track.track_type.as_str().to_string(),
+        codec: track.codec.clone(),
+        codec_name: track.codec_name(),
+        language: track.language.clone(),
+        language_ietf: track.language_ietf.clone(),
+        name: track.name.clone(),
+        is_default: track.default,
+        is_forced: track.forced,
+        width: track.width,
+        height: track.height,
+        channels: track.channels,
+        sampling_frequency: track.sampling_frequency,
+    }).collect()
+}
 
+/// 将元数据中的章节转换为数据库中的章节记录
+pub(crate) fn build_video_chapters(video_id: &str, metadata: &VideoMetadata) -> Vec<VideoChapter> {
+    metadata.chapters.iter().enumerate().map(|(index, chapter)| VideoChapter {
+        video_id: video_id.to_string(),
+        chapter_index: index as i32,
+        start_time: chapter.start_seconds,
+        end_time: chapter.end_seconds,
+        title: chapter.titles.first()
+            .map(|t| t.title.clone())
+            .unwrap_or_else(|| format!("Chapter {}", index + 1)),
+        titles: chapter.titles.iter()
+            .map(|t| (t.language.clone(), t.title.clone()))
+            .collect(),
+    }).collect()
+}
+
+/// 根据元数据生成视频的画质概况，没有视频轨道时返回 None
+pub(crate) fn build_video_quality(video_id: &str, metadata: &VideoMetadata) -> Option<VideoQuality> {
+    let quality = metadata.quality_profile()?;
+    Some(VideoQuality {
+        video_id: video_id.to_string(),
+        resolution: quality.resolution,
+        width: quality.width,
+        height: quality.height,
+        bit_depth: quality.bit_depth,
+        hdr_format: quality.hdr_format.as_str().to_string(),
+        dolby_vision_profile: quality.dolby_vision_profile as u32,
+    })
 }
 
 /// 查找字幕文件
@@ -142,120 +313,68 @@ fn choose_best_subtitle(video: &VideoInfo, subtitles: Vec<PathBuf>) -> Option<Pa
     })
 }
 
-/// 清理视频文件名以获得更好的搜索结果
-pub(crate) fn clean_video_name(filename: &str) -> (String, String) {
-    // 移除扩展名
-    let name = filename.strip_suffix(std::path::Path::new(filename)
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or(""))
-        .unwrap_or(filename)
-        .trim_end_matches('.');
-    log_debug!("Processing name: {}", name);
-    // 1. 首先尝试提取年份前的内容（通常是电影名称）
-    if let Some(year_match) = regex::Regex::new(r"^(.*?)\b((19|20)\d{2})\b")
-        .unwrap()
-        .captures(name) {
-        if let Some(title) = year_match.get(1) {
-            let mut cleaned = title.as_str().to_string();
-            // 清理分隔符
-            cleaned = cleaned.replace('.', " ")
-                           .replace('_', " ")
-                           .replace('-', " ");
-            // 清理多余空格
-            cleaned = cleaned.split_whitespace()
-                .collect::<Vec<_>>()
-                .join(" ")
-                .trim()
-                .to_string();
-            if !cleaned.is_empty() {
-                // 提取年份
-                if let Some(year) = year_match.get(2) {
-                    let year_str = year.as_str().to_string();
-                    println!("video year: {}", year_str);
-                    return (cleaned, year_str);
-                }
-                return (cleaned, "".to_string());
-            }
-        }
-    }
-    
-    // 2. 如果没有找到年份，则进行常规清理
-    let cleaned = name.to_string();
-    
-    // 定义需要移除的模式
-    let patterns = [
-        r"(?i)\b\d{3,4}p\b", // 分辨率
-        r"(?i)\b(?:x264|x265|h264|h265)\b", // 编码格式
-        r"(?i)\b(?:bluray|brrip|dvdrip|hdrip|webrip|web-dl|hdcam|hdts|cam|ts|tc|r5|dvdscr|dvdscreener|screener)\b", // 版本
-        r"(?i)\b(?:aac|ac3|dts|dd5\.1|mp3|flac|truehd|atmos)\b", // 音频格式
-        r"(?i)\b(?:subs|subbed|dubbed|multi|dual)\b", // 字幕/配音
-        r"(?i)\b(?:extended|unrated|director(?:'s)? cut|theatrical|special edition|ultimate edition|collector(?:'s)? edition)\b", // 版本类型
-        r"(?i)\b(?:repack|proper|rerip|real)\b", // 重新打包
-        r"(?i)\b(?:hevc|hdr|uhd|4k|3d|imax)\b", // 其他格式
-        r"(?i)\b(?:part|pt|season|s|episode|e|ep|vol|volume|v)\d+\b", // 剧集信息
-    ];
-    
-    // 3. 保持清理过程，但添加结果验证
-    let original_words: Vec<&str> = name.split(|c: char| !c.is_alphanumeric())
-        .filter(|s| !s.is_empty())
-        .collect();
-        
-    let mut best_result = cleaned.clone();
-    let mut max_words = 0;
-    
-    // 逐步应用模式，每次检查结果
-    for pattern in patterns {
-        let temp_cleaned = regex::Regex::new(pattern)
-            .unwrap()
-            .replace_all(&cleaned, " ")
-            .to_string();
-            
-        let words: Vec<&str> = temp_cleaned.split_whitespace()
-            .filter(|w| w.len() > 1)  // 忽略单字符
-            .collect();
-            
-        // 如果清理后还有有意义的词，更新结果
-        if words.len() > max_words {
-            max_words = words.len();
-            best_result = words.join(" ");
-        }
-    }
-    
-    // 4. 如果清理后结果为空或只剩文件扩展名，回退到原始文件名的首个有意义词组
-    if best_result.is_empty() || best_result.len() < 3 {
-        let extension = Path::new(filename).extension().and_then(|ext| ext.to_str()).unwrap_or("");
-        best_result = original_words.into_iter()
-            .filter(|w| w.len() > 1 && !w.eq_ignore_ascii_case(extension))
-            .take(3)  // 取前三个词
-            .collect::<Vec<_>>()
-            .join(" ");
-    }
-    
-    log_debug!("Original: {} Cleaned: {}", filename, best_result);
-    (best_result, "".to_string())
+/// 同一语言的 ISO 639-2/B、639-2/T 和 639-1 代码，如设置中的 `chi` 与轨道中的 `zho`、`zh`
+const LANGUAGE_ALIASES: &[&[&str]] = &[
+    &["chi", "zho", "zh"],
+    &["eng", "en"],
+    &["fre", "fra", "fr"],
+    &["ger", "deu", "de"],
+    &["spa", "es"],
+    &["ita", "it"],
+    &["jpn", "ja"],
+    &["kor", "ko"],
+    &["rus", "ru"],
+    &["por", "pt"],
+];
+
+/// 判断轨道语言是否为指定的语言，优先使用 IETF 语言标签。
+/// 语言代码不区分大小写，`zh-CN` 之类的地区后缀忽略
+fn same_language(track: &VideoTrack, language: &str) -> bool {
+    let track_language = if track.language_ietf.is_empty() { &track.language } else { &track.language_ietf };
+    let code = |language: &str| language.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    let (a, b) = (code(track_language), code(language));
+    a == b || LANGUAGE_ALIASES.iter().any(|aliases| aliases.contains(&a.as_str()) && aliases.contains(&b.as_str()))
+}
+
+/// 没有外部字幕时选择内嵌字幕轨道：优先指定语言的非强制字幕，其次默认字幕，最后第一条字幕
+///
+/// # 参数
+/// * `tracks` - 视频的轨道列表
+/// * `language` - 设置中的字幕语言，如 chi、eng
+///
+/// # 返回
+/// * `Option<usize>` - 字幕轨道在所有字幕轨道中的序号（从 0 开始），没有字幕轨道时返回 None
+pub(crate) fn choose_embedded_subtitle(tracks: &[VideoTrack], language: &str) -> Option<usize> {
+    let subtitles: Vec<&VideoTrack> = tracks.iter().filter(|track| track.track_type == "subtitle").collect();
+    subtitles.iter().position(|track| same_language(track, language) && !track.is_forced)
+        .or_else(|| subtitles.iter().position(|track| same_language(track, language)))
+        .or_else(|| subtitles.iter().position(|track| track.is_default))
+        .or_else(|| (!subtitles.is_empty()).then_some(0))
 }
 
 /// 从 TMDb API 获取视频信息并过滤结果
 /// 
 /// # 参数
 /// * `video_name` - 视频名称
+/// * `language` - TMDb 语言代码，如 zh-CN
 /// 
 /// # 返回
 /// * `Result<String, String>` - 成功返回过滤后的单个视频信息，失败返回错误信息
-pub(crate) async fn fetch_video_info_from_tmdb(video_name: &String, api_key: &String) -> Result<String, String> {
-    let cleaned_name = clean_video_name(&video_name);
-    log_info!("************Searching for: {:?}************", cleaned_name);
+pub(crate) async fn fetch_video_info_from_tmdb(video_name: &String, api_key: &String, language: &str) -> Result<String, String> {
+    let stem = Path::new(video_name).file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let release = release::parse_release_name(&stem);
+    log_info!("************Searching for: {} ({})************", release.title, release.year);
 
     let url = format!(
-        "https://api.themoviedb.org/3/search/movie?api_key={}&query={}&language=zh-CN&year={}",
+        "https://api.themoviedb.org/3/search/movie?api_key={}&query={}&language={}&year={}",
         api_key,
-        cleaned_name.0,
-        cleaned_name.1
+        release.title,
+        language,
+        release.year
     );
 
     // 查找最优匹配结果
-    let best_match = match_video(&url, &cleaned_name.0).await?;
+    let best_match = match_video(&url, &release.title).await?;
     log_info!("Found match: {}", serde_json::to_string_pretty(&best_match).unwrap());
 
     if best_match.is_empty() || best_match.eq_ignore_ascii_case("null") {
@@ -283,7 +402,7 @@ pub(crate) async fn fetch_video_info_from_tmdb(video_name: &String, api_key: &St
     .unwrap_or_default();
 
     // 获取类型名称
-    let genres = get_genre_names(&genre_ids, api_key).await?;
+    let genres = get_genre_names(&genre_ids, api_key, language).await?;
 
     // 构建我们需要的信息
     let filtered_info = serde_json::json!({
@@ -303,32 +422,34 @@ pub(crate) async fn fetch_video_info_from_tmdb(video_name: &String, api_key: &St
 /// 
 /// # 参数
 /// * `tv_name` - 视频名称
+/// * `language` - TMDb 语言代码，如 zh-CN
 /// 
 /// # 返回
 /// * `Result<String, String>` - 成功返回过滤后的单个视频信息，失败返回错误信息
-pub(crate) async fn fetch_tv_info_from_tmdb(series_info: &SeriesInfo, api_key: &String) -> Result<String, String> {
-    let cleaned_name = &series_info.series_title.replace(".", " "); //clean_video_name(&series_info.series_title);
+pub(crate) async fn fetch_tv_info_from_tmdb(series_info: &SeriesInfo, api_key: &String, language: &str) -> Result<String, String> {
+    let cleaned_name = &series_info.series_title.replace(".", " ");
     log_info!("************Searching for: {}************", cleaned_name);
 
     let mut series: Option<Value> = None;
-    let mut season_info: Option<Value> = None;
+    // 不同语言的信息分别缓存
+    let cache_key = format!("{}:{}", language, cleaned_name);
 
     // 检查缓存
     {
         let cache = TV_SHOW_CACHE.lock().unwrap();
-        if let Some(cached_info) = cache.get(cleaned_name.as_str()) {
+        if let Some(cached_info) = cache.get(&cache_key) {
             log_info!("Cache hit for: {}", cleaned_name);
             // 访问缓存中的值
             series = cached_info.get("series").cloned();
-            season_info = cached_info.get("season_info").cloned();
         }
     };
 
     if series.is_none() {
         let url = format!(
-            "https://api.themoviedb.org/3/search/tv?api_key={}&query={}&language=zh-CN&year={}",
+            "https://api.themoviedb.org/3/search/tv?api_key={}&query={}&language={}&year={}",
             api_key,
             cleaned_name,
+            language,
             &series_info.year
         );
     
@@ -341,32 +462,48 @@ pub(crate) async fn fetch_tv_info_from_tmdb(series_info: &SeriesInfo, api_key: &
         series = serde_json::from_str(&best_match).map_err(|e| e.to_string())?;
     }
     let series = series.as_ref().ok_or_else(|| "Series not found".to_string())?;
+    // 系列ID
+    let series_id = series.get("id").and_then(|id| id.as_i64()).ok_or("Series ID not found")?;
 
-    if season_info.is_none() {
-        // 系列ID
-        let series_id = series.get("id").and_then(|id| id.as_i64()).ok_or("Series ID not found")?;
-
-        let url = format!(
-            "https://api.themoviedb.org/3/tv/{}/season/{}?api_key={}&language=zh-CN",
-            series_id,
-            series_info.season,
-            api_key
-        );
-        log_debug!("API URL: {}", url);
-        
-        // Season 详细信息
-        let season_info_str = api::get_data(&url).await.map_err(|e| e.to_string())?;
-        season_info = Some(serde_json::from_str::<Value>(&season_info_str).map_err(|e| {
-            log_error!("Failed to parse Season info: {}", e);
-            "Failed to parse Season info".to_string()
-        })?);
+    // 按播出日期命名时，播出日期所在的季中查找该日期播出的一集；
+    // 绝对集数按各季的集数换算为季数和集数，超出所有季时按第 1 季查找
+    let mut season_number = series_info.season;
+    let mut first_episode = series_info.episode;
+    let mut last_episode = series_info.episode.max(series_info.episode_end);
+    if !series_info.air_date.is_empty() {
+        let details = fetch_tv_details(series_id, api_key, language).await?;
+        season_number = find_season_by_air_date(&details, &series_info.air_date).ok_or("Season not found")?;
+    } else if series_info.absolute {
+        let details = fetch_tv_details(series_id, api_key, language).await?;
+        if let Some((season, episode)) = map_absolute_episode(&season_episode_counts(&details), first_episode) {
+            log_debug!("Absolute episode {} is S{:02}E{:02}", first_episode, season, episode);
+            season_number = season;
+            last_episode = episode + (last_episode - first_episode);
+            first_episode = episode;
+        }
     }
-    let season_info = season_info.as_ref().ok_or_else(|| "Season not found".to_string())?;
-
+    let season_info = fetch_tv_season(series_id, season_number, api_key, language).await?;
 
-    // Episode 详细信息
-    let episode_info = get_episode_info(&season_info, series_info.episode as u32).cloned();
-    let episode_info = episode_info.as_ref().ok_or_else(|| "Episode not found".to_string())?;
+    // Episode 详细信息，多集合一的文件包含从第一集到最后一集的所有集
+    let episodes: Vec<&Value> = if series_info.air_date.is_empty() {
+        (first_episode..=last_episode)
+            .filter_map(|episode| get_episode_info(&season_info, episode as u32))
+            .collect()
+    } else {
+        get_episode_by_air_date(&season_info, &series_info.air_date).into_iter().collect()
+    };
+    let episode_info = episodes.first().ok_or_else(|| "Episode not found".to_string())?;
+    let episode_number = episode_info.get("episode_number").and_then(|n| n.as_i64()).unwrap_or(series_info.episode as i64);
+    let episode_end = if episodes.len() > 1 {
+        episodes.last().and_then(|episode| episode.get("episode_number")).and_then(|n| n.as_i64()).unwrap_or(0)
+    } else {
+        0
+    };
+    let join_episodes = |field: &str, separator: &str| episodes.iter()
+        .filter_map(|episode| episode.get(field).and_then(|t| t.as_str()))
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator);
     
     // 获取电视剧的类型ID
     let genre_ids = series.get("genre_ids").and_then(|ids| ids.as_array())
@@ -375,16 +512,15 @@ pub(crate) async fn fetch_tv_info_from_tmdb(series_info: &SeriesInfo, api_key: &
                                     .collect::<Vec<i64>>())
                                 .unwrap_or_default();
     // 获取类型名称
-    let genres = get_genre_names(&genre_ids, api_key).await?;
+    let genres = get_genre_names(&genre_ids, api_key, language).await?;
 
     // 缓存结果
     {
         let mut cache = TV_SHOW_CACHE.lock().unwrap();
         let cache_value = serde_json::json!({
             "series": series,
-            "season_info": season_info,
         });
-        cache.insert(cleaned_name.clone(), cache_value);
+        cache.insert(cache_key, cache_value);
     }
 
     // 构建我们需要的信息
@@ -396,13 +532,113 @@ pub(crate) async fn fetch_tv_info_from_tmdb(series_info: &SeriesInfo, api_key: &
         "poster_path": series.get("poster_path").and_then(|t| t.as_str()).unwrap_or_default(),
         "vote_average": season_info.get("vote_average").and_then(|t| t.as_f64()).unwrap_or(0.0),
         "genres": genres,
-        "episode_title": episode_info.get("name").and_then(|t| t.as_str()).unwrap_or(""),
-        "episode_overview": episode_info.get("overview").and_then(|t| t.as_str()).unwrap_or("")
+        "season": season_number,
+        "episode": episode_number,
+        "episode_end": episode_end,
+        "episode_title": join_episodes("name", " / "),
+        "episode_overview": join_episodes("overview", "\n\n")
     });
     
     return Ok(serde_json::to_string(&filtered_info).unwrap());
 }
 
+/// 获取电视剧一季的详细信息（包括每一集），按剧集、季和语言缓存
+async fn fetch_tv_season(series_id: i64, season: i32, api_key: &str, language: &str) -> Result<Value, String> {
+    let cache_key = format!("{}:tv/{}/season/{}", language, series_id, season);
+    if let Some(season_info) = TV_SHOW_CACHE.lock().unwrap().get(&cache_key) {
+        return Ok(season_info.clone());
+    }
+
+    let url = format!(
+        "https://api.themoviedb.org/3/tv/{}/season/{}?api_key={}&language={}",
+        series_id,
+        season,
+        api_key,
+        language
+    );
+    log_debug!("API URL: {}", url);
+
+    // Season 详细信息
+    let season_info_str = api::get_data(&url).await.map_err(|e| e.to_string())?;
+    let season_info = serde_json::from_str::<Value>(&season_info_str).map_err(|e| {
+        log_error!("Failed to parse Season info: {}", e);
+        "Failed to parse Season info".to_string()
+    })?;
+    TV_SHOW_CACHE.lock().unwrap().insert(cache_key, season_info.clone());
+    Ok(season_info)
+}
+
+/// 获取电视剧的详细信息（包括各季的开播日期和集数），按剧集和语言缓存
+async fn fetch_tv_details(series_id: i64, api_key: &str, language: &str) -> Result<Value, String> {
+    let cache_key = format!("{}:tv/{}", language, series_id);
+    if let Some(details) = TV_SHOW_CACHE.lock().unwrap().get(&cache_key) {
+        return Ok(details.clone());
+    }
+
+    let url = format!("https://api.themoviedb.org/3/tv/{}?api_key={}&language={}", series_id, api_key, language);
+    let details_str = api::get_data(&url).await.map_err(|e| e.to_string())?;
+    let details = serde_json::from_str::<Value>(&details_str).map_err(|e| e.to_string())?;
+    TV_SHOW_CACHE.lock().unwrap().insert(cache_key, details.clone());
+    Ok(details)
+}
+
+/// 电视剧详细信息中的各季，不包括特别篇（第 0 季），返回 (季数, 开播日期, 集数)
+fn tv_seasons(details: &Value) -> Vec<(i32, &str, i32)> {
+    let mut seasons: Vec<(i32, &str, i32)> = details.get("seasons").and_then(|seasons| seasons.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|season| {
+            let number = season.get("season_number")?.as_i64()? as i32;
+            let air_date = season.get("air_date").and_then(|date| date.as_str()).unwrap_or("");
+            let episode_count = season.get("episode_count").and_then(|count| count.as_i64()).unwrap_or(0) as i32;
+            (number > 0).then_some((number, air_date, episode_count))
+        })
+        .collect();
+    seasons.sort();
+    seasons
+}
+
+/// 查找播出日期所在的季：开播日期不晚于该日期的最后一季
+fn find_season_by_air_date(details: &Value, air_date: &str) -> Option<i32> {
+    tv_seasons(details).into_iter()
+        .filter(|(_, start, _)| !start.is_empty() && *start <= air_date)
+        .max_by_key(|(_, start, _)| *start)
+        .map(|(number, _, _)| number)
+}
+
+/// 按顺序排列的各季 (季数, 集数)
+fn season_episode_counts(details: &Value) -> Vec<(i32, i32)> {
+    tv_seasons(details).into_iter().map(|(number, _, count)| (number, count)).collect()
+}
+
+/// 将不分季的绝对集数换算为 (季数, 集数)，如各季分别有 12、13 集时第 20 集为第 2 季第 8 集
+///
+/// # 参数
+/// * `seasons` - 按顺序排列的各季 (季数, 集数)，不包括特别篇
+/// * `absolute` - 绝对集数，从 1 开始
+///
+/// # 返回
+/// * `Option<(i32, i32)>` - 超出所有季的集数时返回 None
+pub(crate) fn map_absolute_episode(seasons: &[(i32, i32)], absolute: i32) -> Option<(i32, i32)> {
+    if absolute < 1 {
+        return None;
+    }
+    let mut remaining = absolute;
+    for &(season, count) in seasons {
+        if remaining <= count {
+            return Some((season, remaining));
+        }
+        remaining -= count;
+    }
+    None
+}
+
+/// 在一季中查找指定日期播出的一集
+fn get_episode_by_air_date<'a>(season_info: &'a Value, air_date: &str) -> Option<&'a Value> {
+    season_info.get("episodes")?.as_array()?.iter()
+        .find(|episode| episode.get("air_date").and_then(|date| date.as_str()) == Some(air_date))
+}
+
 pub(crate) async fn save_poster(path: &Path, poster_path: &String) -> Result<String, String> {
     // 获取视频文件的目录
     let poster_dir = path.parent().unwrap().join("poster"); // 创建 poster 文件夹路径
@@ -428,6 +664,50 @@ pub(crate) async fn save_poster(path: &Path, poster_path: &String) -> Result<Str
     Ok(poster_file.to_string_lossy().to_string())
 }
 
+/// 视频移动后，如果海报是保存在原视频目录 poster 文件夹中的文件且已不存在，
+/// 改用新视频目录中同名的海报（海报随视频文件夹一起移动时）。
+/// 
+/// # 参数
+/// * `thumbnail` - 原缩略图路径
+/// * `path` - 新的视频文件路径
+/// # 返回
+/// * `String` - 新的缩略图路径，找不到时返回原路径
+pub(crate) fn relocate_poster(thumbnail: &str, path: &Path) -> String {
+    let old_poster = Path::new(thumbnail);
+    if thumbnail.starts_with("http") || thumbnail.starts_with("/assets/") || old_poster.exists() {
+        return thumbnail.to_string();
+    }
+    match (old_poster.file_name(), path.parent()) {
+        (Some(file_name), Some(dir)) if dir.join("poster").join(file_name).exists() => {
+            dir.join("poster").join(file_name).to_string_lossy().to_string()
+        }
+        _ => thumbnail.to_string(),
+    }
+}
+
+/// 提取视频内嵌的封面图片并保存到 cover_dir，文件名为视频 ID。
+/// 视频没有内嵌封面时返回 `Ok(None)`。
+///
+/// # 参数
+/// * `path` - 视频文件路径
+/// * `id` - 视频 ID
+/// * `cover_dir` - 封面缓存目录
+///
+/// # 返回
+/// 保存后的封面图片路径
+pub(crate) fn save_embedded_cover(path: &Path, id: &str, cover_dir: &Path) -> Result<Option<String>, String> {
+    let cover = match metadata::cover_art(&path.to_string_lossy())? {
+        Some(cover) => cover,
+        None => return Ok(None),
+    };
+
+    fs::create_dir_all(cover_dir).map_err(|e| e.to_string())?;
+    let cover_file = cover_dir.join(format!("{}.{}", id, cover.extension()));
+    fs::write(&cover_file, &cover.data).map_err(|e| e.to_string())?;
+    log_info!("Saved embedded cover: {}", cover_file.display());
+    Ok(Some(cover_file.to_string_lossy().to_string()))
+}
+
 fn get_episode_info(season_info: &serde_json::Value, episode_number: u32) -> Option<&serde_json::Value> {
     println!("episode_number: {}", &episode_number);
 
@@ -497,11 +777,12 @@ async fn match_video(url: &String, video_name: &String) -> Result<String, String
 }
 
 // 获取类型名称的辅助函数
-pub(crate) async fn get_genre_names(genre_ids: &[i64], api_key: &String) -> Result<String, String> {
+pub(crate) async fn get_genre_names(genre_ids: &[i64], api_key: &String, language: &str) -> Result<String, String> {
 
     let url = format!(
-        "https://api.themoviedb.org/3/genre/movie/list?api_key={}&language=zh-CN",
-        api_key
+        "https://api.themoviedb.org/3/genre/movie/list?api_key={}&language={}",
+        api_key,
+        language
     );
     
     match api::get_data(&url).await {
@@ -540,75 +821,1187 @@ pub struct SeriesInfo {
     pub episode: i32,
     pub is_series: bool,
     pub year: String,
+    /// 多集合一的文件中最后一集的集数，单集文件为 0
+    pub episode_end: i32,
+    /// 按播出日期命名的剧集的播出日期（`YYYY-MM-DD`），此时季数为年份，集数为月日（如 315）
+    pub air_date: String,
+    /// 集数为不分季的绝对集数，如动画的 `[Group] Show - 1024`
+    pub absolute: bool,
 }
 
+/// 文件名中的剧集编号格式
+#[derive(Clone, Copy)]
+enum EpisodeFormat {
+    /// `S01E01`，可带多集 `S01E01E02`、`S01E01-E03`
+    SeasonEpisode,
+    /// `1x05`
+    Cross,
+    /// `第1季第1集`、`第十二集`、`第01-02集`
+    Chinese,
+    /// `2024.03.15`
+    Date,
+    /// `E01`、`EP01`，假定为第 1 季
+    Episode,
+}
+
+/// 剧集编号的匹配模式，按顺序匹配，第 1 组均为剧名
+static EPISODE_PATTERNS: Lazy<Vec<(EpisodeFormat, Regex)>> = Lazy::new(|| {
+    let number = "[0-9]{1,4}|[零一二两三四五六七八九十百千]+";
+    [
+        (EpisodeFormat::SeasonEpisode, r"(?i)^(.*?)[\s._\-\[(]*S(\d{1,4})[\s._]?E(\d{1,4})".to_string()),
+        (EpisodeFormat::Cross, r"(?i)^(.*?)[\s._\-\[(]*\b(\d{1,2})x(\d{2,3})\b".to_string()),
+        (EpisodeFormat::Chinese, format!(r"^(.*?)[\s._\-]*(?:第\s*({0})\s*季)?[\s._\-]*第\s*({0})(?:\s*[-~至到]\s*({0}))?\s*[集话話]", number)),
+        (EpisodeFormat::Date, r"^(.*?)[\s._\-\[(]*\b((?:19|20)\d{2})[.\-_ ](0[1-9]|1[0-2])[.\-_ ](0[1-9]|[12]\d|3[01])\b".to_string()),
+        (EpisodeFormat::Episode, r"(?i)^(.*?)[\s._\-]*\bEP?(\d{1,4})(?:[^\dp]|$)".to_string()),
+    ].into_iter().map(|(format, pattern)| (format, Regex::new(&pattern).unwrap())).collect()
+});
+
+/// 紧接在集数后的其他集数：`E02`、`-E03`、`-03`
+static EPISODE_CONTINUATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(?:[\s._]*-[\s._]*E?|[\s._]?E)(\d{1,4})").unwrap()
+});
+
+/// 多集合一时一个文件最多包含的集数，避免把后面的年份等数字当作集数
+const MAX_EPISODES_PER_FILE: i32 = 30;
+
+/// 解析剧集编号之后的多集标记，返回最后一集的集数，没有时返回 0
+fn parse_episode_end(rest: &str, episode: i32) -> i32 {
+    let mut end = episode;
+    let mut rest = rest;
+    while let Some(caps) = EPISODE_CONTINUATION.captures(rest) {
+        let matched = caps.get(0).unwrap();
+        // 数字后紧跟 `p` 的是分辨率，如 `-720p`
+        if rest[matched.end()..].starts_with(['p', 'P']) {
+            break;
+        }
+        match caps[1].parse::<i32>() {
+            Ok(next) if next > end && next - episode < MAX_EPISODES_PER_FILE => end = next,
+            _ => break,
+        }
+        rest = &rest[matched.end()..];
+    }
+    if end > episode { end } else { 0 }
+}
+
+/// 从文件名中解析剧集信息
+///
+/// 支持 `S01E01`（含 `S01E01E02`、`S01E01-E03` 多集）、`1x05`、`第1季第1集`（含中文数字和 `第01-02集`）、
+/// `2024.03.15` 播出日期、`E01` 和动画的 `Show - 1024` 绝对集数，都不匹配时不是剧集。
+/// 以 `[字幕组]` 开头的文件名先按动画发布名称解析，见 [`parse_anime_release`]；
+/// 其他文件名只有三、四位的绝对集数且带有 CRC32 或分辨率标签时才按动画处理，
+/// 避免 `Mission Impossible - 3`、`Spartans - 300` 被当作剧集，剧集文件夹中的文件见 [`parse_series_path`]。
 pub fn parse_series_info(filename: &str) -> SeriesInfo {
     let year_regex = Regex::new(r"(19|20)\d{2}").unwrap();
     let extracted_year = year_regex.captures(filename)
         .and_then(|caps| caps.get(0).map(|m| m.as_str().to_string()));
+    let mut info = SeriesInfo {
+        series_title: String::new(),
+        season: 1,
+        episode: 1,
+        is_series: false,
+        year: extracted_year.unwrap_or_default(),
+        episode_end: 0,
+        air_date: String::new(),
+        absolute: false,
+    };
 
-    // 常见的剧集命名模式
-    let patterns = [
-        // S01E01 格式
-        r"(?i)(.+?)[\s.]*S(\d{1,2})E(\d{1,2})",
-        // 第1季第1集 格式
-        r"(.+?)第(\d{1,2})季第(\d{1,2})集",
-        // 第01集 格式（假定为第1季）
-        r"(.+?)第(\d{1,2})集",
-        // E01 格式（假定为第1季）
-        r"(?i)(.+?)[\s.]*E(\d{1,2})",
-    ];
+    let is_fansub = filename.trim_start().starts_with('[');
+    if let Some(release) = is_fansub.then(|| parse_anime_release(filename)).flatten() {
+        return release.into_series_info(info);
+    }
+    for (format, pattern) in EPISODE_PATTERNS.iter() {
+        let Some(caps) = pattern.captures(filename) else { continue };
+        let number = |index: usize| caps.get(index).and_then(|m| parse_chinese_number(m.as_str()));
+        let rest = &filename[caps.get(0).unwrap().end()..];
+        match format {
+            EpisodeFormat::SeasonEpisode | EpisodeFormat::Cross => {
+                let (Some(season), Some(episode)) = (number(2), number(3)) else { continue };
+                info.season = season;
+                info.episode = episode;
+                info.episode_end = parse_episode_end(rest, episode);
+            }
+            EpisodeFormat::Chinese => {
+                let Some(episode) = number(3) else { continue };
+                info.season = number(2).unwrap_or(1);
+                info.episode = episode;
+                info.episode_end = number(4).filter(|end| *end > episode).unwrap_or(0);
+            }
+            EpisodeFormat::Date => {
+                let (Some(month), Some(day)) = (number(3), number(4)) else { continue };
+                info.season = caps[2].parse().unwrap_or(1);
+                info.episode = month * 100 + day;
+                info.air_date = format!("{}-{}-{}", &caps[2], &caps[3], &caps[4]);
+                info.year = caps[2].to_string();
+            }
+            EpisodeFormat::Episode => {
+                let Some(episode) = number(2) else { continue };
+                info.episode = episode;
+                let digits = caps.get(2).unwrap();
+                info.episode_end = parse_episode_end(&filename[digits.end()..], episode);
+            }
+        }
+        // `[BD] Show S01E02` 这样开头的标签不属于剧名
+        static LEADING_TAGS: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:\s*\[[^\]]*\])+").unwrap());
+        let title = LEADING_TAGS.replace(&caps[1], "");
+        info.series_title = title.trim_matches(|c: char| c.is_whitespace() || "._-[(".contains(c)).to_string();
+        info.is_series = true;
+        return info;
+    }
+    match parse_anime_release(filename).filter(|release| !is_fansub && release.tagged && release.episode >= MIN_BARE_ABSOLUTE_EPISODE) {
+        Some(release) => release.into_series_info(info),
+        None => info,
+    }
+}
 
-    for pattern in patterns {
-        if let Some(caps) = Regex::new(pattern).unwrap().captures(filename) {
-            match pattern {
-                r"(?i)(.+?)[\s.]*S(\d{1,2})E(\d{1,2})" => {
-                    return SeriesInfo {
-                        series_title: caps.get(1).unwrap().as_str().trim().to_string(),
-                        season: caps.get(2).unwrap().as_str().parse().unwrap_or(1),
-                        episode: caps.get(3).unwrap().as_str().parse().unwrap_or(1),
-                        is_series: true,
-                        year: extracted_year.unwrap_or_default()
-                    };
-                }
-                r"(.+?)第(\d{1,2})季第(\d{1,2})集" => {
-                    return SeriesInfo {
-                        series_title: caps.get(1).unwrap().as_str().trim().to_string(),
-                        season: caps.get(2).unwrap().as_str().parse().unwrap_or(1),
-                        episode: caps.get(3).unwrap().as_str().parse().unwrap_or(1),
-                        is_series: true,
-                        year: extracted_year.unwrap_or_default()
-                    };
-                }
-                r"(.+?)第(\d{1,2})集" => {
-                    return SeriesInfo {
-                        series_title: caps.get(1).unwrap().as_str().trim().to_string(),
-                        season: 1,
-                        episode: caps.get(2).unwrap().as_str().parse().unwrap_or(1),
-                        is_series: true,
-                        year: extracted_year.unwrap_or_default()
-                    };
-                }
-                r"(?i)(.+?)[\s.]*E(\d{1,2})" => {
-                    return SeriesInfo {
-                        series_title: caps.get(1).unwrap().as_str().trim().to_string(),
-                        season: 1,
-                        episode: caps.get(2).unwrap().as_str().parse().unwrap_or(1),
-                        is_series: true,
-                        year: extracted_year.unwrap_or_default()
-                    };
-                }
-                _ => {}
+/// 没有字幕组标签的文件名按绝对集数识别时的最小集数
+const MIN_BARE_ABSOLUTE_EPISODE: i32 = 100;
+
+/// 动画发布名称中的集数：`17`、`17v2`、`01-12`、`01~12`、`第17话`、`EP17`、`17 END`
+static ANIME_EPISODE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(?:第\s*|ep?\s*)?(\d{1,4})(?:\s*[-~]\s*(\d{1,4}))?(?:v\d)?(?:\s*[话話集])?(?:\s+(?:end|fin))?$").unwrap()
+});
+
+/// 动画发布名称中明确的集数标记：`EP17`、`E17`、`第17话`
+static ANIME_EPISODE_MARKER: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^(?:第|ep?\s*\d)|[话話集]$").unwrap());
+
+/// 动画发布名称标签中的分辨率：`1080p`、`BD 720p`、`1920x1080`
+static ANIME_RESOLUTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\b(?:\d{3,4}[pi]|\d{3,4}x\d{3,4}|4k)\b").unwrap());
+
+/// 动画发布名称中的片段：方括号或圆括号中的标签，或括号之间的文字
+static ANIME_PIECES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[([^\]]*)\]|\(([^)]*)\)|([^\[\]()]+)").unwrap());
+
+/// 动画标题末尾的季数：`S2`、`Season 2`、`2nd Season`、`第二季`
+static ANIME_SEASON: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\s+(?:s(\d{1,2})|season\s*(\d{1,2})|(\d{1,2})(?:st|nd|rd|th)\s+season|第\s*([0-9]{1,2}|[一二三四五六七八九十]+)\s*季)$").unwrap()
+});
+
+/// 字幕组发布的动画文件名信息，如 `[SubsPlease] Frieren - 17 (1080p) [ABCD1234].mkv`、
+/// `[字幕组][葬送的芙莉莲][17][1080p][简日双语].mp4`
+#[derive(Debug, PartialEq)]
+pub(crate) struct AnimeRelease {
+    /// 字幕组，没有时为空
+    pub group: String,
+    /// 剧名，不包括季数
+    pub title: String,
+    /// 剧名中的季数，没有时集数为不分季的绝对集数
+    pub season: Option<i32>,
+    pub episode: i32,
+    /// 合集（如 `01-12`）的最后一集，单集为 0
+    pub episode_end: i32,
+    /// 带有 CRC32 或分辨率标签，可以确定是发布的视频而不是片名中的数字
+    pub tagged: bool,
+}
+
+impl AnimeRelease {
+    fn into_series_info(self, info: SeriesInfo) -> SeriesInfo {
+        SeriesInfo {
+            series_title: self.title,
+            season: self.season.unwrap_or(1),
+            episode: self.episode,
+            is_series: true,
+            episode_end: self.episode_end,
+            absolute: self.season.is_none(),
+            ..info
+        }
+    }
+}
+
+/// 解析动画发布名称中的集数，返回 (集数, 最后一集)，`v2` 之类的修正版本后缀忽略
+fn parse_anime_episode(text: &str) -> Option<(i32, i32)> {
+    let caps = ANIME_EPISODE.captures(text.trim())?;
+    // 四位数的年份不是集数
+    if caps[1].len() == 4 && (caps[1].starts_with("19") || caps[1].starts_with("20")) {
+        return None;
+    }
+    let episode = caps[1].parse().ok()?;
+    let episode_end = caps.get(2).and_then(|m| m.as_str().parse().ok()).filter(|end| *end > episode).unwrap_or(0);
+    Some((episode, episode_end))
+}
+
+/// 解析字幕组发布的动画文件名：开头的方括号为字幕组，之后第一段文字为剧名，
+/// 剧名后 ` - ` 之后、单独方括号中或带有 `EP`/`第N话` 标记的数字为集数，
+/// 只以空格分隔的数字不是集数，如 `[Group] Toy Story 3 [1080p].mkv`。8 位十六进制的 CRC32 标签和其他标签忽略。
+///
+/// # 参数
+/// * `file_name` - 文件名
+///
+/// # 返回
+/// * `Option<AnimeRelease>` - 找不到集数时返回 None
+pub(crate) fn parse_anime_release(file_name: &str) -> Option<AnimeRelease> {
+    let stem = Path::new(file_name).file_stem()?.to_string_lossy().replace('_', " ");
+    let mut release = AnimeRelease {
+        group: String::new(),
+        title: String::new(),
+        season: None,
+        episode: 0,
+        episode_end: 0,
+        tagged: false,
+    };
+    let mut found_episode = false;
+    for (index, caps) in ANIME_PIECES.captures_iter(&stem).enumerate() {
+        let text = caps.get(1).or(caps.get(2)).or(caps.get(3)).map_or("", |m| m.as_str()).trim();
+        let text = text.trim_matches(|c: char| c == '-' || c.is_whitespace());
+        if text.is_empty() {
+            continue;
+        }
+        if index == 0 && caps.get(1).is_some() {
+            release.group = text.to_string();
+            continue;
+        }
+        let is_tag = caps.get(3).is_none();
+        if is_tag && (text.len() == 8 && text.chars().all(|c| c.is_ascii_hexdigit()) || ANIME_RESOLUTION.is_match(text)) {
+            release.tagged = true;
+            continue;
+        }
+        if found_episode {
+            continue;
+        }
+        if release.title.is_empty() {
+            // `剧名 - 17`，没有分隔符时必须带有集数标记，如 `剧名 EP17`
+            let split = text.rsplit_once(" - ")
+                .or_else(|| text.rsplit_once(' ').filter(|(_, rest)| ANIME_EPISODE_MARKER.is_match(rest)));
+            if let Some((title, episode)) = split.and_then(|(title, rest)| Some((title, parse_anime_episode(rest)?))) {
+                release.title = title.trim().to_string();
+                (release.episode, release.episode_end) = episode;
+                found_episode = true;
+            } else if parse_anime_episode(text).is_none() {
+                release.title = text.to_string();
+            }
+        } else if let Some(episode) = parse_anime_episode(text) {
+            // 圆括号和括号之间的数字多为片名或年份，如 `Movie 2 (2010)`，`[剧名] - 17` 除外
+            let dashed = caps.get(0).unwrap().as_str().trim_start().starts_with('-');
+            if caps.get(1).is_some() || dashed || ANIME_EPISODE_MARKER.is_match(text) {
+                (release.episode, release.episode_end) = episode;
+                found_episode = true;
             }
         }
     }
+    if !found_episode || release.title.is_empty() {
+        return None;
+    }
 
-    // 如果没有匹配到任何模式，返回默认值
-    SeriesInfo {
-        series_title: String::new(),
-        season: 1,
-        episode: 1,
-        is_series: false,
-        year: extracted_year.unwrap_or_default()
+    if let Some(caps) = ANIME_SEASON.captures(&release.title) {
+        let season = (1..=4).find_map(|index| caps.get(index)).map(|m| m.as_str()).unwrap_or_default();
+        release.season = parse_chinese_number(season);
+        release.title.truncate(caps.get(0).unwrap().start());
+    }
+    Some(release)
+}
+
+/// 剧集文件夹中的标记文件，存在时该文件夹为一部剧集
+pub(crate) const TVSHOW_MARKER: &str = "tvshow.nfo";
+
+/// 季文件夹名称：`Season 2`、`S02`、`Series 2`、`第2季`、`第二季`，`Specials` 为第 0 季
+static SEASON_FOLDER_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"(?i)^(?:season|series|s)[ _.\-]*(\d{1,3})$",
+        r"^第\s*([0-9]{1,3}|[零一二两三四五六七八九十百]+)\s*季$",
+        r"(?i)^(specials?|sp)$",
+    ].iter().map(|pattern| Regex::new(pattern).unwrap()).collect()
+});
+
+/// 文件名中明确写出季数的标记，如 `S02E05`、`2x05`、`第2季`
+static SEASON_IN_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)S\d{1,4}[\s._]?E\d{1,4}|\b\d{1,2}x\d{2,3}\b|第\s*(?:[0-9]{1,3}|[零一二两三四五六七八九十百]+)\s*季").unwrap()
+});
+
+/// 剧集文件夹中只有集数的文件名：`Episode 05`、`EP05`、`E05`、`第五集`、`05`、`Show - 05`
+static EPISODE_ONLY_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"(?i)(?:^|[^a-z])(?:episode|ep|e)[ _.\-]*(\d{1,3})(?:[^\d]|$)",
+        r"第\s*([0-9]{1,3}|[零一二两三四五六七八九十百]+)\s*[集话話]",
+        r"^[ _.\-]*(\d{1,3})(?:[ _.\-\[(]|$)",
+        r"[ _.\-](\d{1,3})[ _.\-]*(?:[\[(].*)?$",
+    ].iter().map(|pattern| Regex::new(pattern).unwrap()).collect()
+});
+
+/// 解析中文数字，如 `十二`、`二十`、`一百零五`，也接受阿拉伯数字
+pub(crate) fn parse_chinese_number(text: &str) -> Option<i32> {
+    if let Ok(number) = text.parse() {
+        return Some(number);
+    }
+    let mut total = 0;
+    let mut current = None;
+    for c in text.chars() {
+        let digit = match c {
+            '零' => 0, '一' => 1, '二' | '两' => 2, '三' => 3, '四' => 4,
+            '五' => 5, '六' => 6, '七' => 7, '八' => 8, '九' => 9,
+            '十' | '百' | '千' => {
+                let unit = match c { '十' => 10, '百' => 100, _ => 1000 };
+                // `十二` 中省略了十位的 `一`
+                total += current.unwrap_or(1) * unit;
+                current = None;
+                continue;
+            }
+            _ => return None,
+        };
+        current = Some(digit);
+    }
+    let number = total + current.unwrap_or(0);
+    (!text.is_empty() && number > 0).then_some(number)
+}
+
+/// 从季文件夹名称中解析季数，不是季文件夹时返回 None
+pub(crate) fn parse_season_folder(name: &str) -> Option<i32> {
+    let name = name.trim();
+    SEASON_FOLDER_PATTERNS.iter().find_map(|pattern| {
+        let caps = pattern.captures(name)?;
+        let season = caps.get(1)?.as_str();
+        if season.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Some(0);
+        }
+        parse_chinese_number(season).or_else(|| season.parse().ok())
+    })
+}
+
+/// 视频所在的剧集文件夹
+#[derive(Debug, PartialEq)]
+pub(crate) struct SeriesFolder {
+    /// 剧集文件夹名称去掉年份后的剧名，季文件夹直接位于资料库根目录时为空
+    pub title: String,
+    /// 剧集文件夹名称中的年份
+    pub year: String,
+    /// 季文件夹对应的季数
+    pub season: Option<i32>,
+}
+
+/// 根据上级目录判断视频是否在剧集文件夹中：直接位于季文件夹中，
+/// 或所在文件夹（季文件夹的上级）包含 `tvshow.nfo`。
+///
+/// # 参数
+/// * `path` - 视频文件路径
+/// * `root` - 资料库根目录，根目录及以上的文件夹名称不作为剧名
+///
+/// # 返回
+/// * `Option<SeriesFolder>` - 不在剧集文件夹中时返回 None
+pub(crate) fn find_series_folder(path: &Path, root: Option<&Path>) -> Option<SeriesFolder> {
+    let within_root = |dir: &Path| root.is_none_or(|root| dir.starts_with(root));
+    let below_root = |dir: &Path| within_root(dir) && root != Some(dir);
+    let has_marker = |dir: &Path| within_root(dir) && dir.join(TVSHOW_MARKER).is_file();
+
+    let parent = path.parent()?;
+    let season = parent.file_name()
+        .and_then(|name| parse_season_folder(&name.to_string_lossy()))
+        .filter(|_| within_root(parent));
+    let series_dir = match season {
+        Some(_) => parent.parent().filter(|dir| below_root(dir) || has_marker(dir)),
+        None => Some(parent).filter(|dir| has_marker(dir)),
+    };
+    if season.is_none() && series_dir.is_none() {
+        return None;
+    }
+
+    let (title, year) = series_dir
+        .and_then(|dir| dir.file_name())
+        .map(|name| split_folder_year(&name.to_string_lossy()))
+        .unwrap_or_default();
+    Some(SeriesFolder { title, year, season })
+}
+
+/// 拆分文件夹名称中的年份，如 `Breaking Bad (2008)` 为 (`Breaking Bad`, `2008`)
+fn split_folder_year(name: &str) -> (String, String) {
+    static YEAR: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(.+?)[ _.\-]*[(\[]?((?:19|20)\d{2})[)\]]?$").unwrap());
+    let name = name.replace(['.', '_'], " ");
+    let name = name.trim();
+    match YEAR.captures(name) {
+        Some(caps) => (caps[1].trim().to_string(), caps[2].to_string()),
+        None => (name.to_string(), String::new()),
+    }
+}
+
+/// 从剧集文件夹中只有集数的文件名中解析集数，如 `02.mkv`、`Episode 05.mkv`
+fn parse_episode_only(file_name: &str) -> Option<i32> {
+    let stem = Path::new(file_name).file_stem()?.to_string_lossy();
+    EPISODE_ONLY_PATTERNS.iter()
+        .find_map(|pattern| parse_chinese_number(pattern.captures(&stem)?.get(1)?.as_str()))
+        .filter(|episode| *episode > 0)
+}
+
+/// 剧名只有集数、标点或 `Episode` 这样的词时无法用于查找
+fn is_weak_series_title(title: &str) -> bool {
+    let title = title.trim_matches(|c: char| !c.is_alphanumeric());
+    title.is_empty()
+        || title.chars().all(|c| c.is_ascii_digit())
+        || ["episode", "ep", "e"].contains(&title.to_ascii_lowercase().as_str())
+}
+
+/// 结合文件名和上级目录解析剧集信息
+///
+/// 文件名无法识别为剧集、但位于季文件夹或包含 `tvshow.nfo` 的文件夹中时，按剧集处理并从文件名中解析集数；
+/// 文件名中没有明确的季数时使用季文件夹的季数；文件名中的剧名无法用于查找时使用剧集文件夹的名称。
+///
+/// # 参数
+/// * `path` - 视频文件路径
+/// * `file_name` - 用于识别的文件名，光盘文件夹为加上类型后的名称
+/// * `root` - 资料库根目录
+///
+/// # 返回
+/// * `SeriesInfo` - 剧集信息
+pub(crate) fn parse_series_path(path: &Path, file_name: &str, root: Option<&Path>) -> SeriesInfo {
+    let mut info = parse_series_info(file_name);
+    let Some(folder) = find_series_folder(path, root) else {
+        return info;
+    };
+    if !info.is_series {
+        // 剧集文件夹中没有标签的 `One Piece - 1024` 也是绝对集数
+        if let Some(release) = parse_anime_release(file_name).filter(|release| release.episode >= MIN_BARE_ABSOLUTE_EPISODE) {
+            info = release.into_series_info(info);
+        } else {
+            let Some(episode) = parse_episode_only(file_name) else {
+                return info;
+            };
+            info = SeriesInfo { episode, is_series: true, ..info };
+        }
+    }
+    // 按播出日期命名时季数为年份，不使用季文件夹
+    if let Some(season) = folder.season.filter(|_| info.air_date.is_empty() && !SEASON_IN_NAME.is_match(file_name)) {
+        info.season = season;
+        // 季文件夹中的 `Show - 05` 是该季的集数
+        info.absolute = false;
+    }
+    if is_weak_series_title(&info.series_title) && !folder.title.is_empty() {
+        info.series_title = folder.title;
+        if !folder.year.is_empty() {
+            info.year = folder.year;
+        }
+    } else if info.series_title.is_empty() {
+        // 季文件夹直接位于资料库根目录，没有剧集文件夹
+        info.series_title = Path::new(file_name).file_stem().unwrap_or_default().to_string_lossy().to_string();
+    }
+    info
+}
+
+/// 多段视频的分段标记：cd/dvd/disc/disk/part/pt 加数字或字母，或文件名末尾紧跟 `-`、`_` 的 a-d 字母。
+/// 单独的字母要求紧跟连接符，避免 `Plan B`、`Ocean's Eleven - A` 之类的片名被当作分段。
+/// 分组依次为：标记前的名称、分段序号、标记后的名称、扩展名
+static STACK_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"(?i)^(.+?)[ _.\-]+(?:cd|dvd|part|pt|dis[ck])[ _.\-]*(\d{1,2})((?:[ _.\-\[(].*?)?)(\.[^.]+)$",
+        r"(?i)^(.+?)[ _.\-]+(?:cd|dvd|part|pt|dis[ck])[ _.\-]*([a-d])((?:[ _.\-\[(].*?)?)(\.[^.]+)$",
+        r"(?i)^(.*\S)[_\-]([a-d])()(\.[^.]+)$",
+    ].iter().map(|pattern| Regex::new(pattern).unwrap()).collect()
+});
+
+/// 多段视频中的一段在文件名中的信息
+#[derive(Debug, PartialEq)]
+pub(crate) struct StackPart {
+    /// 去掉分段标记后的文件名（含扩展名），同一目录下相同的文件属于同一部视频
+    pub name: String,
+    /// 分段序号，从 1 开始，字母 a-d 对应 1-4
+    pub number: i32,
+    /// 分段标记为 `part`/`pt`，也可能是续集的编号，如 `Back to the Future Part 2`
+    pub sequel_like: bool,
+}
+
+/// 从文件名中识别分段标记，如 `Movie.CD1.avi`、`Movie (1999) part 2.mkv`、`Movie-b.avi`
+///
+/// 只根据文件名判断，同一目录下存在其他段时才会被分组为多段视频，见 [`group_video_parts`]；
+/// `part`/`pt` 标记还要求所在文件夹与视频同名，见 [`is_stack_dir`]。
+pub(crate) fn parse_stack_part(file_name: &str) -> Option<StackPart> {
+    STACK_PATTERNS.iter().find_map(|pattern| {
+        let caps = pattern.captures(file_name)?;
+        let marker = caps.get(2)?.as_str();
+        let number = match marker.parse::<i32>() {
+            Ok(number) => number,
+            Err(_) => (marker.to_ascii_lowercase().as_bytes()[0] - b'a') as i32 + 1,
+        };
+        if number < 1 {
+            return None;
+        }
+        let separator = &file_name[caps.get(1)?.end()..caps.get(2)?.start()];
+        Some(StackPart {
+            name: format!("{}{}{}", &caps[1], &caps[3], &caps[4]),
+            number,
+            sequel_like: separator.trim_start_matches([' ', '_', '.', '-']).starts_with(['p', 'P']),
+        })
+    })
+}
+
+/// 判断分段所在的目录是否可以组成多段视频。
+/// `part`/`pt` 标记只在去掉标记后的名称与文件夹名称相同时才视为分段，
+/// 如 `Movie (1999)/Movie (1999) part 2.mkv`，合集文件夹中的 `Kill Bill Part 2.mkv` 是续集。
+fn is_stack_dir(part: &StackPart, dir: &Path) -> bool {
+    if !part.sequel_like {
+        return true;
+    }
+    let stem = Path::new(&part.name).file_stem().unwrap_or_default().to_string_lossy().trim().to_lowercase();
+    dir.file_name().is_some_and(|folder| folder.to_string_lossy().trim().to_lowercase() == stem)
+}
+
+/// 查找同一目录下与该文件属于同一部视频的文件（包括文件本身），返回 (分段序号, 文件路径)
+pub(crate) fn find_stack_files(path: &Path, part: &StackPart) -> Vec<(i32, PathBuf)> {
+    let Some(dir) = path.parent().filter(|dir| is_stack_dir(part, dir)) else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let name = part.name.to_lowercase();
+    let mut files: Vec<(i32, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let other = parse_stack_part(&entry.file_name().to_string_lossy())?;
+            (other.name.to_lowercase() == name).then(|| (other.number, entry.path()))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// 按目录和分段标记重新分组多段视频，返回分组需要变化的视频：(视频ID, 主视频ID, 分段序号)
+///
+/// 同一目录下去掉分段标记后文件名相同、序号互不相同且包含第 1 段的至少两个电影组成一部多段视频，
+/// 第 1 段为主视频，主视频ID为空；不再满足条件的视频恢复为独立视频。
+pub(crate) fn group_video_parts(videos: &[VideoInfo]) -> Vec<(String, String, i32)> {
+    let mut groups: HashMap<(PathBuf, String), Vec<(i32, &VideoInfo)>> = HashMap::new();
+    for video in videos.iter().filter(|video| !video.is_series) {
+        let path = Path::new(&video.path);
+        let Some(part) = path.file_name().and_then(|name| parse_stack_part(&name.to_string_lossy())) else {
+            continue;
+        };
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        if !is_stack_dir(&part, &dir) {
+            continue;
+        }
+        groups.entry((dir, part.name.to_lowercase())).or_default().push((part.number, video));
+    }
+
+    let mut assigned: HashMap<&str, (&str, i32)> = HashMap::new();
+    for parts in groups.values_mut() {
+        parts.sort_by_key(|(number, _)| *number);
+        let distinct = parts.windows(2).all(|pair| pair[0].0 != pair[1].0);
+        if parts.len() < 2 || !distinct || parts[0].0 != 1 {
+            continue;
+        }
+        let primary = parts[0].1.id.as_str();
+        for (number, video) in parts.iter() {
+            let part_of = if video.id == primary { "" } else { primary };
+            assigned.insert(&video.id, (part_of, *number));
+        }
+    }
+
+    videos.iter()
+        .filter_map(|video| {
+            let (part_of, number) = assigned.get(video.id.as_str()).copied().unwrap_or(("", 0));
+            (video.part_of != part_of || video.part_number != number)
+                .then(|| (video.id.clone(), part_of.to_string(), number))
+        })
+        .collect()
+}
+
+/// 合并多段视频：其他段并入主视频的 `parts`，主视频的时长为各段时长之和。
+///
+/// 主视频已不存在的段作为独立视频返回。
+pub(crate) fn merge_video_parts(videos: Vec<VideoInfo>) -> Vec<VideoInfo> {
+    let (mut merged, others): (Vec<_>, Vec<_>) = videos.into_iter().partition(|video| video.part_of.is_empty());
+    let mut parts: HashMap<String, Vec<VideoInfo>> = HashMap::new();
+    for video in others {
+        parts.entry(video.part_of.clone()).or_default().push(video);
+    }
+
+    for video in merged.iter_mut() {
+        let Some(others) = parts.remove(&video.id) else { continue };
+        let mut video_parts: Vec<VideoPart> = std::iter::once(&*video).chain(&others).map(|part| VideoPart {
+            id: part.id.clone(),
+            path: part.path.clone(),
+            duration: part.duration.clone(),
+            part_number: part.part_number,
+            status: part.status.clone(),
+        }).collect();
+        video_parts.sort_by_key(|part| part.part_number);
+        video.duration = format_duration(video_parts.iter().map(|part| parse_duration(&part.duration)).sum());
+        video.parts = video_parts;
     }
-}
\ No newline at end of file
+
+    merged.extend(parts.into_values().flatten().map(|mut video| {
+        video.part_of.clear();
+        video.part_number = 0;
+        video
+    }));
+    merged
+}
+
+/// 附加内容文件夹名称（不区分大小写）及对应的类型
+const EXTRA_FOLDERS: [(&str, &str); 9] = [
+    ("trailers", "trailer"),
+    ("featurettes", "featurette"),
+    ("behind the scenes", "behindthescenes"),
+    ("deleted scenes", "deleted"),
+    ("interviews", "interview"),
+    ("scenes", "scene"),
+    ("shorts", "short"),
+    ("extras", "other"),
+    ("other", "other"),
+];
+
+/// 附加内容文件名后缀（不区分大小写，以 `-` 或 `_` 分隔）及对应的类型
+const EXTRA_SUFFIXES: [(&str, &str); 11] = [
+    ("trailer", "trailer"),
+    ("teaser", "trailer"),
+    ("featurette", "featurette"),
+    ("behindthescenes", "behindthescenes"),
+    ("deleted", "deleted"),
+    ("interview", "interview"),
+    ("scene", "scene"),
+    ("short", "short"),
+    ("clip", "other"),
+    ("extra", "other"),
+    ("other", "other"),
+];
+
+/// 附加内容的识别结果
+#[derive(Debug, PartialEq)]
+pub(crate) struct ExtraMatch {
+    /// 附加内容类型，见 [`crate::db::VideoExtra`]
+    pub extra_type: &'static str,
+    /// 是否由所在文件夹识别，此时所属视频在上一级目录中，否则在同一目录中
+    pub in_folder: bool,
+}
+
+/// 按 Plex / Jellyfin 的命名规则识别附加内容：
+/// 所在文件夹为 `Trailers`、`Featurettes`、`Behind The Scenes` 等，
+/// 或文件名以 `-trailer`、`-featurette`、`-deleted` 等结尾，或文件名就是 `trailer`
+/// 
+/// 附加内容文件夹必须位于包含正片的电影文件夹中，
+/// 资料库中名为 `Other`、`Shorts` 的普通文件夹里的视频仍作为电影入库。
+/// 
+/// # 参数
+/// * `path` - 视频路径
+/// # 返回
+/// * `Option<ExtraMatch>` - 是附加内容时返回类型，否则返回None
+pub(crate) fn parse_extra(path: &Path) -> Option<ExtraMatch> {
+    let dir = path.parent();
+    let folder = dir
+        .and_then(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy().to_lowercase());
+    if let Some(folder) = folder {
+        if let Some((_, extra_type)) = EXTRA_FOLDERS.iter().find(|(name, _)| *name == folder) {
+            if dir.and_then(Path::parent).is_some_and(has_main_video) {
+                return Some(ExtraMatch { extra_type, in_folder: true });
+            }
+        }
+    }
+    parse_extra_suffix(path)
+}
+
+/// 按文件名后缀识别附加内容
+fn parse_extra_suffix(path: &Path) -> Option<ExtraMatch> {
+    let stem = path.file_stem()?.to_string_lossy().to_lowercase();
+    EXTRA_SUFFIXES.iter()
+        .find(|(suffix, _)| {
+            stem == *suffix || stem.strip_suffix(suffix).is_some_and(|rest| rest.ends_with(['-', '_']))
+        })
+        .map(|(_, extra_type)| ExtraMatch { extra_type, in_folder: false })
+}
+
+/// 判断目录是否包含正片：不是附加内容的视频文件、光盘镜像，或目录本身是光盘文件夹
+fn has_main_video(dir: &Path) -> bool {
+    if disc::disc_kind(dir).is_some() {
+        return true;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    entries.filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .any(|path| {
+            let extension = path.extension().map(|ext| ext.to_string_lossy().to_ascii_lowercase()).unwrap_or_default();
+            metadata::supported_extensions().chain(disc::DISC_IMAGE_EXTENSIONS).any(|ext| ext == extension)
+                && parse_extra_suffix(&path).is_none()
+        })
+}
+
+/// 为附加内容选择所属视频：候选视频为所属目录中的视频，
+/// 优先选择附加内容文件名以其文件名开头的视频，如 `Movie (2020)-trailer.mkv` 属于 `Movie (2020).mkv`，
+/// 否则选择路径排在最前的视频。
+/// 
+/// # 参数
+/// * `path` - 附加内容路径
+/// * `extra` - 附加内容识别结果
+/// * `candidates` - 所属目录（含子目录）中的视频
+/// # 返回
+/// * `Option<&VideoInfo>` - 成功返回所属视频，目录中没有视频时返回None
+pub(crate) fn choose_extra_parent<'a>(path: &Path, extra: &ExtraMatch, candidates: &'a [VideoInfo]) -> Option<&'a VideoInfo> {
+    let dir = extra_parent_dir(path, extra)?;
+    let stem = path.file_stem()?.to_string_lossy().to_lowercase();
+    let mut videos: Vec<&VideoInfo> = candidates.iter()
+        .filter(|video| video.part_of.is_empty() && Path::new(&video.path).parent() == Some(dir))
+        .collect();
+    videos.sort_by(|a, b| a.path.cmp(&b.path));
+    videos.iter()
+        .find(|video| {
+            Path::new(&video.path).file_stem()
+                .is_some_and(|name| stem.starts_with(&name.to_string_lossy().to_lowercase()))
+        })
+        .or(videos.first())
+        .copied()
+}
+
+/// 附加内容所属视频所在的目录
+pub(crate) fn extra_parent_dir<'a>(path: &'a Path, extra: &ExtraMatch) -> Option<&'a Path> {
+    let dir = path.parent()?;
+    if extra.in_folder { dir.parent() } else { Some(dir) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{test_video, TempDir};
+
+    fn subtitle(language: &str, is_default: bool, is_forced: bool) -> VideoTrack {
+        VideoTrack {
+            video_id: String::new(),
+            track_index: 0,
+            track_type: "subtitle".to_string(),
+            codec: String::new(),
+            codec_name: String::new(),
+            language: language.to_string(),
+            language_ietf: String::new(),
+            name: String::new(),
+            is_default,
+            is_forced,
+            width: 0,
+            height: 0,
+            channels: 0,
+            sampling_frequency: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_choose_embedded_subtitle() {
+        let mut video = subtitle("", false, false);
+        video.track_type = "video".to_string();
+        let tracks = vec![
+            video,
+            subtitle("eng", true, false),
+            subtitle("zho", false, true),
+            subtitle("zh-CN", false, false),
+        ];
+        assert_eq!(choose_embedded_subtitle(&tracks, "chi"), Some(2));
+        assert_eq!(choose_embedded_subtitle(&tracks, "eng"), Some(0));
+        assert_eq!(choose_embedded_subtitle(&tracks, "jpn"), Some(0));
+        assert_eq!(choose_embedded_subtitle(&tracks[..1], "eng"), None);
+        assert_eq!(choose_embedded_subtitle(&[subtitle("fre", false, true)], "chi"), Some(0));
+        assert_eq!(choose_embedded_subtitle(&[subtitle("ger", false, false), subtitle("deu", false, false)], "deu"), Some(0));
+
+        // Language 为默认值 und 时使用 LanguageIETF
+        let mut ietf = subtitle("und", false, false);
+        ietf.language_ietf = "zh-Hans".to_string();
+        assert_eq!(choose_embedded_subtitle(&[subtitle("eng", true, false), ietf], "chi"), Some(1));
+    }
+
+    #[test]
+    fn test_content_fingerprint() {
+        let size = 300 * 1024;
+        let data: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+        let dir = TempDir::new("fingerprint", &[("a.mkv", data.clone()), ("small.mkv", b"small".to_vec()), ("empty.mkv", Vec::new())]);
+        let path = dir.0.join("a.mkv");
+        let fingerprint = content_fingerprint(&path).unwrap();
+        assert!(fingerprint.starts_with(&format!("{:016x}-", size)));
+
+        // 移动或改名后指纹不变
+        let renamed = dir.0.join("Renamed/b.mkv");
+        fs::create_dir_all(renamed.parent().unwrap()).unwrap();
+        fs::rename(&path, &renamed).unwrap();
+        assert_eq!(content_fingerprint(&renamed).unwrap(), fingerprint);
+
+        // 只有头部、中部、尾部的采样块参与计算
+        let last = size - FINGERPRINT_CHUNK_SIZE as usize;
+        for (offset, changes) in [(0, true), (last / 2 + 10, true), (size - 1, true), (80_000, false)] {
+            let mut modified = data.clone();
+            modified[offset] ^= 0xFF;
+            fs::write(&renamed, &modified).unwrap();
+            assert_eq!(content_fingerprint(&renamed).unwrap() != fingerprint, changes, "offset {}", offset);
+        }
+
+        // 小于一个采样块和空文件也能计算
+        let small = content_fingerprint(&dir.0.join("small.mkv")).unwrap();
+        fs::write(dir.0.join("small.mkv"), b"smalL").unwrap();
+        assert_ne!(content_fingerprint(&dir.0.join("small.mkv")).unwrap(), small);
+        let empty = content_fingerprint(&dir.0.join("empty.mkv")).unwrap();
+        assert!(empty.starts_with(&format!("{:016x}-", 0)));
+        assert_ne!(empty, small);
+        assert!(content_fingerprint(&dir.0.join("missing.mkv")).is_err());
+    }
+
+    #[test]
+    fn test_file_status() {
+        let dir = TempDir::new("file_status", &[("Movies/Movie.mkv", Vec::new())]);
+        assert_eq!(file_status(&dir.0.join("Movies/Movie.mkv")), STATUS_OK);
+        // 所在目录还在，文件被删除
+        assert_eq!(file_status(&dir.0.join("Movies/Deleted.mkv")), STATUS_MISSING);
+        assert_eq!(file_status(&dir.0.join("Deleted/Sub/Movie.mkv")), STATUS_MISSING);
+
+        // 只剩下根目录或挂载目录时视为磁盘未挂载
+        assert_eq!(file_status(Path::new("/local_video_unplugged_disk/Movies/Movie.mkv")), STATUS_OFFLINE);
+        assert_eq!(file_status(Path::new("/Volumes/local_video_unplugged_disk/Movie.mkv")), STATUS_OFFLINE);
+        assert_eq!(file_status(Path::new("/mnt/local_video_unplugged_disk/Movie.mkv")), STATUS_OFFLINE);
+
+        assert!(is_mount_container(Path::new("/Volumes")));
+        assert!(is_mount_container(Path::new("/media/alice")));
+        assert!(is_mount_container(Path::new("/run/media/alice")));
+        assert!(!is_mount_container(Path::new("/media/alice/Disk")));
+        assert!(!is_mount_container(Path::new("/home/alice")));
+    }
+
+    #[test]
+    fn test_parse_stack_part() {
+        let cases = [
+            ("Movie.CD1.avi", Some(("Movie.avi", 1, false))),
+            ("Movie (1999) part 2.mkv", Some(("Movie (1999).mkv", 2, true))),
+            ("Movie.Disc-3.1080p.mkv", Some(("Movie.1080p.mkv", 3, false))),
+            ("Movie pt.B.mkv", Some(("Movie.mkv", 2, true))),
+            ("Movie-b.avi", Some(("Movie.avi", 2, false))),
+            ("Movie_A.avi", Some(("Movie.avi", 1, false))),
+            ("Movie.CD0.avi", None),
+            ("Plan B.mkv", None),
+            ("Ocean's Eleven - A.mkv", None),
+            ("Movie.Edition.mkv", None),
+            ("Apollo 13.mkv", None),
+        ];
+        for (file_name, expected) in cases {
+            let part = parse_stack_part(file_name);
+            assert_eq!(part.as_ref().map(|part| (part.name.as_str(), part.number, part.sequel_like)), expected, "{}", file_name);
+        }
+
+        let part = parse_stack_part("Kill Bill Part 2.mkv").unwrap();
+        assert!(!is_stack_dir(&part, Path::new("/m/Kill Bill Collection")));
+        assert!(is_stack_dir(&part, Path::new("/m/kill bill")));
+        assert!(is_stack_dir(&parse_stack_part("Movie.CD2.avi").unwrap(), Path::new("/m/Movies")));
+    }
+
+    #[test]
+    fn test_group_video_parts() {
+        let grouped = |video: VideoInfo, part_of: &str, part_number: i32| VideoInfo { part_of: part_of.to_string(), part_number, ..video };
+        let mut episode = test_video("episode", "/m/Show.Part1.mkv");
+        episode.is_series = true;
+        let videos = vec![
+            test_video("cd2", "/m/Movie.CD2.avi"),
+            test_video("cd1", "/m/Movie.CD1.avi"),
+            test_video("other", "/m/Other/Movie.CD2.avi"),
+            test_video("plan", "/m/Plan.mkv"),
+            test_video("plan_b", "/m/Plan B.mkv"),
+            // 只剩一段时恢复为独立视频
+            grouped(test_video("alone", "/m/Alone-b.mkv"), "gone", 2),
+            // 已经分组且没有变化的视频不返回
+            grouped(test_video("x1", "/m/X-a.mkv"), "", 1),
+            grouped(test_video("x2", "/m/X-b.mkv"), "x1", 2),
+            // 序号重复时不分组
+            test_video("dup1", "/m/Dup.cd1.mkv"),
+            test_video("dup2", "/m/Dup/Dup.part1.mkv"),
+            test_video("dup3", "/m/Dup/Dup.cd1.mkv"),
+            // 合集文件夹中的 Part 1/2/3 是续集，与文件夹同名时才是分段
+            test_video("bttf1", "/m/Back to the Future Trilogy/Back to the Future Part 1.mkv"),
+            test_video("bttf2", "/m/Back to the Future Trilogy/Back to the Future Part 2.mkv"),
+            test_video("bttf3", "/m/Back to the Future Trilogy/Back to the Future Part 3.mkv"),
+            test_video("long1", "/m/Long Movie (1999)/Long Movie (1999) part 1.mkv"),
+            test_video("long2", "/m/Long Movie (1999)/Long Movie (1999) pt.2.mkv"),
+            episode,
+            test_video("episode2", "/m/Show.Part2.mkv"),
+        ];
+        assert_eq!(group_video_parts(&videos), vec![
+            ("cd2".to_string(), "cd1".to_string(), 2),
+            ("cd1".to_string(), String::new(), 1),
+            ("alone".to_string(), String::new(), 0),
+            ("long1".to_string(), String::new(), 1),
+            ("long2".to_string(), "long1".to_string(), 2),
+        ]);
+    }
+
+    #[test]
+    fn test_merge_video_parts() {
+        let videos = vec![
+            VideoInfo { part_of: "a".to_string(), part_number: 2, duration: "00:45:30".to_string(), ..test_video("b", "/m/Movie.CD2.avi") },
+            VideoInfo { part_number: 1, duration: "01:00:40".to_string(), ..test_video("a", "/m/Movie.CD1.avi") },
+            VideoInfo { part_of: "gone".to_string(), part_number: 2, ..test_video("c", "/m/Other.CD2.avi") },
+            test_video("d", "/m/Single.mkv"),
+        ];
+        let merged = merge_video_parts(videos);
+        let ids: Vec<&str> = merged.iter().map(|video| video.id.as_str()).collect();
+        assert_eq!(ids, ["a", "d", "c"]);
+
+        assert_eq!(merged[0].duration, "01:46:10");
+        let parts: Vec<(&str, i32)> = merged[0].parts.iter().map(|part| (part.id.as_str(), part.part_number)).collect();
+        assert_eq!(parts, [("a", 1), ("b", 2)]);
+        assert!(merged[1].parts.is_empty());
+        // 主视频已不存在的段作为独立视频
+        assert_eq!((merged[2].part_of.as_str(), merged[2].part_number), ("", 0));
+    }
+
+    #[test]
+    fn test_parse_extra() {
+        let dir = TempDir::new("parse_extra", &[
+            ("Movie (2020)/Movie (2020).mkv", Vec::new()),
+            ("Movie (2020)/Trailers/Teaser.mkv", Vec::new()),
+            ("Movie (2020)/Other/Making Of.mkv", Vec::new()),
+            ("Other/Real Movie.mkv", Vec::new()),
+            ("Shorts/Short Film.mkv", Vec::new()),
+            ("Only Extras/Movie-trailer.mkv", Vec::new()),
+            ("Only Extras/Featurettes/Making Of.mkv", Vec::new()),
+            ("Disc/BDMV/index.bdmv", Vec::new()),
+            ("Disc/Extras/Interview.mkv", Vec::new()),
+        ]);
+        let extra = |file: &str| parse_extra(&dir.0.join(file)).map(|extra| (extra.extra_type, extra.in_folder));
+        assert_eq!(extra("Movie (2020)/Trailers/Teaser.mkv"), Some(("trailer", true)));
+        assert_eq!(extra("Movie (2020)/Other/Making Of.mkv"), Some(("other", true)));
+        assert_eq!(extra("Disc/Extras/Interview.mkv"), Some(("other", true)));
+        // 上一级目录中没有正片时，文件夹名称不作为附加内容的依据
+        assert_eq!(extra("Other/Real Movie.mkv"), None);
+        assert_eq!(extra("Shorts/Short Film.mkv"), None);
+        assert_eq!(extra("Only Extras/Featurettes/Making Of.mkv"), None);
+
+        let cases = [
+            ("Movie (2020)-trailer.mkv", Some(("trailer", false))),
+            ("Movie_behindthescenes.mkv", Some(("behindthescenes", false))),
+            ("TRAILER.mp4", Some(("trailer", false))),
+            ("Movie-Deleted.mkv", Some(("deleted", false))),
+            ("The Trailer.mkv", None),
+            ("Movie.mkv", None),
+        ];
+        for (file_name, expected) in cases {
+            assert_eq!(extra(file_name), expected, "{}", file_name);
+        }
+    }
+
+    #[test]
+    fn test_choose_extra_parent() {
+        let candidates = vec![
+            test_video("b", "/m/Movie/B.mkv"),
+            test_video("a", "/m/Movie/A.mkv"),
+            test_video("a2", "/m/Movie/A.CD2.mkv"),
+            test_video("nested", "/m/Movie/Sub/Movie.mkv"),
+        ];
+        let candidates: Vec<VideoInfo> = candidates.into_iter()
+            .map(|video| if video.id == "a2" { VideoInfo { part_of: "a".to_string(), ..video } } else { video })
+            .collect();
+        let suffix = ExtraMatch { extra_type: "trailer", in_folder: false };
+        let folder = ExtraMatch { extra_type: "trailer", in_folder: true };
+        let parent = |path: &str, extra: &ExtraMatch| choose_extra_parent(Path::new(path), extra, &candidates).map(|video| video.id.as_str());
+
+        // 文件名以视频文件名开头时属于该视频，否则属于路径排在最前的视频
+        assert_eq!(parent("/m/Movie/B-trailer.mkv", &suffix), Some("b"));
+        assert_eq!(parent("/m/Movie/trailer.mkv", &suffix), Some("a"));
+        assert_eq!(parent("/m/Movie/Trailers/B Teaser.mkv", &folder), Some("b"));
+        assert_eq!(parent("/m/Movie/Trailers/Teaser.mkv", &folder), Some("a"));
+        // 多段视频的其他段和子目录中的视频不作为候选
+        assert_eq!(parent("/m/Movie/Sub/Movie-trailer.mkv", &suffix), Some("nested"));
+        assert_eq!(parent("/m/Other/trailer.mkv", &suffix), None);
+    }
+
+    /// (文件名, 字幕组, 剧名, 季数, 集数, 合集最后一集, 带有 CRC32 或分辨率标签)
+    type AnimeCase = (&'static str, &'static str, &'static str, Option<i32>, i32, i32, bool);
+
+    const ANIME_CASES: &[AnimeCase] = &[
+        ("[SubsPlease] Frieren - 17 (1080p) [ABCD1234].mkv", "SubsPlease", "Frieren", None, 17, 0, true),
+        ("[SubsPlease] One Piece - 1024 (1080p) [0A1B2C3D].mkv", "SubsPlease", "One Piece", None, 1024, 0, true),
+        ("[Erai-raws] Spy x Family Season 2 - 05v2 [1080p].mkv", "Erai-raws", "Spy x Family", Some(2), 5, 0, true),
+        ("[Group] Show S2 - 03 END [720p].mkv", "Group", "Show", Some(2), 3, 0, true),
+        ("[Judas] Made in Abyss (Season 1) [01-13] [1080p].mkv", "Judas", "Made in Abyss", None, 1, 13, true),
+        ("[Group] Show 2nd Season - 01~12 [BD 1080p].mkv", "Group", "Show", Some(2), 1, 12, true),
+        ("[字幕组][葬送的芙莉莲][17][1080p][简日双语].mp4", "字幕组", "葬送的芙莉莲", None, 17, 0, true),
+        ("[字幕组] 鬼灭之刃 第二季 [第05话][1080p].mp4", "字幕组", "鬼灭之刃", Some(2), 5, 0, true),
+        ("[Group]_Some_Show_-_08_[BD][ABCDEF12].mkv", "Group", "Some Show", None, 8, 0, true),
+        ("[Group] Show EP05 [1080p].mkv", "Group", "Show", None, 5, 0, true),
+        ("[字幕组][进击的巨人] - 25 [1080p].mp4", "字幕组", "进击的巨人", None, 25, 0, true),
+        ("Naruto Shippuden - 120.mkv", "", "Naruto Shippuden", None, 120, 0, false),
+    ];
+
+    /// 只以空格或圆括号分隔数字的电影名称，不是动画的集数
+    const ANIME_MOVIE_NAMES: &[&str] = &[
+        "[Group] Show [1080p].mkv",
+        "[Group] Movie (2019) [1080p].mkv",
+        "[1080p].mkv",
+        "[Group] Toy Story 3 [1080p].mkv",
+        "[Rarbg] Movie 2 (2010).mkv",
+        "[Group] Movie (2) [720p].mkv",
+    ];
+
+    #[test]
+    fn test_parse_anime_release() {
+        for &(file_name, group, title, season, episode, episode_end, tagged) in ANIME_CASES {
+            let expected = AnimeRelease { group: group.to_string(), title: title.to_string(), season, episode, episode_end, tagged };
+            assert_eq!(parse_anime_release(file_name), Some(expected), "{}", file_name);
+        }
+        for file_name in ANIME_MOVIE_NAMES {
+            assert_eq!(parse_anime_release(file_name), None, "{}", file_name);
+        }
+    }
+
+    #[test]
+    fn test_parse_series_info_anime() {
+        let info = parse_series_info("[SubsPlease] Frieren - 17 (1080p) [ABCD1234].mkv");
+        assert!(info.is_series && info.absolute);
+        assert_eq!((info.series_title.as_str(), info.season, info.episode), ("Frieren", 1, 17));
+
+        let info = parse_series_info("[Group] Show - 1024 [1080p].mkv");
+        assert!(info.is_series && info.absolute);
+        assert_eq!((info.series_title.as_str(), info.episode), ("Show", 1024));
+
+        // 字幕组标签中的 S01E05 按常规剧集解析
+        let info = parse_series_info("[Group] Show S01E05 [1080p].mkv");
+        assert!(info.is_series && !info.absolute);
+        assert_eq!((info.series_title.as_str(), info.season, info.episode), ("Show", 1, 5));
+
+        // 没有字幕组标签时只有带 CRC32 或分辨率标签的三、四位集数才是绝对集数
+        let info = parse_series_info("One Piece - 1024 [1080p].mkv");
+        assert!(info.is_series && info.absolute);
+        assert_eq!((info.series_title.as_str(), info.episode), ("One Piece", 1024));
+        let info = parse_series_info("Naruto Shippuden - 120 [ABCD1234].mkv");
+        assert!(info.is_series && info.absolute);
+        assert_eq!((info.series_title.as_str(), info.episode), ("Naruto Shippuden", 120));
+        let movies = ["Mission Impossible - 3.mkv", "Ocean's - 11.mkv", "Blade Runner - 2049.mkv", "Fahrenheit - 451.mkv", "Spartans - 300.mkv", "One Piece - 1024.mkv"];
+        for file_name in movies.iter().chain(ANIME_MOVIE_NAMES) {
+            assert!(!parse_series_info(file_name).is_series, "{}", file_name);
+        }
+    }
+
+    #[test]
+    fn test_map_absolute_episode() {
+        let seasons = [(1, 12), (2, 13), (3, 24)];
+        let cases = [
+            (1, Some((1, 1))),
+            (12, Some((1, 12))),
+            (13, Some((2, 1))),
+            (20, Some((2, 8))),
+            (25, Some((2, 13))),
+            (26, Some((3, 1))),
+            (49, Some((3, 24))),
+            (50, None),
+            (0, None),
+        ];
+        for (absolute, expected) in cases {
+            assert_eq!(map_absolute_episode(&seasons, absolute), expected, "{}", absolute);
+        }
+        assert_eq!(map_absolute_episode(&[], 1), None);
+    }
+
+    /// (文件名, 剧名, 季数, 集数, 最后一集, 播出日期)
+    type SeriesCase = (&'static str, &'static str, i32, i32, i32, &'static str);
+
+    const SERIES_CASES: &[SeriesCase] = &[
+        ("Breaking Bad S01E01 Pilot 1080p.mkv", "Breaking Bad", 1, 1, 0, ""),
+        ("Show.S01E01E02.1080p.mkv", "Show", 1, 1, 2, ""),
+        ("Show.S01E01-E03.mkv", "Show", 1, 1, 3, ""),
+        ("Show.S01E01-03.mkv", "Show", 1, 1, 3, ""),
+        ("Show.S01E05-720p.mkv", "Show", 1, 5, 0, ""),
+        ("Show.S01E05.2160p.mkv", "Show", 1, 5, 0, ""),
+        ("Show S10E100.mkv", "Show", 10, 100, 0, ""),
+        ("Show 1x05.mkv", "Show", 1, 5, 0, ""),
+        ("Show.2x10-2019.mkv", "Show", 2, 10, 0, ""),
+        ("The Daily Show 2024.03.15.mkv", "The Daily Show", 2024, 315, 0, "2024-03-15"),
+        ("第十二集.mp4", "", 1, 12, 0, ""),
+        ("庆余年第二季第三集.mp4", "庆余年", 2, 3, 0, ""),
+        ("庆余年 第01-02集.mp4", "庆余年", 1, 1, 2, ""),
+        ("[BD] 剧名 第1季 第5集.mkv", "剧名", 1, 5, 0, ""),
+        ("Show EP03.mkv", "Show", 1, 3, 0, ""),
+        ("Show.E01E02.mkv", "Show", 1, 1, 2, ""),
+    ];
+
+    #[test]
+    fn test_parse_series_info() {
+        for &(file_name, title, season, episode, episode_end, air_date) in SERIES_CASES {
+            let info = parse_series_info(file_name);
+            assert!(info.is_series && !info.absolute, "{}", file_name);
+            assert_eq!(
+                (info.series_title.as_str(), info.season, info.episode, info.episode_end, info.air_date.as_str()),
+                (title, season, episode, episode_end, air_date),
+                "{}", file_name
+            );
+        }
+        for file_name in ["Movie.2010.1080p.BluRay.x264.mkv", "Apollo 13 (1995).mkv", "Se7en.1995.mkv", "1917.2019.mkv", "Plain Title.mkv"] {
+            assert!(!parse_series_info(file_name).is_series, "{}", file_name);
+        }
+    }
+
+    #[test]
+    fn test_parse_episode_end() {
+        let cases = [
+            ("E02", 1, 2),
+            ("E02E03", 1, 3),
+            ("-E03", 1, 3),
+            (" - 03", 1, 3),
+            ("-720p", 5, 0),
+            ("-2019", 1, 0),
+            ("-04", 5, 0),
+            (".1080p", 1, 0),
+            ("", 1, 0),
+        ];
+        for (rest, episode, expected) in cases {
+            assert_eq!(parse_episode_end(rest, episode), expected, "{:?}", rest);
+        }
+    }
+
+    #[test]
+    fn test_parse_chinese_number() {
+        let cases = [
+            ("12", Some(12)),
+            ("十", Some(10)),
+            ("十二", Some(12)),
+            ("二十", Some(20)),
+            ("二十三", Some(23)),
+            ("两", Some(2)),
+            ("一百零五", Some(105)),
+            ("零", None),
+            ("", None),
+            ("十a", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_chinese_number(text), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_series_path() {
+        let dir = TempDir::new("series_path", &[
+            ("Breaking Bad (2008)/Season 2/02.mkv", Vec::new()),
+            ("Show/S02/Episode 05.mkv", Vec::new()),
+            ("Show/S02/Show S01E07.mkv", Vec::new()),
+            ("Show/Specials/01.mkv", Vec::new()),
+            ("庆余年/第二季/05.mkv", Vec::new()),
+            ("Marked Show (2020)/tvshow.nfo", Vec::new()),
+            ("Marked Show (2020)/Episode 3.mkv", Vec::new()),
+            ("Season 3/Firefly E04.mkv", Vec::new()),
+            ("One Piece/tvshow.nfo", Vec::new()),
+            ("One Piece/One Piece - 1024.mkv", Vec::new()),
+            ("Movies/Movie (2010).mkv", Vec::new()),
+            ("Movies/Spartans - 300.mkv", Vec::new()),
+        ]);
+        let root = Some(dir.0.as_path());
+        let folder = |file: &str| find_series_folder(&dir.0.join(file), root);
+        let series_folder = |title: &str, year: &str, season: Option<i32>| Some(SeriesFolder { title: title.to_string(), year: year.to_string(), season });
+
+        assert_eq!(folder("Breaking Bad (2008)/Season 2/02.mkv"), series_folder("Breaking Bad", "2008", Some(2)));
+        assert_eq!(folder("Show/Specials/01.mkv"), series_folder("Show", "", Some(0)));
+        assert_eq!(folder("庆余年/第二季/05.mkv"), series_folder("庆余年", "", Some(2)));
+        assert_eq!(folder("Marked Show (2020)/Episode 3.mkv"), series_folder("Marked Show", "2020", None));
+        // 季文件夹直接位于资料库根目录时没有剧名
+        assert_eq!(folder("Season 3/Firefly E04.mkv"), series_folder("", "", Some(3)));
+        assert_eq!(folder("Movies/Movie (2010).mkv"), None);
+        // 没有资料库根目录时季文件夹的上级就是剧集文件夹
+        assert_eq!(find_series_folder(&dir.0.join("Season 3/Firefly E04.mkv"), None).map(|folder| folder.season), Some(Some(3)));
+
+        // (文件, 剧名, 年份, 季数, 集数)
+        let cases = [
+            ("Breaking Bad (2008)/Season 2/02.mkv", "Breaking Bad", "2008", 2, 2),
+            ("Show/S02/Episode 05.mkv", "Show", "", 2, 5),
+            // 文件名中明确写出的季数优先
+            ("Show/S02/Show S01E07.mkv", "Show", "", 1, 7),
+            ("Show/Specials/01.mkv", "Show", "", 0, 1),
+            ("庆余年/第二季/05.mkv", "庆余年", "", 2, 5),
+            ("Marked Show (2020)/Episode 3.mkv", "Marked Show", "2020", 1, 3),
+            ("Season 3/Firefly E04.mkv", "Firefly", "", 3, 4),
+            // 剧集文件夹中没有标签的三、四位数字也是绝对集数
+            ("One Piece/One Piece - 1024.mkv", "One Piece", "", 1, 1024),
+        ];
+        for (file, title, year, season, episode) in cases {
+            let path = dir.0.join(file);
+            let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+            let info = parse_series_path(&path, &file_name, root);
+            assert!(info.is_series, "{}", file);
+            assert_eq!((info.series_title.as_str(), info.year.as_str(), info.season, info.episode), (title, year, season, episode), "{}", file);
+        }
+        assert!(!parse_series_path(&dir.0.join("Movies/Movie (2010).mkv"), "Movie (2010).mkv", root).is_series);
+        assert!(!parse_series_path(&dir.0.join("Movies/Spartans - 300.mkv"), "Spartans - 300.mkv", root).is_series);
+    }
+}
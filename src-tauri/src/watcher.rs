@@ -0,0 +1,227 @@
+//! 资料库目录监听
+//!
+//! 文件系统事件（Linux 上为 inotify）经过防抖合并后，交给与扫描文件夹相同的流程处理，
+//! 处理结果通过 Tauri 事件通知前端，视频列表无需重新扫描即可实时更新。
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap}, path::{Path, PathBuf}, sync::{Arc, Mutex}, time::Duration
+};
+
+use notify_debouncer_mini::{
+    new_debouncer, notify::{RecommendedWatcher, RecursiveMode}, DebounceEventResult, Debouncer
+};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+
+use crate::{db::{self, DbState}, disc, emit_event, scan_filter::ScanFilter, video, AppState, ScanOutcome};
+use crate::{log_error, log_info};
+
+/// 同一路径在该时间内的连续事件合并为一次，避免复制大文件时反复解析
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 新视频入库，内容为 `VideoInfo`
+pub const EVENT_VIDEO_ADDED: &str = "video-added";
+/// 视频文件变化后重新解析，或多段视频分组变化，内容为 `VideoInfo`
+pub const EVENT_VIDEO_UPDATED: &str = "video-updated";
+/// 视频被移动或改名，内容为 `MovedVideo`
+pub const EVENT_VIDEO_MOVED: &str = "video-moved";
+/// 文件被识别为附加内容，内容为 `VideoExtra`，以前被当作视频入库的同一文件已删除
+pub const EVENT_VIDEO_EXTRA: &str = "video-extra";
+/// 视频文件被删除或所在磁盘离线，内容为 `VideoStatus`
+pub const EVENT_VIDEO_STATUS: &str = "video-status";
+
+/// 视频文件状态变化
+#[derive(Serialize, Clone)]
+struct VideoStatus {
+    id: String,
+    status: String,
+}
+
+/// 资料库监听器，所有资料库根目录共用一个防抖监听器
+pub struct LibraryWatcher {
+    debouncer: Mutex<Debouncer<RecommendedWatcher>>,
+}
+
+impl LibraryWatcher {
+    /// 创建监听器，并启动处理文件变化的后台任务
+    ///
+    /// # 参数
+    /// * `app_handle` - Tauri应用句柄，用于获取数据库、设置和发送事件
+    ///
+    /// # 返回
+    /// * `Result<LibraryWatcher, String>` - 成功返回监听器，系统不支持监听时返回错误
+    pub fn new(app_handle: AppHandle) -> Result<Self, String> {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Vec<PathBuf>>();
+        let debouncer = new_debouncer(DEBOUNCE_TIMEOUT, move |result: DebounceEventResult| match result {
+            Ok(events) => {
+                let _ = tx.send(events.into_iter().map(|event| event.path).collect());
+            }
+            Err(e) => log_error!("Library watcher error: {}", e),
+        }).map_err(|e| e.to_string())?;
+
+        tauri::async_runtime::spawn(async move {
+            while let Some(paths) = rx.recv().await {
+                // 处理上一批变化时积压的事件一并处理
+                let mut paths: BTreeSet<PathBuf> = paths.into_iter().collect();
+                while let Ok(more) = rx.try_recv() {
+                    paths.extend(more);
+                }
+                handle_changes(&app_handle, paths).await;
+            }
+        });
+
+        Ok(LibraryWatcher { debouncer: Mutex::new(debouncer) })
+    }
+
+    /// 开始监听目录及其子目录
+    pub fn watch(&self, path: &Path) -> Result<(), String> {
+        let mut debouncer = self.debouncer.lock().map_err(|_| "Failed to acquire watcher lock".to_string())?;
+        debouncer.watcher().watch(path, RecursiveMode::Recursive).map_err(|e| e.to_string())?;
+        log_info!("Watching library: {}", path.display());
+        Ok(())
+    }
+
+    /// 停止监听目录
+    pub fn unwatch(&self, path: &Path) -> Result<(), String> {
+        let mut debouncer = self.debouncer.lock().map_err(|_| "Failed to acquire watcher lock".to_string())?;
+        debouncer.watcher().unwatch(path).map_err(|e| e.to_string())?;
+        log_info!("Stopped watching library: {}", path.display());
+        Ok(())
+    }
+}
+
+/// 处理一批发生变化的路径：仍然存在的视频文件（或目录中的视频文件）按扫描流程入库或刷新，
+/// 已不存在的路径更新其中视频的文件状态。
+///
+/// 先处理存在的文件，移动或改名的视频会通过内容指纹重新关联，不会被标记为已删除。
+async fn handle_changes(app_handle: &AppHandle, paths: BTreeSet<PathBuf>) {
+    let db = app_handle.state::<DbState>().0.clone();
+    let settings = app_handle.state::<AppState>().settings.lock().unwrap().clone();
+    let extensions = crate::video_extensions(&settings);
+    let cover_dir = app_handle.path().app_cache_dir().ok().map(|dir| dir.join("covers"));
+    let libraries = match db.lock().map(|conn| db::get_all_libraries(&conn)) {
+        Ok(Ok(libraries)) => libraries,
+        _ => {
+            log_error!("Failed to load libraries");
+            return;
+        }
+    };
+
+    // 每个资料库的扫描规则只编译一次
+    let mut filters: HashMap<i64, Option<ScanFilter>> = HashMap::new();
+    let mut files = BTreeMap::new();
+    let mut removed = Vec::new();
+    for path in paths {
+        if !path.exists() {
+            removed.push(path);
+            continue;
+        }
+        // 停用后仍可能收到排队中的事件
+        let library = match crate::library_for_path(&libraries, &path) {
+            Some(library) if library.enabled => library,
+            _ => continue,
+        };
+        let filter = filters.entry(library.id).or_insert_with(|| {
+            ScanFilter::new(Path::new(&library.path), &library.rules, &extensions)
+                .map_err(|e| log_error!("Invalid scan rules for library {}: {}", library.path, e))
+                .ok()
+        });
+        let Some(filter) = filter else { continue };
+        // 光盘文件夹中的文件变化按整个光盘处理
+        let path = disc::disc_root(&path, Path::new(&library.path)).map_or(path.clone(), Path::to_path_buf);
+        if !filter.accepts_path(&path) {
+            continue;
+        }
+        if path.is_dir() {
+            // 整个目录被移入或复制进来时只会收到目录本身的事件
+            files.extend(filter.walk(&path).map(|file| (file, library)));
+        } else {
+            files.insert(path, library);
+        }
+    }
+
+    let mut dirs = BTreeSet::new();
+    for (path, library) in files {
+        // 文件在写入过程中也会触发事件，按重新扫描处理，写入完成后会刷新为最终的文件信息
+        match crate::process_video_file(path.clone(), true, db.clone(), &settings, Some(library), cover_dir.as_deref()).await {
+            Ok(ScanOutcome::Added(video)) => emit_event(app_handle, EVENT_VIDEO_ADDED, video),
+            Ok(ScanOutcome::Updated(video)) => emit_event(app_handle, EVENT_VIDEO_UPDATED, video),
+            Ok(ScanOutcome::Moved(moved)) => emit_event(app_handle, EVENT_VIDEO_MOVED, moved),
+            Ok(ScanOutcome::Extra(extra)) => emit_event(app_handle, EVENT_VIDEO_EXTRA, extra),
+            Ok(ScanOutcome::Unchanged) => continue,
+            Err(e) => {
+                log_error!("Failed to process video {}: {}", path.display(), e);
+                continue;
+            }
+        }
+        dirs.extend(path.parent().map(Path::to_path_buf));
+    }
+
+    // 新增或移动的文件可能与同目录的文件组成多段视频，或是同目录视频的附加内容
+    if !dirs.is_empty() {
+        let db = db.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+            let mut regrouped = Vec::new();
+            for dir in dirs {
+                let dir = dir.to_string_lossy();
+                regrouped.extend(crate::regroup_video_parts(&mut conn, &dir)?);
+                crate::link_video_extras(&mut conn, &dir)?;
+            }
+            Ok::<_, String>(regrouped)
+        }).await;
+        match result {
+            Ok(Ok(regrouped)) => {
+                for video in regrouped {
+                    emit_event(app_handle, EVENT_VIDEO_UPDATED, video);
+                }
+            }
+            Ok(Err(e)) => log_error!("Failed to group multi-part videos and extras: {}", e),
+            Err(e) => log_error!("Failed to group multi-part videos and extras: {}", e),
+        }
+    }
+
+    if !removed.is_empty() {
+        let result = tokio::task::spawn_blocking(move || update_removed(&db, &removed)).await;
+        match result {
+            Ok(Ok(statuses)) => {
+                for (id, status) in statuses {
+                    emit_event(app_handle, EVENT_VIDEO_STATUS, VideoStatus { id, status });
+                }
+            }
+            Ok(Err(e)) => log_error!("Failed to update removed videos: {}", e),
+            Err(e) => log_error!("Failed to update removed videos: {}", e),
+        }
+    }
+}
+
+/// 更新已不存在的路径（文件或目录）中视频的文件状态并删除其中的附加内容，返回状态发生变化的 (视频ID, 文件状态)
+fn update_removed(db: &Arc<Mutex<rusqlite::Connection>>, removed: &[PathBuf]) -> Result<Vec<(String, String)>, String> {
+    let mut conn = db.lock().map_err(|_| "Failed to acquire database lock".to_string())?;
+    let mut changed = Vec::new();
+    for path in removed {
+        let path_str = path.to_string_lossy();
+        let id = format!("{:x}", md5::compute(path_str.as_bytes()));
+        let mut videos = db::get_videos_under_path(&conn, &path_str).map_err(|e| e.to_string())?;
+        videos.extend(db::get_video(&conn, &id).map_err(|e| e.to_string())?);
+
+        for video in videos {
+            let status = video::file_status(Path::new(&video.path));
+            if video.status != status {
+                changed.push((video.id, status.to_string()));
+            }
+        }
+
+        // 附加内容不记录文件状态，文件被删除时直接删除
+        let mut extras = db::get_extras_under_path(&conn, &path_str).map_err(|e| e.to_string())?;
+        extras.extend(db::get_video_extra(&conn, &id).map_err(|e| e.to_string())?);
+        for extra in extras {
+            if video::file_status(Path::new(&extra.path)) == db::STATUS_MISSING {
+                db::delete_video_extra(&conn, &extra.id).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    db::update_video_statuses(&mut conn, &changed).map_err(|e| e.to_string())?;
+    Ok(changed)
+}
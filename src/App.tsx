@@ -1,23 +1,31 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import "./App.css";
-import { VideoInfo } from "./types";
+import { AddedLibrary, HealthReport, Library, ScanReport, VideoExtra, VideoInfo, VideoQuality, VideoRelease } from "./types";
+import { videocommon } from "./utils";
 import VideoCard from "./components/video-card/VideoCard";
 import VideoDetail from "./components/video-detail/VideoDetail";
 import SettingsPage from "./pages/Settings";
 import simpleAlert from "./components/simplealert";
 import Modal from "./components/modal/Modal";
-import Loading from "./components/loading/Loading";
+import ScanProgress from "./components/scan-progress/ScanProgress";
 
 function App() {
   const [leftWidth, setLeftWidth] = useState(200); // 左侧初始宽度
   const [isResizing, setIsResizing] = useState(false); // 是否正在拖动
-  const [isLoading, setIsLoading] = useState(false);
   const [videos, setVideos] = useState<VideoInfo[]>([]);
   const [filter, setFilter] = useState<string>("all"); // 当前过滤条件
   const [selectedVideo, setSelectedVideo] = useState<VideoInfo>();
   const [isShowModal, setIsShowModal] = useState(false);
   const [currentPage, setCurrentPage] = useState<string>("home"); // 当前页面
+  const [qualities, setQualities] = useState<Record<string, VideoQuality>>({}); // 视频ID -> 画质概况
+  const [qualityFilter, setQualityFilter] = useState<string>("all"); // 画质筛选条件
+  const [releases, setReleases] = useState<Record<string, VideoRelease>>({}); // 视频ID -> 发布信息
+  const [sourceFilter, setSourceFilter] = useState<string>("all"); // 片源筛选条件
+  const [sortOrder, setSortOrder] = useState<string>("default"); // 排序方式
+  const [libraries, setLibraries] = useState<Library[]>([]);
+  const [libraryFilter, setLibraryFilter] = useState<number>(0); // 资料库筛选条件，0 为全部
 
   // 开始拖动
   const handleMouseDown = () => {
@@ -59,12 +67,87 @@ function App() {
     setLeftWidth(200); // 恢复默认宽度
   };
   
+  /**
+   * 加载所有视频的画质概况
+   */
+  const fetchQualities = async () => {
+    try {
+      const list = await invoke<VideoQuality[]>('get_video_qualities');
+      setQualities(Object.fromEntries(list.map(quality => [quality.video_id, quality])));
+    } catch (error) {
+      console.error('Error loading video qualities:', error);
+    }
+  };
+
+  /**
+   * 加载所有视频的发布信息
+   */
+  const fetchReleases = async () => {
+    try {
+      const list = await invoke<VideoRelease[]>('get_video_releases');
+      setReleases(Object.fromEntries(list.map(release => [release.video_id, release])));
+    } catch (error) {
+      console.error('Error loading video releases:', error);
+    }
+  };
+
+  useEffect(() => {
+    fetchQualities();
+    fetchReleases();
+  }, []);
+
+  /**
+   * 加载资料库列表
+   */
+  const fetchLibraries = async () => {
+    try {
+      setLibraries(await invoke<Library[]>('list_libraries'));
+    } catch (error) {
+      console.error('Error loading libraries:', error);
+    }
+  };
+
+  useEffect(() => {
+    fetchLibraries();
+  }, []);
+
+  /**
+   * 检查所有视频文件并更新状态
+   * @param showReport 是否提示检查结果，存在已删除的文件时询问是否清理
+   */
+  const verifyVideos = async (showReport: boolean) => {
+    try {
+      const report = await invoke<HealthReport>('get_health_report');
+      const unavailable = new Map([...report.missing, ...report.offline].map(video => [video.id, video.status]));
+      setVideos(prev => prev.map(video => ({ ...video, status: unavailable.get(video.id) ?? 'ok' })));
+      if (!showReport) return;
+      if (report.missing.length === 0) {
+        simpleAlert.success(`共 ${report.total} 个视频，正常：${report.ok}，离线：${report.offline.length}`, { duration: 5000 });
+        return;
+      }
+      simpleAlert.confirm(`共 ${report.total} 个视频，正常：${report.ok}，离线：${report.offline.length}<br/>文件已删除：${report.missing.length}，是否从资料库中清理？`,
+        null,
+        async (confirmed) => {
+          if (!confirmed) return;
+          const purged = await invoke<string[]>('purge_missing_videos');
+          setVideos(prev => prev.filter(video => !purged.includes(video.id)));
+          simpleAlert.success(`已清理：${purged.length}`, { duration: 5000 });
+        }
+      );
+    } catch (error) {
+      console.error('Error verifying videos:', error);
+      if (showReport) simpleAlert.error('检查视频文件时出错：' + error);
+    }
+  };
+
   useEffect(() => {
     const fetchCachedVideos = async () => {
       try {
         // 尝试加载缓存的视频
         const cachedVideos = await invoke<VideoInfo[]>('get_cached_videos');
         setVideos(cachedVideos);
+        // 后台检查文件是否仍然存在
+        verifyVideos(false);
       } catch (error) {
         console.error('Error loading cached videos:', error);
         simpleAlert.error(error as string);
@@ -77,40 +160,140 @@ function App() {
     fetchCachedVideos();
   }, []);
 
+  useEffect(() => {
+    // 资料库目录中的文件变化由后台监听，收到事件后直接更新列表
+    const unlisteners = [
+      listen<VideoInfo>('video-added', ({ payload }) => {
+        setVideos(prev => [...prev.filter(video => video.id !== payload.id), payload]);
+        fetchQualities();
+        fetchReleases();
+      }),
+      listen<VideoInfo>('video-updated', ({ payload }) => {
+        setVideos(prev => {
+          // 并入多段视频的段不再单独显示，恢复为独立视频的段重新显示
+          if (payload.part_of) return prev.filter(video => video.id !== payload.id);
+          if (!prev.some(video => video.id === payload.id)) return [...prev, payload];
+          return prev.map(video => video.id === payload.id ? payload : video);
+        });
+        fetchQualities();
+        fetchReleases();
+      }),
+      listen<{ old_id: string; video: VideoInfo }>('video-moved', ({ payload }) => {
+        setVideos(prev => prev.map(video => video.id === payload.old_id ? payload.video : video));
+        fetchReleases();
+      }),
+      listen<VideoExtra>('video-extra', ({ payload }) => {
+        // 以前被当作视频入库的附加内容不再单独显示
+        setVideos(prev => prev.filter(video => video.id !== payload.id));
+      }),
+      listen<{ id: string; status: VideoInfo['status'] }>('video-status', ({ payload }) => {
+        setVideos(prev => prev.map(video => video.id === payload.id ? { ...video, status: payload.status } : video));
+      }),
+    ];
+    return () => {
+      unlisteners.forEach(unlisten => unlisten.then(fn => fn()));
+    };
+  }, []);
+
   /**
    * 过滤视频列表
    * @param keyword 过滤视频关键字
    * @returns 
    */
   const getVideos = (keyword: string): VideoInfo[] => {
-    let tempVideos:VideoInfo[] = videos;
+    let tempVideos:VideoInfo[] = videos.filter(video => videocommon.matchQuality(qualities[video.id], qualityFilter));
+    if (sourceFilter !== 'all') {
+      tempVideos = tempVideos.filter(video => releases[video.id]?.source === sourceFilter);
+    }
+    if (libraryFilter !== 0) {
+      tempVideos = tempVideos.filter(video => video.library_id === libraryFilter);
+    }
     if (keyword === 'tv') {
-      return tempVideos.filter(video => video.is_series);
+      tempVideos = tempVideos.filter(video => video.is_series);
     }
     if (keyword === 'mv') {
-      return tempVideos.filter(video => !video.is_series);
+      tempVideos = tempVideos.filter(video => !video.is_series);
     }
     if (keyword === 'played') {
-      return tempVideos.filter(video => video.play_count > 0).sort((a, b) => b.last_play_time - a.last_play_time);
+      tempVideos = tempVideos.filter(video => video.play_count > 0).sort((a, b) => b.last_play_time - a.last_play_time);
+    }
+    if (sortOrder === 'quality') {
+      // 同一画质的多个版本按片源排序，便于挑选保留的版本
+      tempVideos = [...tempVideos].sort((a, b) =>
+        videocommon.qualityRank(qualities[b.id]) - videocommon.qualityRank(qualities[a.id])
+        || videocommon.releaseRank(releases[b.id]) - videocommon.releaseRank(releases[a.id]));
     }
-    return videos;
+    return tempVideos;
   }
 
-  const handleScanFoldersClick = async () => {
-    console.log('扫描文件夹');
-    setIsLoading(true); // 显示 Loading
+  /**
+   * 扫描文件夹
+   * @param rescan 是否重新解析已入库但文件有变化的视频
+   */
+  const handleScanFoldersClick = async (rescan: boolean) => {
+    console.log(rescan ? '重新扫描文件夹' : '扫描文件夹');
     try {
-      let tempVideos: VideoInfo[] = await invoke<VideoInfo[]>('select_and_scan_folder');
-      if (tempVideos.length === 0) {
-        return;
-      }
-      simpleAlert.success(`已添加：${tempVideos.length}!`, { duration: 5000 });
-      setVideos([...videos, ...tempVideos]);
+      const report = await invoke<ScanReport>('select_and_scan_folder', { rescan });
+      applyScanReport(report);
     } catch (error) {
       console.error('Error scanning folder:', error);
       simpleAlert.error('扫描文件夹时出错：' + error);
-    } finally {
-      setIsLoading(false); // 操作完成后隐藏 Loading
+    }
+  };
+
+  /**
+   * 提示扫描结果并更新视频列表
+   */
+  const applyScanReport = (report: ScanReport) => {
+    if (report.cancelled) {
+      simpleAlert.success('扫描已取消，已处理的视频已保留', { duration: 5000 });
+    }
+    if (report.extras.length > 0) {
+      const extras = new Set(report.extras.map(extra => extra.id));
+      setVideos(prev => prev.filter(video => !extras.has(video.id)));
+    }
+    if (report.added.length === 0 && report.updated.length === 0 && report.moved.length === 0) {
+      if (report.unchanged > 0) {
+        simpleAlert.success(`没有新增或变化的视频，未变化：${report.unchanged}`, { duration: 5000 });
+      }
+      return;
+    }
+    simpleAlert.success(`新增：${report.added.length}，更新：${report.updated.length}，移动：${report.moved.length}，附加内容：${report.extras.length}，未变化：${report.unchanged}`, { duration: 5000 });
+    // 移动的视频沿用原记录，按原ID替换为新路径的记录
+    const updated = new Map([
+      ...report.updated.map(video => [video.id, video] as const),
+      ...report.moved.map(({ old_id, video }) => [old_id, video] as const),
+    ]);
+    const added = new Set(report.added.map(video => video.id));
+    // 更新中包含新识别的多段视频，其他段不单独显示
+    const reappeared = report.updated.filter(video => !video.part_of);
+    setVideos(prev => {
+      const known = new Set(prev.map(video => video.id));
+      return [
+        ...prev.filter(video => !added.has(video.id)).map(video => updated.get(video.id) ?? video),
+        ...report.added,
+        ...reappeared.filter(video => !known.has(video.id) && !added.has(video.id)),
+      ].filter(video => !video.part_of);
+    });
+    fetchQualities();
+    fetchReleases();
+  };
+
+  /**
+   * 添加资料库：扫描选择的文件夹，之后其中的变化会自动同步
+   */
+  const handleAddLibraryClick = async () => {
+    try {
+      const added = await invoke<AddedLibrary | null>('add_library');
+      if (added) {
+        applyScanReport(added.report);
+        // 添加前已扫描过的视频也归入了新资料库
+        setVideos(await invoke<VideoInfo[]>('get_cached_videos'));
+        fetchLibraries();
+      }
+    } catch (error) {
+      console.error('Error adding library:', error);
+      simpleAlert.error('添加资料库时出错：' + error);
     }
   };
 
@@ -123,7 +306,7 @@ function App() {
   const handleDeleteVideo = async (video: VideoInfo) => {
     try {
       const title = video.is_series 
-      ? `${video.title} S${video.season.toString().padStart(2, '0')}E${video.episode.toString().padStart(2, '0')} ${video.episode_title}`
+      ? `${video.title} ${videocommon.episodeLabel(video)} ${video.episode_title}`
       : video.title || video.original_title;
       simpleAlert.confirm('您确定要删除这个项目吗？'+`<br/>${title}`, 
         `<div style="font-size:16px;"><input type="checkbox"> 同时删除文件</div>`, 
@@ -175,7 +358,9 @@ function App() {
               <li><a href="#" className={`recently-played-button ${filter === 'played' ? 'active' : null}`} onClick={() => handleCurrentPage("played")}>最近播放</a></li>
               <li><a href="#" className={`mv-button ${filter === 'mv' ? 'active' : null}`} onClick={() => handleCurrentPage("mv")}>电影</a></li>
               <li><a href="#" className={`tv-button ${filter === 'tv' ? 'active' : null}`} onClick={() => handleCurrentPage("tv")}>剧集</a></li>
-              <li><a href="#" className="scan-button" onClick={handleScanFoldersClick}>扫描文件夹</a></li>
+              <li><a href="#" className="scan-button" onClick={handleAddLibraryClick}>添加资料库</a></li>
+              <li><a href="#" className="scan-button" onClick={() => handleScanFoldersClick(false)}>扫描文件夹</a></li>
+              <li><a href="#" className="scan-button" onClick={() => handleScanFoldersClick(true)}>重新扫描</a></li>
               <li><a href="#" className={`settings-button ${currentPage === 'Settings' ? 'active' : null}`} onClick={handleSettingsClick}>设置</a></li>
             </ul>
           </nav>
@@ -184,8 +369,46 @@ function App() {
           onDoubleClick={handleDoubleClick}
           onMouseDown={handleMouseDown} style={{ cursor: "ew-resize", left: `${leftWidth}px` }}></div>
         <div className="content">
+          <ScanProgress />
           {
-            isLoading && <Loading duration={30000} />
+            currentPage === "home" &&
+            <div className="quality-toolbar">
+              <select value={qualityFilter} onChange={(e) => setQualityFilter(e.target.value)}>
+                <option value="all">全部画质</option>
+                <option value="2160p">2160p</option>
+                <option value="1440p">1440p</option>
+                <option value="1080p">1080p</option>
+                <option value="720p">720p</option>
+                <option value="sd">标清</option>
+                <option value="hdr">HDR</option>
+                <option value="dv">Dolby Vision</option>
+                <option value="10bit">10 位及以上</option>
+              </select>
+              <select value={sourceFilter} onChange={(e) => setSourceFilter(e.target.value)}>
+                <option value="all">全部片源</option>
+                <option value="Remux">Remux</option>
+                <option value="BluRay">BluRay</option>
+                <option value="WEB-DL">WEB-DL</option>
+                <option value="WEBRip">WEBRip</option>
+                <option value="HDTV">HDTV</option>
+                <option value="DVD">DVD</option>
+                <option value="CAM">CAM</option>
+              </select>
+              {
+                libraries.length > 0 &&
+                <select value={libraryFilter} onChange={(e) => setLibraryFilter(Number(e.target.value))}>
+                  <option value={0}>全部资料库</option>
+                  {libraries.map(library => (
+                    <option key={library.id} value={library.id}>{library.name || library.path}</option>
+                  ))}
+                </select>
+              }
+              <select value={sortOrder} onChange={(e) => setSortOrder(e.target.value)}>
+                <option value="default">默认排序</option>
+                <option value="quality">按画质排序</option>
+              </select>
+              <button onClick={() => verifyVideos(true)}>检查文件</button>
+            </div>
           }
           {
             currentPage === "home" && 
@@ -200,12 +423,12 @@ function App() {
                 ))
               }
               <Modal isOpen={isShowModal} onClose={handleCloseModal}>
-                {selectedVideo && <VideoDetail data={selectedVideo} />}
+                {selectedVideo && <VideoDetail data={selectedVideo} release={releases[selectedVideo.id]} />}
               </Modal>
             </div>
           }
           {
-            currentPage === "Settings" && <SettingsPage />
+            currentPage === "Settings" && <SettingsPage onLibrariesChange={setLibraries} onLibraryScanned={applyScanReport} />
           }
         </div>
     </main>
@@ -0,0 +1,65 @@
+import { useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { ScanError, ScanProgress as Progress } from "../../types";
+import "./ScanProgress.css";
+
+/**
+ * 扫描进度面板：收到扫描进度事件时显示已发现 / 已处理的文件数和当前文件，可以取消扫描，扫描结束后自动隐藏
+ */
+const ScanProgress = () => {
+  const [progress, setProgress] = useState<Progress | null>(null);
+  const [errors, setErrors] = useState<ScanError[]>([]);
+  const [cancelling, setCancelling] = useState(false);
+  const scanning = useRef(false);
+
+  useEffect(() => {
+    const unlisteners = [
+      listen<Progress>('scan-progress', ({ payload }) => {
+        // 最后一个进度事件查找和处理都已结束
+        const finished = payload.discovery_done && !payload.current;
+        if (!scanning.current && !finished) {
+          // 新的扫描开始，清空上一次的失败记录
+          setErrors([]);
+          setCancelling(false);
+        }
+        scanning.current = !finished;
+        setProgress(finished ? null : payload);
+      }),
+      listen<ScanError>('scan-error', ({ payload }) => setErrors(prev => [...prev, payload])),
+    ];
+    return () => {
+      unlisteners.forEach(unlisten => unlisten.then(fn => fn()));
+    };
+  }, []);
+
+  if (!progress) return null;
+
+  const handleCancel = async () => {
+    setCancelling(true);
+    await invoke('cancel_scan');
+  };
+
+  const total = progress.discovered;
+  const percent = progress.discovery_done && total > 0 ? Math.round(progress.processed / total * 100) : 0;
+
+  return (
+    <div className="scan-progress">
+      <div className="scan-progress-header">
+        <span>
+          {!progress.discovery_done
+            ? `正在查找视频文件… 已发现 ${total} 个`
+            : `正在处理 ${progress.processed} / ${total}`}
+          {errors.length > 0 && <span className="scan-progress-errors" title={errors.map(e => `${e.path}: ${e.error}`).join('\n')}>，失败 {errors.length} 个</span>}
+        </span>
+        <button onClick={handleCancel} disabled={cancelling}>{cancelling ? '正在取消…' : '取消扫描'}</button>
+      </div>
+      <div className="scan-progress-bar">
+        <div className={`scan-progress-fill ${progress.discovery_done ? '' : 'indeterminate'}`} style={{ width: progress.discovery_done ? `${percent}%` : undefined }} />
+      </div>
+      {progress.current && <div className="scan-progress-current" title={progress.current}>{progress.current}</div>}
+    </div>
+  );
+};
+
+export default ScanProgress;
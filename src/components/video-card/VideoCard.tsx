@@ -12,7 +12,7 @@ interface CardProps {
 const VideoCard = (props: CardProps) => {
   const video = props.data;
   const title = video.is_series 
-    ? `${video.title} S${video.season.toString().padStart(2, '0')}E${video.episode.toString().padStart(2, '0')}`
+    ? `${video.title} ${videocommon.episodeLabel(video)}`
     : video.title || video.original_title;
 
   const handleDeleteClick = async () => {
@@ -24,9 +24,11 @@ const VideoCard = (props: CardProps) => {
   }
 
   return (
-    <div className="video-card" onClick={props.onClick}>
+    <div className={`video-card ${video.status && video.status !== 'ok' ? 'unavailable' : ''}`} onClick={props.onClick}>
       <div className="video-thumbnail">
           <img src={videocommon.convertFileSrc(video.thumbnail)} alt={title} />
+          {video.status === 'missing' && <span className="video-status">文件已删除</span>}
+          {video.status === 'offline' && <span className="video-status">离线</span>}
           <div className="card-play-button" onClick={(e) => { e.stopPropagation(); handlePlayVideo(); }} />
       </div>
       <div className="video-info">
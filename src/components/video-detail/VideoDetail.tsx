@@ -1,19 +1,72 @@
-import { VideoInfo } from '../../types';
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { VideoChapter, VideoExtra, VideoInfo, VideoRelease, VideoTrack } from '../../types';
 import './VideoDetail.css';
 import { videocommon } from '../../utils';
+import simpleAlert from '../simplealert';
+
+const EXTRA_TYPE_LABELS: Record<VideoExtra['extra_type'], string> = {
+  trailer: '预告片',
+  featurette: '特辑',
+  behindthescenes: '幕后花絮',
+  deleted: '删减片段',
+  interview: '访谈',
+  scene: '片段',
+  short: '短片',
+  other: '其他'
+};
 
 interface CardProps {
   data: VideoInfo | undefined,
+  release?: VideoRelease,
 }
 
 const VideoDetail = (props: CardProps) => {
   const video = props.data;
+  const [tracks, setTracks] = useState<VideoTrack[]>([]);
+  const [chapters, setChapters] = useState<VideoChapter[]>([]);
+  const [extras, setExtras] = useState<VideoExtra[]>([]);
+
+  useEffect(() => {
+    if (!video) return;
+    invoke<VideoTrack[]>('get_video_tracks', { id: video.id, path: video.path })
+      .then(setTracks)
+      .catch((error) => {
+        console.error('Error loading video tracks:', error);
+        setTracks([]);
+      });
+    invoke<VideoChapter[]>('get_video_chapters', { id: video.id, path: video.path })
+      .then(setChapters)
+      .catch((error) => {
+        console.error('Error loading video chapters:', error);
+        setChapters([]);
+      });
+    invoke<VideoExtra[]>('get_video_extras', { id: video.id })
+      .then(setExtras)
+      .catch((error) => {
+        console.error('Error loading video extras:', error);
+        setExtras([]);
+      });
+  }, [video?.id]);
+
   if (!video) return;
   
   const handlePlayVideo = async () => {
     await videocommon.handlePlayVideo(video);
   }
 
+  const handlePlayChapter = async (chapter: VideoChapter) => {
+    await videocommon.handlePlayVideo(video, chapter.start_time);
+  }
+
+  const handlePlayExtra = async (extra: VideoExtra) => {
+    try {
+      await invoke('play_extra', { extra });
+    } catch (error) {
+      simpleAlert.error('播放失败：' + error);
+    }
+  }
+
   return (
     <div className="video-details">
       <img src={videocommon.convertFileSrc(video.thumbnail)} style={{ width: '200px', float: 'left', marginRight: '20px', borderRadius: '5px' }} />
@@ -21,6 +74,24 @@ const VideoDetail = (props: CardProps) => {
       <p>
         <strong>时长：</strong>{video.duration}
       </p>
+      {
+        video.parts.length > 1 &&
+        <p>
+          <strong>分段：</strong>{video.parts.map(part => `${part.part_number}. ${part.duration}`).join('，')}
+        </p>
+      }
+      {
+        tracks.length > 0 &&
+        <p>
+          <strong>媒体信息：</strong>{videocommon.formatTrackSummary(tracks)}
+        </p>
+      }
+      {
+        props.release && videocommon.formatRelease(props.release) &&
+        <p>
+          <strong>发布信息：</strong>{videocommon.formatRelease(props.release)}
+        </p>
+      }
       <p>
         <strong>分类：</strong>{video.category}
       </p>
@@ -31,6 +102,35 @@ const VideoDetail = (props: CardProps) => {
         <strong>描述：</strong>{video.is_series ? video.episode_overview : video.description}
       </p>
       <button className="play-button" onClick={handlePlayVideo}>播放视频</button>
+      {
+        chapters.length > 0 &&
+        <div className="chapter-list">
+          <strong>章节：</strong>
+          <ul>
+            {chapters.map((chapter) => (
+              <li key={chapter.chapter_index} onClick={() => handlePlayChapter(chapter)}>
+                <span className="chapter-time">{videocommon.formatChapterTime(chapter.start_time)}</span>
+                {chapter.titles['chi'] || chapter.titles['zh'] || chapter.title}
+              </li>
+            ))}
+          </ul>
+        </div>
+      }
+      {
+        extras.length > 0 &&
+        <div className="chapter-list">
+          <strong>附加内容：</strong>
+          <ul>
+            {extras.map((extra) => (
+              <li key={extra.id} onClick={() => handlePlayExtra(extra)}>
+                <span className="chapter-time">{EXTRA_TYPE_LABELS[extra.extra_type] ?? extra.extra_type}</span>
+                {extra.title}
+                <span className="chapter-time"> {extra.duration}</span>
+              </li>
+            ))}
+          </ul>
+        </div>
+      }
     </div>
   );
 };
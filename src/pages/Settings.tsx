@@ -1,6 +1,6 @@
 import { useState, useEffect, memo, useCallback } from 'react';
 import './Settings.css';
-import { Settings } from '../types';
+import { Library, ScanReport, ScanRules, Settings } from '../types';
 import { invoke } from '@tauri-apps/api/core';
 import simpleAlert from '../components/simplealert';
 
@@ -24,6 +24,31 @@ const PLAYER_OPTIONS = [
   { value: 'system', label: '系统默认' }
 ];
 
+const CONTENT_TYPE_OPTIONS = [
+  { value: 'mixed', label: '混合' },
+  { value: 'movie', label: '电影' },
+  { value: 'series', label: '剧集' }
+];
+
+const METADATA_LANGUAGE_OPTIONS = [
+  { value: '', label: '默认 (zh-CN)' },
+  { value: 'zh-CN', label: '简体中文' },
+  { value: 'zh-TW', label: '繁体中文' },
+  { value: 'en-US', label: '英语' },
+  { value: 'ja-JP', label: '日语' },
+  { value: 'ko-KR', label: '韩语' },
+  { value: 'fr-FR', label: '法语' },
+  { value: 'de-DE', label: '德语' },
+  { value: 'es-ES', label: '西班牙语' },
+  { value: 'ru-RU', label: '俄语' },
+  { value: 'pt-BR', label: '葡萄牙语' }
+];
+
+const MB = 1024 * 1024;
+
+/** 按行拆分规则中的模式 */
+const parsePatterns = (text: string) => text.split('\n').map(line => line.trim()).filter(Boolean);
+
 const ToggleSwitch = memo(({ id, checked, onChange, label, description }: {
   id: string;
   checked: boolean;
@@ -46,8 +71,13 @@ const ToggleSwitch = memo(({ id, checked, onChange, label, description }: {
   </div>
 ));
 
-const SettingsPage = () => {
+const SettingsPage = ({ onLibrariesChange, onLibraryScanned }: {
+  onLibrariesChange?: (libraries: Library[]) => void;
+  onLibraryScanned?: (report: ScanReport) => void;
+}) => {
   const [settings, setSettings] = useState<Settings | null>(null);
+  const [libraries, setLibraries] = useState<Library[]>([]);
+  const [scanningId, setScanningId] = useState<number | null>(null); // 正在扫描的资料库
 
   useEffect(() => {
     // 加载已保存的设置
@@ -56,8 +86,54 @@ const SettingsPage = () => {
       setSettings(settings);
     };
     loadSettings();
+    invoke<Library[]>('list_libraries').then(setLibraries);
   }, []);
 
+  const changeLibraries = (next: Library[]) => {
+    setLibraries(next);
+    onLibrariesChange?.(next);
+  };
+
+  /**
+   * 修改资料库设置后立即保存
+   */
+  const handleLibraryChange = async (library: Library, changes: Partial<Library>) => {
+    try {
+      const updated = await invoke<Library>('update_library', { library: { ...library, ...changes } });
+      changeLibraries(libraries.map(item => item.id === updated.id ? updated : item));
+    } catch (error) {
+      simpleAlert.error('保存资料库设置时出错：' + error);
+    }
+  };
+
+  const handleRulesChange = (library: Library, changes: Partial<ScanRules>) =>
+    handleLibraryChange(library, { rules: { ...library.rules, ...changes } });
+
+  const handleRescanLibrary = async (library: Library) => {
+    setScanningId(library.id);
+    try {
+      const report = await invoke<ScanReport>('rescan_library', { id: library.id });
+      onLibraryScanned?.(report);
+      changeLibraries(await invoke<Library[]>('list_libraries'));
+    } catch (error) {
+      simpleAlert.error('扫描资料库时出错：' + error);
+    } finally {
+      setScanningId(null);
+    }
+  };
+
+  const handleRemoveLibrary = (library: Library) => {
+    simpleAlert.confirm(`确定要移除资料库吗？<br/>${library.path}`, '已添加的视频会保留，但不再自动同步', async (confirmed) => {
+      if (!confirmed) return;
+      try {
+        await invoke('remove_library', { id: library.id });
+        changeLibraries(libraries.filter(item => item.id !== library.id));
+      } catch (error) {
+        simpleAlert.error('移除资料库时出错：' + error);
+      }
+    });
+  };
+
   const handleInputChange = useCallback((e: React.ChangeEvent<HTMLInputElement | HTMLSelectElement>) => {
     const { id, value } = e.target;
     setSettings((prev) => prev ? { ...prev, [id]: value } : null);
@@ -157,6 +233,128 @@ const SettingsPage = () => {
             />
           </div>
         </div>
+        <div className="form-group">
+          <h4>扫描<br/><span>默认支持 mkv、webm、mp4、m4v、mov、3gp、avi、wmv、flv、ts、m2ts，以及 iso 镜像和 Blu-ray（BDMV）、DVD（VIDEO_TS）文件夹</span></h4>
+          <div>
+            <label htmlFor="extra-video-extensions">其他扩展名：</label>
+            <input
+              type="text"
+              id="extra_video_extensions"
+              placeholder="例如：rmvb, vob"
+              value={settings.extra_video_extensions ?? ''}
+              onChange={handleInputChange}
+            />
+          </div>
+        </div>
+        <div className="form-group">
+          <h4>资料库<br/><span>资料库中新增、移动和删除的视频会自动同步</span></h4>
+          {libraries.length === 0 && <div className="library-empty">尚未添加资料库</div>}
+          {libraries.map(library => (
+            <div className={`library-item ${library.enabled ? '' : 'disabled'}`} key={library.id}>
+              <div className="library-header">
+                <input
+                  type="text"
+                  defaultValue={library.name}
+                  placeholder="资料库名称"
+                  onBlur={(e) => e.target.value !== library.name && handleLibraryChange(library, { name: e.target.value })}
+                />
+                <label>
+                  <input
+                    type="checkbox"
+                    checked={library.enabled}
+                    onChange={(e) => handleLibraryChange(library, { enabled: e.target.checked })}
+                  /> 启用
+                </label>
+              </div>
+              <div className="library-path" title={library.path}>{library.path}</div>
+              <div className="library-options">
+                <select
+                  value={library.content_type}
+                  onChange={(e) => handleLibraryChange(library, { content_type: e.target.value as Library['content_type'] })}
+                >
+                  {CONTENT_TYPE_OPTIONS.map(option => (
+                    <option key={option.value} value={option.value}>{option.label}</option>
+                  ))}
+                </select>
+                <select
+                  value={library.language}
+                  onChange={(e) => handleLibraryChange(library, { language: e.target.value })}
+                >
+                  {METADATA_LANGUAGE_OPTIONS.map(option => (
+                    <option key={option.value} value={option.value}>{option.label}</option>
+                  ))}
+                </select>
+              </div>
+              <details className="library-rules">
+                <summary>扫描规则</summary>
+                <label>排除（每行一个，glob 或以 re: 开头的正则表达式）：</label>
+                <textarea
+                  rows={3}
+                  defaultValue={library.rules.exclude_patterns.join('\n')}
+                  placeholder={'**/Extras/**\n*.partial.mkv'}
+                  onBlur={(e) => handleRulesChange(library, { exclude_patterns: parsePatterns(e.target.value) })}
+                />
+                <label>仅包含（为空时不限制）：</label>
+                <textarea
+                  rows={2}
+                  defaultValue={library.rules.include_patterns.join('\n')}
+                  placeholder="Movies/**"
+                  onBlur={(e) => handleRulesChange(library, { include_patterns: parsePatterns(e.target.value) })}
+                />
+                <div className="library-options">
+                  <label>最小文件 (MB)：</label>
+                  <input
+                    type="number"
+                    min={0}
+                    defaultValue={library.rules.min_file_size / MB}
+                    onBlur={(e) => handleRulesChange(library, { min_file_size: Math.max(0, Math.round(Number(e.target.value) * MB)) })}
+                  />
+                  <label>最大层级：</label>
+                  <input
+                    type="number"
+                    min={0}
+                    title="0 表示不限制"
+                    defaultValue={library.rules.max_depth}
+                    onBlur={(e) => handleRulesChange(library, { max_depth: Math.max(0, Math.floor(Number(e.target.value))) })}
+                  />
+                </div>
+                <div className="library-options">
+                  <label>
+                    <input
+                      type="checkbox"
+                      checked={library.rules.skip_hidden}
+                      onChange={(e) => handleRulesChange(library, { skip_hidden: e.target.checked })}
+                    /> 跳过隐藏目录
+                  </label>
+                  <label>
+                    <input
+                      type="checkbox"
+                      checked={library.rules.skip_samples}
+                      onChange={(e) => handleRulesChange(library, { skip_samples: e.target.checked })}
+                    /> 跳过示例视频
+                  </label>
+                  <label>
+                    <input
+                      type="checkbox"
+                      checked={library.rules.follow_symlinks}
+                      onChange={(e) => handleRulesChange(library, { follow_symlinks: e.target.checked })}
+                    /> 跟随符号链接
+                  </label>
+                </div>
+                <span className="library-rules-hint">包含 .ignore 或 .nomedia 文件的目录不会被扫描</span>
+              </details>
+              <div className="library-footer">
+                <span>
+                  最后扫描：{library.last_scan_time > 0 ? new Date(library.last_scan_time * 1000).toLocaleString() : '从未扫描'}
+                </span>
+                <button disabled={!library.enabled || scanningId !== null} onClick={() => handleRescanLibrary(library)}>
+                  {scanningId === library.id ? '扫描中...' : '重新扫描'}
+                </button>
+                <button onClick={() => handleRemoveLibrary(library)}>移除</button>
+              </div>
+            </div>
+          ))}
+        </div>
         <button className="save-settings" onClick={handleSave}>保存设置</button>
       </div>
     </div>
@@ -20,6 +20,134 @@ export interface VideoInfo {
     episode: number;
     episode_title: string;
     episode_overview: string;
+    file_size: number;
+    mtime: number;
+    fingerprint: string;
+    status: 'ok' | 'missing' | 'offline';
+    /** 所属资料库ID，不属于任何资料库时为 0 */
+    library_id: number;
+    /** 多段视频中非第一段所属的主视频ID，其他视频为空 */
+    part_of: string;
+    /** 多段视频中的分段序号，其他视频为 0 */
+    part_number: number;
+    /** 多集合一的文件中最后一集的集数，单集文件为 0 */
+    episode_end: number;
+    /** 按播出日期命名的剧集的播出日期（YYYY-MM-DD），其他视频为空 */
+    air_date: string;
+    /** 多段视频的各段（包括主视频本身），只在主视频中返回 */
+    parts: VideoPart[];
+}
+
+/**
+ * 视频附加内容（预告片、花絮等）接口
+ */
+export interface VideoExtra {
+    id: string;
+    /** 所属视频ID，尚未找到所属视频时为空 */
+    video_id: string;
+    extra_type: 'trailer' | 'featurette' | 'behindthescenes' | 'deleted' | 'interview' | 'scene' | 'short' | 'other';
+    title: string;
+    path: string;
+    duration: string;
+    file_size: number;
+    mtime: number;
+    create_time: number;
+}
+
+/**
+ * 多段视频中的一段接口
+ */
+export interface VideoPart {
+    id: string;
+    path: string;
+    duration: string;
+    part_number: number;
+    status: 'ok' | 'missing' | 'offline';
+}
+
+/**
+ * 资料库健康报告接口
+ */
+export interface HealthReport {
+    total: number;
+    ok: number;
+    missing: VideoInfo[];
+    offline: VideoInfo[];
+}
+
+/**
+ * 扫描结果接口
+ */
+export interface ScanReport {
+    added: VideoInfo[];
+    updated: VideoInfo[];
+    moved: { old_id: string; video: VideoInfo }[];
+    /** 新增或变化的附加内容，以前被当作视频入库的同一文件已删除 */
+    extras: VideoExtra[];
+    unchanged: number;
+    /** 扫描是否被取消，取消前已处理的视频仍然保留 */
+    cancelled: boolean;
+}
+
+/**
+ * 扫描进度接口
+ */
+export interface ScanProgress {
+    root: string;
+    discovered: number;
+    discovery_done: boolean;
+    processed: number;
+    errors: number;
+    current: string;
+}
+
+/**
+ * 扫描失败的文件接口
+ */
+export interface ScanError {
+    path: string;
+    error: string;
+}
+
+/**
+ * 资料库接口
+ */
+export interface Library {
+    id: number;
+    path: string;
+    name: string;
+    content_type: 'movie' | 'series' | 'mixed';
+    /** TMDb 语言代码，为空时使用默认语言 */
+    language: string;
+    enabled: boolean;
+    last_scan_time: number;
+    create_time: number;
+    rules: ScanRules;
+}
+
+/**
+ * 资料库扫描规则接口
+ */
+export interface ScanRules {
+    /** 只入库匹配的文件，glob 或以 re: 开头的正则表达式，为空时不限制 */
+    include_patterns: string[];
+    /** 排除匹配的文件和目录，如 **\/Extras/**、*.partial.mkv */
+    exclude_patterns: string[];
+    /** 最小文件大小（字节），0 表示不限制 */
+    min_file_size: number;
+    /** 文件相对根目录的最大层级，0 表示不限制 */
+    max_depth: number;
+    skip_hidden: boolean;
+    follow_symlinks: boolean;
+    skip_samples: boolean;
+}
+
+/**
+ * 新添加的资料库及首次扫描结果接口
+ */
+export interface AddedLibrary {
+    library: Library;
+    report: ScanReport;
 }
 
 /**
@@ -33,4 +161,67 @@ export interface Settings {
     tmdb_api_key: string;
     auto_tmdb: boolean;
     auto_tmdb_poster: boolean;
-}
\ No newline at end of file
+    extra_video_extensions: string;
+}
+
+/**
+ * 视频轨道信息接口
+ */
+export interface VideoTrack {
+    video_id: string;
+    track_index: number;
+    track_type: 'video' | 'audio' | 'subtitle' | 'other';
+    codec: string;
+    codec_name: string;
+    language: string;
+    language_ietf: string;
+    name: string;
+    is_default: boolean;
+    is_forced: boolean;
+    width: number;
+    height: number;
+    channels: number;
+    sampling_frequency: number;
+}
+
+/**
+ * 视频章节信息接口
+ */
+export interface VideoChapter {
+    video_id: string;
+    chapter_index: number;
+    start_time: number;
+    end_time: number;
+    title: string;
+    titles: Record<string, string>;
+}
+
+/**
+ * 视频画质概况接口
+ */
+export interface VideoQuality {
+    video_id: string;
+    resolution: string;
+    width: number;
+    height: number;
+    bit_depth: number;
+    hdr_format: 'SDR' | 'HDR10' | 'HLG' | 'Dolby Vision';
+    dolby_vision_profile: number;
+}
+
+/**
+ * 视频发布信息接口，由文件名解析
+ */
+export interface VideoRelease {
+    video_id: string;
+    title: string;
+    year: string;
+    resolution: string;
+    source: string;
+    video_codec: string;
+    audio_codec: string;
+    hdr: boolean;
+    edition: string;
+    release_group: string;
+    repack: boolean;
+}
@@ -1,6 +1,6 @@
 import { convertFileSrc, invoke } from "@tauri-apps/api/core";
 import simpleAlert from "./components/simplealert";
-import { VideoInfo } from "./types";
+import { VideoInfo, VideoQuality, VideoRelease, VideoTrack } from "./types";
 
 export const videocommon = {
   /**
@@ -26,15 +26,132 @@ export const videocommon = {
   /**
    * 播放视频，调用Rust方法
    * @param video VideoInfo
+   * @param startTime 开始播放的位置（秒），用于章节跳转
    */
-  handlePlayVideo: async (video: VideoInfo) => {
+  handlePlayVideo: async (video: VideoInfo, startTime?: number) => {
     try {
-      await invoke('play_video', { video: video });
+      await invoke('play_video', { video: video, startTime: startTime ?? null });
     } catch (error) {
       console.error('Error playing video:', error);
       simpleAlert.error('播放视频时出错：' + error);
     }
   },
+  /**
+   * 根据分辨率返回清晰度标签，如 2160p、1080p
+   * @param width 宽度
+   * @param height 高度
+   * @returns string
+   */
+  resolutionLabel: (width: number, height: number): string => {
+    if (width >= 3200 || height >= 2000) return '2160p';
+    if (width >= 2200 || height >= 1300) return '1440p';
+    if (width >= 1600 || height >= 900) return '1080p';
+    if (width >= 1100 || height >= 650) return '720p';
+    if (height > 0) return `${height}p`;
+    return '';
+  },
+  /**
+   * 生成轨道摘要，如 “2160p HEVC · 3 条音轨 · 内嵌字幕 chi/eng”
+   * @param tracks 轨道列表
+   * @returns string
+   */
+  formatTrackSummary: (tracks: VideoTrack[]): string => {
+    const parts: string[] = [];
+    const video = tracks.find(track => track.track_type === 'video');
+    if (video) {
+      parts.push(`${videocommon.resolutionLabel(video.width, video.height)} ${video.codec_name}`.trim());
+    }
+    const audioCount = tracks.filter(track => track.track_type === 'audio').length;
+    if (audioCount > 0) {
+      parts.push(`${audioCount} 条音轨`);
+    }
+    const subtitleLanguages = [...new Set(tracks
+      .filter(track => track.track_type === 'subtitle')
+      .map(track => track.language_ietf || track.language))];
+    if (subtitleLanguages.length > 0) {
+      parts.push(`内嵌字幕 ${subtitleLanguages.join('/')}`);
+    }
+    return parts.join(' · ');
+  },
+  /**
+   * 画质排序权重：先比较清晰度，再比较 HDR 格式和位深
+   * @param quality 画质概况，未知时为 undefined
+   * @returns number
+   */
+  qualityRank: (quality?: VideoQuality): number => {
+    if (!quality) return 0;
+    const hdrRank = ['SDR', 'HLG', 'HDR10', 'Dolby Vision'].indexOf(quality.hdr_format);
+    return (parseInt(quality.resolution) || 0) * 1000 + Math.max(hdrRank, 0) * 100 + quality.bit_depth;
+  },
+  /**
+   * 发布信息排序权重：画质相同时先比较片源，再优先 REPACK / PROPER 修正版本
+   * @param release 发布信息，未知时为 undefined
+   * @returns number
+   */
+  releaseRank: (release?: VideoRelease): number => {
+    if (!release) return 0;
+    const sourceRank = ['CAM', 'DVD', 'HDTV', 'HDRip', 'WEBRip', 'WEB-DL', 'BluRay', 'Remux'].indexOf(release.source) + 1;
+    return sourceRank * 10 + (release.repack ? 1 : 0);
+  },
+  /**
+   * 生成发布信息摘要，如 “2160p · Remux · H.265 · TrueHD Atmos · HDR · Extended · FGT”
+   * @param release 发布信息
+   * @returns string
+   */
+  formatRelease: (release: VideoRelease): string => {
+    return [
+      release.resolution,
+      release.source,
+      release.video_codec,
+      release.audio_codec,
+      release.hdr ? 'HDR' : '',
+      release.edition,
+      release.repack ? 'REPACK' : '',
+      release.release_group,
+    ].filter(Boolean).join(' · ');
+  },
+  /**
+   * 判断视频画质是否符合筛选条件
+   * @param quality 画质概况
+   * @param filter 筛选条件：all、2160p、1080p、720p、sd、hdr、dv
+   * @returns boolean
+   */
+  matchQuality: (quality: VideoQuality | undefined, filter: string): boolean => {
+    if (filter === 'all') return true;
+    if (!quality) return false;
+    switch (filter) {
+      case 'hdr': return quality.hdr_format !== 'SDR';
+      case 'dv': return quality.hdr_format === 'Dolby Vision';
+      case '10bit': return quality.bit_depth >= 10;
+      case 'sd': return (parseInt(quality.resolution) || 0) < 720;
+      default: return quality.resolution === filter;
+    }
+  },
+  /**
+   * 将秒数格式化为章节时间，如 1:02:03、12:34
+   * @param seconds 秒数
+   * @returns string
+   */
+  formatChapterTime: (seconds: number): string => {
+    const total = Math.floor(seconds);
+    const hours = Math.floor(total / 3600);
+    const minutes = Math.floor((total % 3600) / 60);
+    const secs = (total % 60).toString().padStart(2, '0');
+    return hours > 0
+      ? `${hours}:${minutes.toString().padStart(2, '0')}:${secs}`
+      : `${minutes}:${secs}`;
+  },
+  /**
+   * 剧集编号，如 S01E02、S01E01-E03，按播出日期命名的剧集未匹配到 TMDb 时显示播出日期
+   * @param video VideoInfo
+   * @returns string
+   */
+  episodeLabel: (video: VideoInfo): string => {
+    const pad = (n: number) => n.toString().padStart(2, '0');
+    if (video.air_date && video.season > 1900) return video.air_date;
+    const range = video.episode_end > video.episode ? `-E${pad(video.episode_end)}` : '';
+    return `S${pad(video.season)}E${pad(video.episode)}${range}`;
+  },
   /**
    * 延时
    * @param duration 毫秒，默认1000